@@ -180,6 +180,22 @@ impl BitOrAssign<&BitBoard> for BitBoard {
     }
 }
 
+/// Set a single square, without wrapping it in `BitBoard::from_square` first.
+///
+/// ```
+/// use chess::{BitBoard, Square};
+///
+/// let mut bb = BitBoard::new(0);
+/// bb |= Square::E4;
+/// assert_eq!(bb, BitBoard::from_square(Square::E4));
+/// ```
+impl BitOrAssign<Square> for BitBoard {
+    #[inline]
+    fn bitor_assign(&mut self, other: Square) {
+        *self |= BitBoard::from_square(other);
+    }
+}
+
 // Impl BitXor Assign
 impl BitXorAssign for BitBoard {
     #[inline]
@@ -283,11 +299,53 @@ impl BitBoard {
     }
 
     /// Construct a new `BitBoard` with a particular `Square` set
+    ///
+    /// ```
+    /// use chess::{BitBoard, Square};
+    ///
+    /// let bb = BitBoard::from_square(Square::C2);
+    /// assert_eq!(bb.popcnt(), 1);
+    /// assert_eq!(bb.to_square(), Square::C2);
+    /// ```
     #[inline]
     pub fn from_square(sq: Square) -> BitBoard {
         BitBoard(1u64 << sq.to_int())
     }
 
+    /// Construct a new `BitBoard` with every square on a particular `Rank` set.  This is the same
+    /// as `get_rank`.
+    ///
+    /// ```
+    /// use chess::{get_rank, BitBoard, Rank, Square};
+    ///
+    /// let bb = BitBoard::from_rank(Rank::Second);
+    /// assert_eq!(bb, get_rank(Rank::Second));
+    /// assert_eq!(bb.popcnt(), 8);
+    /// assert_eq!(bb.to_square(), Square::A2);
+    /// ```
+    #[inline]
+    pub fn from_rank(rank: Rank) -> BitBoard {
+        // Computed directly rather than with `get_rank`, because the build script that generates
+        // the tables uses this file too.
+        BitBoard(0xFF << (8 * rank.to_index()))
+    }
+
+    /// Construct a new `BitBoard` with every square on a particular `File` set.  This is the same
+    /// as `get_file`.
+    ///
+    /// ```
+    /// use chess::{get_file, BitBoard, File, Square};
+    ///
+    /// let bb = BitBoard::from_file(File::C);
+    /// assert_eq!(bb, get_file(File::C));
+    /// assert_eq!(bb.popcnt(), 8);
+    /// assert_eq!(bb.to_square(), Square::C1);
+    /// ```
+    #[inline]
+    pub fn from_file(file: File) -> BitBoard {
+        BitBoard(0x0101_0101_0101_0101 << file.to_index())
+    }
+
     /// Convert an `Option<Square>` to an `Option<BitBoard>`
     #[inline]
     pub fn from_maybe_square(sq: Option<Square>) -> Option<BitBoard> {
@@ -312,6 +370,68 @@ impl BitBoard {
         BitBoard(self.0.swap_bytes())
     }
 
+    /// Iterate over every `Square` set, least-significant first, along with a `BitBoard` of the
+    /// squares that come after it.
+    ///
+    /// ```
+    /// use chess::{BitBoard, Square, EMPTY};
+    ///
+    /// let bb = BitBoard::from_square(Square::A1)
+    ///     | BitBoard::from_square(Square::C1)
+    ///     | BitBoard::from_square(Square::E4);
+    ///
+    /// let items: Vec<(Square, BitBoard)> = bb.carry_iter().collect();
+    /// assert_eq!(items.len(), 3);
+    /// assert_eq!(items[0], (Square::A1, bb ^ BitBoard::from_square(Square::A1)));
+    /// assert_eq!(items[1].1, BitBoard::from_square(Square::E4));
+    /// assert_eq!(items[2], (Square::E4, EMPTY));
+    /// ```
+    #[inline]
+    pub fn carry_iter(self) -> impl Iterator<Item = (Square, BitBoard)> {
+        let mut rest = self;
+        std::iter::from_fn(move || {
+            let sq = rest.next()?;
+            Some((sq, rest))
+        })
+    }
+
+    /// Iterate over every subset of the squares set in this `BitBoard`, starting with `EMPTY` and
+    /// ending with the `BitBoard` itself.  A `BitBoard` with N squares set has 2^N subsets.  This
+    /// is handy for enumerating blocker configurations when building magic tables.
+    ///
+    /// ```
+    /// use chess::{BitBoard, Square, EMPTY};
+    ///
+    /// let bb = BitBoard::from_square(Square::B1) | BitBoard::from_square(Square::C1);
+    /// let subsets: Vec<BitBoard> = bb.subsets().collect();
+    ///
+    /// assert_eq!(
+    ///     subsets,
+    ///     vec![
+    ///         EMPTY,
+    ///         BitBoard::from_square(Square::B1),
+    ///         BitBoard::from_square(Square::C1),
+    ///         bb,
+    ///     ]
+    /// );
+    /// assert_eq!(EMPTY.subsets().count(), 1);
+    /// ```
+    #[inline]
+    pub fn subsets(self) -> impl Iterator<Item = BitBoard> {
+        // the carry-rippler: subtracting the mask carries through the bits outside of it
+        let mut next = Some(0u64);
+        std::iter::from_fn(move || {
+            let subset = next?;
+            let following = subset.wrapping_sub(self.0) & self.0;
+            next = if following == 0 {
+                None
+            } else {
+                Some(following)
+            };
+            Some(BitBoard(subset))
+        })
+    }
+
     /// Convert this `BitBoard` to a `usize` (for table lookups)
     #[inline]
     pub fn to_size(&self, rightshift: u8) -> usize {
@@ -334,3 +454,65 @@ impl Iterator for BitBoard {
         }
     }
 }
+
+/// Iterate over every `Square` set in a borrowed `BitBoard`, leaving the original untouched.
+///
+/// ```
+/// use chess::{get_rank, Rank, Square};
+///
+/// let first_rank = get_rank(Rank::First);
+///
+/// let mut squares = vec![];
+/// for sq in &first_rank {
+///     squares.push(sq);
+/// }
+///
+/// assert_eq!(squares.len(), 8);
+/// assert_eq!(squares[0], Square::A1);
+/// assert_eq!(squares[7], Square::H1);
+/// assert_eq!(first_rank.popcnt(), 8);
+/// ```
+impl IntoIterator for &BitBoard {
+    type Item = Square;
+    type IntoIter = BitBoard;
+
+    #[inline]
+    fn into_iter(self) -> BitBoard {
+        *self
+    }
+}
+
+/// Set every `Square` from an iterator.
+///
+/// ```
+/// use chess::{BitBoard, Square};
+///
+/// let mut bb = BitBoard::from_square(Square::A1);
+/// bb.extend([Square::D4, Square::E5, Square::A1].iter().copied());
+///
+/// assert_eq!(bb.popcnt(), 3);
+/// assert_eq!(bb.into_iter().collect::<Vec<_>>(), vec![Square::A1, Square::D4, Square::E5]);
+/// ```
+impl Extend<Square> for BitBoard {
+    fn extend<T: IntoIterator<Item = Square>>(&mut self, iter: T) {
+        for sq in iter {
+            *self |= sq;
+        }
+    }
+}
+
+#[test]
+fn test_subsets() {
+    let bb = BitBoard::from_square(Square::A1)
+        | BitBoard::from_square(Square::D4)
+        | BitBoard::from_square(Square::H8);
+
+    let subsets: Vec<BitBoard> = bb.subsets().collect();
+    assert_eq!(subsets.len(), 8);
+    assert!(subsets.iter().all(|s| *s & !bb == EMPTY));
+    for i in 0..subsets.len() {
+        for j in (i + 1)..subsets.len() {
+            assert_ne!(subsets[i], subsets[j]);
+        }
+    }
+}
@@ -1,19 +1,22 @@
 use crate::bitboard::{BitBoard, EMPTY};
 use crate::board_builder::BoardBuilder;
-use crate::castle_rights::CastleRights;
+use crate::castle_rights::{CastleRights, CastleSide};
 use crate::chess_move::ChessMove;
 use crate::color::{Color, ALL_COLORS, NUM_COLORS};
 use crate::error::Error;
 use crate::file::File;
 use crate::magic::{
-    between, get_adjacent_files, get_bishop_rays, get_castle_moves, get_file, get_king_moves,
-    get_knight_moves, get_pawn_attacks, get_pawn_dest_double_moves, get_pawn_source_double_moves,
-    get_rank, get_rook_rays,
+    between, get_adjacent_files, get_bishop_moves, get_bishop_rays, get_castle_moves, get_file,
+    get_king_moves, get_knight_moves, get_pawn_attacks, get_pawn_dest_double_moves,
+    get_pawn_source_double_moves, get_rank, get_rook_moves, get_rook_rays,
 };
 use crate::movegen::*;
-use crate::piece::{Piece, ALL_PIECES, NUM_PIECES};
+use crate::piece::{Piece, PieceValues, ALL_PIECES, NUM_PIECES};
+use crate::rank::Rank;
 use crate::square::{Square, ALL_SQUARES};
 use crate::zobrist::Zobrist;
+#[cfg(feature = "rand")]
+use rand::Rng;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -21,7 +24,24 @@ use std::mem;
 use std::str::FromStr;
 
 /// A representation of a chess board.  That's why you're here, right?
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///
+/// Two boards are `==` (and hash the same) when they have the same pieces on the same squares,
+/// the same side to move, the same castle rights, and the same en passant square.  The en passant
+/// square is only recorded when an enemy pawn is in place to make the capture, so it matches the
+/// FIDE rules for repeated positions.  The halfmove clock and fullmove number from a FEN are not
+/// part of a `Board` at all, so boards that differ only in those are equal, which is what you
+/// want for repetition tables.  If check counting is turned on (see `track_checks`), the counts
+/// are compared as well, but they don't go into the hash.
+///
+/// ```
+/// use chess::Board;
+/// use std::str::FromStr;
+///
+/// let a = Board::from_str("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+/// let b = Board::from_str("4k3/8/8/8/8/8/4P3/4K3 w - - 37 80").unwrap();
+/// assert_eq!(a, b);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Board {
     pieces: [BitBoard; NUM_PIECES],
     color_combined: [BitBoard; NUM_COLORS],
@@ -31,6 +51,9 @@ pub struct Board {
     pinned: BitBoard,
     checkers: BitBoard,
     hash: u64,
+    pawn_hash: u64,
+    non_pawn_material: [i32; NUM_COLORS],
+    check_counts: Option<[u8; NUM_COLORS]>,
     en_passant: Option<Square>,
 }
 
@@ -42,8 +65,59 @@ pub enum BoardStatus {
     Checkmate,
 }
 
+/// The state that makes up a `Board`, laid bare.  Everything else a `Board` stores can be
+/// recomputed from these.  See `Board::raw_parts` and `Board::from_raw_parts`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BoardParts {
+    /// Where each type of piece is, indexed by `Piece::to_index()`.
+    pub pieces: [BitBoard; NUM_PIECES],
+    /// Where each color's pieces are, indexed by `Color::to_index()`.
+    pub color_combined: [BitBoard; NUM_COLORS],
+    pub side_to_move: Color,
+    /// The castle rights of each color, indexed by `Color::to_index()`.
+    pub castle_rights: [CastleRights; NUM_COLORS],
+    /// The square of the pawn that can be captured en passant, as in `Board::en_passant_victim`.
+    pub en_passant: Option<Square>,
+    /// The hash of the board, as in `Board::get_hash`.
+    pub hash: u64,
+}
+
+/// The parts of `Board::get_hash`, kept apart so a bad hash can be traced to the part that went
+/// wrong.  XORing all four together gives the hash back.  See `Board::zobrist_breakdown`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ZobristBreakdown {
+    /// The pieces and where they stand.
+    pub pieces: u64,
+    /// Both colors' castle rights.
+    pub castling: u64,
+    /// The en passant file, or 0 if there isn't one.
+    pub en_passant: u64,
+    /// Whether black is to move, or 0 if white is.
+    pub side_to_move: u64,
+}
+
+/// The game phase of a position with all its pieces, and the most `Board::phase` returns.
+pub const MAX_PHASE: i32 = 24;
+
+/// How many moves ahead `Board::is_perpetual_check_candidate` looks.
+const PERPETUAL_CHECK_DEPTH: usize = 2;
+
+/// Maps a `(file, rank)` pair onto its image under one of the board's symmetries.
+type Symmetry = fn(usize, usize) -> (usize, usize);
+
 /// Construct the initial position.
 impl Default for Board {
+    /// The standard starting position.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// let start = Board::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+    ///     .expect("Valid FEN");
+    /// assert_eq!(Board::default(), start);
+    /// assert_eq!(Board::default().get_hash(), start.get_hash());
+    /// ```
     #[inline]
     fn default() -> Board {
         Board::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
@@ -52,8 +126,9 @@ impl Default for Board {
 }
 
 impl Hash for Board {
+    /// Feeds in `get_hash()`, so everything `==` looks at is covered.
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.hash.hash(state);
+        self.get_hash().hash(state);
     }
 }
 
@@ -70,6 +145,9 @@ impl Board {
             pinned: EMPTY,
             checkers: EMPTY,
             hash: 0,
+            pawn_hash: 0,
+            non_pawn_material: [0; NUM_COLORS],
+            check_counts: None,
             en_passant: None,
         }
     }
@@ -99,6 +177,189 @@ impl Board {
         Board::from_str(&fen).ok()
     }
 
+    /// Construct a board from a list of pieces and the side to move.  Neither side has any
+    /// castle rights, and there is no en-passant square.
+    ///
+    /// This is a shortcut for `BoardBuilder::setup(...)` when setting up sparse positions.
+    ///
+    /// ```
+    /// use chess::{Board, Square, Piece, Color, MoveGen};
+    /// # use chess::Error;
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let board = Board::from_pieces(
+    ///     &[
+    ///         (Square::E1, Piece::King, Color::White),
+    ///         (Square::A1, Piece::Rook, Color::White),
+    ///         (Square::E8, Piece::King, Color::Black),
+    ///     ],
+    ///     Color::White,
+    /// )?;
+    ///
+    /// // 5 king moves and 10 rook moves
+    /// assert_eq!(MoveGen::new_legal(&board).len(), 15);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_pieces(
+        pieces: &[(Square, Piece, Color)],
+        side_to_move: Color,
+    ) -> Result<Board, Error> {
+        BoardBuilder::setup(
+            pieces,
+            side_to_move,
+            CastleRights::NoRights,
+            CastleRights::NoRights,
+            None,
+        )
+        .try_into()
+    }
+
+    /// Build a board from `BoardBuilder` for puzzle composition, with relaxed validation.
+    ///
+    /// Unlike `Board::try_from`, the side not to move may be in check.  Everything structural is
+    /// still checked: each side has exactly one king, the kings don't touch, no pawns stand on
+    /// the first or eighth rank, and the castle rights and en passant square make sense.
+    ///
+    /// A board whose side not to move is in check is only good for display and analysis: the
+    /// side to move can capture the king, and making that move will panic.
+    ///
+    /// ```
+    /// use chess::{Board, BoardBuilder, Color, Piece, Square};
+    /// use std::convert::TryFrom;
+    ///
+    /// // White to move, but the black king is already in check
+    /// let mut builder = BoardBuilder::new();
+    /// builder
+    ///     .piece(Square::E1, Piece::King, Color::White)
+    ///     .piece(Square::E2, Piece::Rook, Color::White)
+    ///     .piece(Square::E8, Piece::King, Color::Black);
+    ///
+    /// assert!(Board::try_from(&builder).is_err());
+    /// assert!(Board::from_builder_lenient(builder).is_ok());
+    /// ```
+    pub fn from_builder_lenient(builder: BoardBuilder) -> Result<Board, Error> {
+        let board = Board::from_builder_unchecked(&builder);
+        let back_ranks = get_rank(Rank::First) | get_rank(Rank::Eighth);
+
+        if board.is_sane_inner(true) && board.pieces(Piece::Pawn) & back_ranks == EMPTY {
+            Ok(board)
+        } else {
+            Err(Error::InvalidBoard)
+        }
+    }
+
+    /// Build a board from a `BoardBuilder`, without checking that it makes sense.
+    fn from_builder_unchecked(fen: &BoardBuilder) -> Board {
+        let mut board = Board::new();
+
+        for sq in ALL_SQUARES.iter() {
+            if let Some((piece, color)) = fen[*sq] {
+                board.xor(piece, BitBoard::from_square(*sq), color);
+            }
+        }
+
+        board.side_to_move = fen.get_side_to_move();
+
+        if let Some(ep) = fen.get_en_passant() {
+            board.side_to_move = !board.side_to_move;
+            board.set_ep(ep);
+            board.side_to_move = !board.side_to_move;
+        }
+
+        #[allow(deprecated)]
+        board.add_castle_rights(Color::White, fen.get_castle_rights(Color::White));
+        #[allow(deprecated)]
+        board.add_castle_rights(Color::Black, fen.get_castle_rights(Color::Black));
+
+        board.update_pin_info();
+        board
+    }
+
+    /// Take this board apart, for storing it compactly or keeping your own incrementally
+    /// updated copy.
+    ///
+    /// ```
+    /// use chess::{Board, Color};
+    ///
+    /// let parts = Board::default().raw_parts();
+    /// assert_eq!(parts.side_to_move, Color::White);
+    /// assert_eq!(parts.hash, Board::default().get_hash());
+    /// ```
+    pub fn raw_parts(&self) -> BoardParts {
+        BoardParts {
+            pieces: self.pieces,
+            color_combined: self.color_combined,
+            side_to_move: self.side_to_move,
+            castle_rights: self.castle_rights,
+            en_passant: self.en_passant,
+            hash: self.get_hash(),
+        }
+    }
+
+    /// Put a board back together from its parts.
+    ///
+    /// The parts are checked: the bitboards must agree with each other, the en passant square
+    /// must hold a pawn that can be captured, the hash must match, and the position must pass
+    /// `is_sane`.  If not, this returns `Error::InvalidBoard`.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let parts = Board::default().raw_parts();
+    /// assert_eq!(Board::from_raw_parts(parts).unwrap(), Board::default());
+    ///
+    /// let mut bad_hash = parts;
+    /// bad_hash.hash ^= 1;
+    /// assert!(Board::from_raw_parts(bad_hash).is_err());
+    /// ```
+    pub fn from_raw_parts(parts: BoardParts) -> Result<Board, Error> {
+        let mut board = Board::new();
+
+        let mut occupied = EMPTY;
+        for piece in ALL_PIECES.iter() {
+            let bb = parts.pieces[piece.to_index()];
+            if bb & occupied != EMPTY {
+                return Err(Error::InvalidBoard);
+            }
+            occupied |= bb;
+
+            for color in ALL_COLORS.iter() {
+                for sq in bb & parts.color_combined[color.to_index()] {
+                    board.xor(*piece, BitBoard::from_square(sq), *color);
+                }
+            }
+        }
+        if board.color_combined != parts.color_combined || board.combined != occupied {
+            return Err(Error::InvalidBoard);
+        }
+
+        board.side_to_move = parts.side_to_move;
+        board.castle_rights = parts.castle_rights;
+        if let Some(ep) = parts.en_passant {
+            if board.piece_on(ep) != Some(Piece::Pawn)
+                || board.color_on(ep) != Some(!board.side_to_move)
+                || ep.get_rank() != (!board.side_to_move).to_fourth_rank()
+            {
+                return Err(Error::InvalidBoard);
+            }
+            board.side_to_move = !board.side_to_move;
+            board.set_ep(ep);
+            board.side_to_move = !board.side_to_move;
+            if board.en_passant != parts.en_passant {
+                return Err(Error::InvalidBoard);
+            }
+        }
+
+        board.update_pin_info();
+
+        if board.is_sane() && board.get_hash() == parts.hash {
+            Ok(board)
+        } else {
+            Err(Error::InvalidBoard)
+        }
+    }
+
     #[deprecated(
         since = "3.0.0",
         note = "please use the MoveGen structure instead.  It is faster and more idiomatic."
@@ -114,6 +375,25 @@ impl Board {
         size
     }
 
+    /// Clear `buf` and fill it with every legal move in this position.
+    ///
+    /// This lets a caller keep one `Vec` around and reuse its allocation from node to node.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove};
+    ///
+    /// let board = Board::default();
+    /// let mut moves: Vec<ChessMove> = Vec::with_capacity(256);
+    ///
+    /// board.legal_moves_into(&mut moves);
+    /// assert_eq!(moves.len(), 20);
+    /// ```
+    #[inline]
+    pub fn legal_moves_into(&self, buf: &mut Vec<ChessMove>) {
+        buf.clear();
+        buf.extend(MoveGen::new_legal(self));
+    }
+
     /// Is this game Ongoing, is it Stalemate, or is it Checkmate?
     ///
     /// ```
@@ -155,965 +435,5462 @@ impl Board {
     /// ```
     #[inline]
     pub fn status(&self) -> BoardStatus {
-        let moves = MoveGen::new_legal(&self).len();
-        match moves {
-            0 => {
-                if self.checkers == EMPTY {
-                    BoardStatus::Stalemate
-                } else {
-                    BoardStatus::Checkmate
-                }
-            }
-            _ => BoardStatus::Ongoing,
+        if self.checkers == EMPTY && self.has_obvious_move() {
+            return BoardStatus::Ongoing;
+        }
+
+        if self.has_legal_moves() {
+            BoardStatus::Ongoing
+        } else if self.checkers == EMPTY {
+            BoardStatus::Stalemate
+        } else {
+            BoardStatus::Checkmate
         }
     }
 
-    /// Grab the "combined" `BitBoard`.  This is a `BitBoard` with every piece.
+    /// Is this a dead position, where no sequence of legal moves can end in checkmate?  Under
+    /// the FIDE rules, the game is drawn immediately.
     ///
-    /// ```
-    /// use chess::{Board, BitBoard, Rank, get_rank};
+    /// Only the material on the board is looked at, so this covers exactly these cases, with
+    /// no pawns, rooks or queens left:
     ///
-    /// let board = Board::default();
+    /// * King against king.
+    /// * King and knight against king.
+    /// * Kings and any number of bishops, on either side, as long as every bishop stands on the
+    ///   same color of square.  This includes king and bishop against king, king and bishop
+    ///   against king and bishop with both bishops on the same color, and several bishops of
+    ///   one color against a lone king.
     ///
-    /// let combined_should_be = get_rank(Rank::First) |
-    ///                          get_rank(Rank::Second) |
-    ///                          get_rank(Rank::Seventh) |
-    ///                          get_rank(Rank::Eighth);
+    /// Positions that are dead because the pawns are locked, or because the only moves left
+    /// lead to a draw, are not detected.
     ///
-    /// assert_eq!(*board.combined(), combined_should_be);
     /// ```
-    #[inline]
-    pub fn combined(&self) -> &BitBoard {
-        &self.combined
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// // Both bishops are on light squares
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/3BKB2 w - - 0 1").unwrap();
+    /// assert!(board.is_dead_position());
+    ///
+    /// // The bishop pair can mate
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+    /// assert!(!board.is_dead_position());
+    /// ```
+    pub fn is_dead_position(&self) -> bool {
+        const LIGHT_SQUARES: BitBoard = BitBoard(0x55AA_55AA_55AA_55AA);
+
+        let heavy = self.pieces(Piece::Pawn) | self.pieces(Piece::Rook) | self.pieces(Piece::Queen);
+        if heavy != EMPTY {
+            return false;
+        }
+
+        let knights = *self.pieces(Piece::Knight);
+        let bishops = *self.pieces(Piece::Bishop);
+        if knights != EMPTY {
+            // A lone knight can't mate, but a knight with anything else can, with help
+            return knights.popcnt() == 1 && bishops == EMPTY;
+        }
+
+        bishops & LIGHT_SQUARES == EMPTY || bishops & !LIGHT_SQUARES == EMPTY
     }
 
-    /// Grab the "color combined" `BitBoard`.  This is a `BitBoard` with every piece of a particular
-    /// color.
+    /// Does each side have exactly one bishop, with the two on opposite colors of square?
+    /// Endings with opposite-colored bishops are often drawn even a pawn or two up, so an
+    /// evaluation may want to scale its score down.  Other pieces are not looked at.
     ///
     /// ```
-    /// use chess::{Board, BitBoard, Rank, get_rank, Color};
+    /// use chess::Board;
+    /// use std::str::FromStr;
     ///
-    /// let board = Board::default();
+    /// // c1 is dark and c8 is light
+    /// let board = Board::from_str("2b1k3/pp6/8/8/8/8/PPP5/2B1K3 w - - 0 1").unwrap();
+    /// assert!(board.is_opposite_colored_bishops());
     ///
-    /// let white_pieces = get_rank(Rank::First) |
-    ///                    get_rank(Rank::Second);
+    /// assert!(!Board::default().is_opposite_colored_bishops());
+    /// ```
+    pub fn is_opposite_colored_bishops(&self) -> bool {
+        let white = self.pieces_of(Piece::Bishop, Color::White);
+        let black = self.pieces_of(Piece::Bishop, Color::Black);
+        white.popcnt() == 1
+            && black.popcnt() == 1
+            && white.to_square().color() != black.to_square().color()
+    }
+
+    /// A quick test for a legal move when not in check, without generating moves: can an
+    /// unpinned pawn push, or can the king step to a square nobody attacks?  `false` only means
+    /// that no such move was found.
+    fn has_obvious_move(&self) -> bool {
+        let color = self.side_to_move;
+        let pawns = self.pieces(Piece::Pawn) & self.color_combined(color) & !self.pinned;
+        if Board::shift_forward(pawns, color) & !self.combined() != EMPTY {
+            return true;
+        }
+
+        let king_moves = get_king_moves(self.king_square(color)) & !self.color_combined(color);
+        king_moves & !self.attacked_by(!color, *self.combined()) != EMPTY
+    }
+
+    /// Does this move put the opponent in check, either directly or by discovery?  `m` must be
+    /// legal.
     ///
-    /// let black_pieces = get_rank(Rank::Seventh) |
-    ///                    get_rank(Rank::Eighth);
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
     ///
-    /// assert_eq!(*board.color_combined(Color::White), white_pieces);
-    /// assert_eq!(*board.color_combined(Color::Black), black_pieces);
+    /// let board = Board::from_str("4k3/8/8/8/8/8/4B3/4R1K1 w - - 0 1").unwrap();
+    ///
+    /// // Moving the bishop off the e-file uncovers the rook
+    /// assert!(board.gives_check(ChessMove::new(Square::E2, Square::C4, None)));
+    /// assert!(!board.gives_check(ChessMove::new(Square::G1, Square::G2, None)));
     /// ```
-    #[inline]
-    pub fn color_combined(&self, color: Color) -> &BitBoard {
-        unsafe { self.color_combined.get_unchecked(color.to_index()) }
+    pub fn gives_check(&self, m: ChessMove) -> bool {
+        *self.make_move_new(m).checkers() != EMPTY
     }
 
-    /// Give me the `Square` the `color` king is on.
+    /// All the legal moves that give check, either directly or by discovery.
     ///
     /// ```
-    /// use chess::{Board, Square, Color};
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
     ///
-    /// let board = Board::default();
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
     ///
-    /// assert_eq!(board.king_square(Color::White), Square::E1);
-    /// assert_eq!(board.king_square(Color::Black), Square::E8);
+    /// assert_eq!(
+    ///     board.checking_moves(),
+    ///     vec![ChessMove::new(Square::A1, Square::A8, None)]
+    /// );
     /// ```
-    #[inline]
-    pub fn king_square(&self, color: Color) -> Square {
-        (self.pieces(Piece::King) & self.color_combined(color)).to_square()
+    pub fn checking_moves(&self) -> Vec<ChessMove> {
+        MoveGen::new_legal(self)
+            .filter(|m| self.gives_check(*m))
+            .collect()
     }
 
-    /// Grab the "pieces" `BitBoard`.  This is a `BitBoard` with every piece of a particular type.
+    /// Is this move reversible?  Pawn moves and captures can never be undone, and they reset the
+    /// halfmove clock used by the fifty-move rule.  Every other move is reversible.
     ///
     /// ```
-    /// use chess::{Board, BitBoard, Piece, Square};
-    ///
-    /// // The rooks should be in each corner of the board
-    /// let rooks = BitBoard::from_square(Square::A1) |
-    ///             BitBoard::from_square(Square::H1) |
-    ///             BitBoard::from_square(Square::A8) |
-    ///             BitBoard::from_square(Square::H8);
+    /// use chess::{Board, ChessMove, Square};
     ///
     /// let board = Board::default();
     ///
-    /// assert_eq!(*board.pieces(Piece::Rook), rooks);
+    /// assert!(board.is_reversible(ChessMove::new(Square::G1, Square::F3, None)));
+    /// assert!(!board.is_reversible(ChessMove::new(Square::E2, Square::E4, None)));
     /// ```
-    #[inline]
-    pub fn pieces(&self, piece: Piece) -> &BitBoard {
-        unsafe { self.pieces.get_unchecked(piece.to_index()) }
+    pub fn is_reversible(&self, m: ChessMove) -> bool {
+        self.piece_on(m.get_source()) != Some(Piece::Pawn) && self.piece_on(m.get_dest()).is_none()
     }
 
-    /// Grab the `CastleRights` for a particular side.
+    /// What piece does this move capture, if any?  This includes the pawn taken by an en passant
+    /// capture, which isn't on the destination square.
     ///
     /// ```
-    /// use chess::{Board, Square, CastleRights, Color, ChessMove};
-    ///
-    /// let move1 = ChessMove::new(Square::A2,
-    ///                            Square::A4,
-    ///                            None);
-    ///
-    /// let move2 = ChessMove::new(Square::E7,
-    ///                            Square::E5,
-    ///                            None);
+    /// use chess::{Board, ChessMove, Piece, Square};
+    /// use std::str::FromStr;
     ///
-    /// let move3 = ChessMove::new(Square::A1,
-    ///                            Square::A2,
-    ///                            None);
+    /// let board = Board::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
     ///
-    /// let move4 = ChessMove::new(Square::E8,
-    ///                            Square::E7,
-    ///                            None);
+    /// assert_eq!(
+    ///     board.captured_piece(ChessMove::new(Square::E5, Square::D6, None)),
+    ///     Some(Piece::Pawn)
+    /// );
+    /// assert_eq!(board.captured_piece(ChessMove::new(Square::E5, Square::E6, None)), None);
+    /// ```
+    pub fn captured_piece(&self, m: ChessMove) -> Option<Piece> {
+        let source = m.get_source();
+        let dest = m.get_dest();
+        if self.color_on(dest) == Some(!self.side_to_move) {
+            self.piece_on(dest)
+        } else if self.piece_on(source) == Some(Piece::Pawn)
+            && source.get_file() != dest.get_file()
+            && self.piece_on(dest).is_none()
+        {
+            Some(Piece::Pawn)
+        } else {
+            None
+        }
+    }
+
+    /// How much material does this move win straight away, before any recapture?  This is the
+    /// value of the captured piece, plus what a promotion gains over the pawn, using
+    /// `Piece::value`.  Quiescence search can use this for delta pruning, before paying for
+    /// `see_ge`.
     ///
-    /// let mut board = Board::default();
+    /// ```
+    /// use chess::{Board, ChessMove, Piece, Square};
+    /// use std::str::FromStr;
     ///
-    /// assert_eq!(board.castle_rights(Color::White), CastleRights::Both);
-    /// assert_eq!(board.castle_rights(Color::Black), CastleRights::Both);
+    /// let board = Board::from_str("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
     ///
-    /// board = board.make_move_new(move1)
-    ///              .make_move_new(move2)
-    ///              .make_move_new(move3)
-    ///              .make_move_new(move4);
+    /// // The rook, plus a queen for a pawn
+    /// let axb8q = ChessMove::new(Square::A7, Square::B8, Some(Piece::Queen));
+    /// assert_eq!(board.capture_gain(axb8q), 500 + 900 - 100);
     ///
-    /// assert_eq!(board.castle_rights(Color::White), CastleRights::KingSide);
-    /// assert_eq!(board.castle_rights(Color::Black), CastleRights::NoRights);
+    /// let e1e2 = ChessMove::new(Square::E1, Square::E2, None);
+    /// assert_eq!(board.capture_gain(e1e2), 0);
     /// ```
-    #[inline]
-    pub fn castle_rights(&self, color: Color) -> CastleRights {
-        unsafe { *self.castle_rights.get_unchecked(color.to_index()) }
-    }
-
-    /// Add castle rights for a particular side.  Note: this can create an invalid position.
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn add_castle_rights(&mut self, color: Color, add: CastleRights) {
-        unsafe {
-            *self.castle_rights.get_unchecked_mut(color.to_index()) =
-                self.castle_rights(color).add(add);
-        }
+    pub fn capture_gain(&self, m: ChessMove) -> i32 {
+        let captured = self.captured_piece(m).map_or(0, |piece| piece.value());
+        let promoted = m
+            .get_promotion()
+            .map_or(0, |piece| piece.value() - Piece::Pawn.value());
+        captured + promoted
     }
 
-    /// Remove castle rights for a particular side.
+    /// Does this move capture on `last_dest`, the square the opponent's last move landed on?
+    /// `Board` doesn't keep any history, so the caller has to supply that square.
     ///
     /// ```
-    /// use chess::{Board, CastleRights, Color};
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
     ///
-    /// let mut board = Board::default();
-    /// assert_eq!(board.castle_rights(Color::White), CastleRights::Both);
+    /// // Black just played ...Bxd4
+    /// let board = Board::from_str("4k3/8/8/1p6/P2b4/8/2N5/3QK3 w - - 0 1").unwrap();
     ///
-    /// board.remove_castle_rights(Color::White, CastleRights::KingSide);
-    /// assert_eq!(board.castle_rights(Color::White), CastleRights::QueenSide);
+    /// assert!(board.is_recapture(ChessMove::new(Square::C2, Square::D4, None), Square::D4));
+    /// assert!(!board.is_recapture(ChessMove::new(Square::A4, Square::B5, None), Square::D4));
     /// ```
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn remove_castle_rights(&mut self, color: Color, remove: CastleRights) {
-        unsafe {
-            *self.castle_rights.get_unchecked_mut(color.to_index()) =
-                self.castle_rights(color).remove(remove);
-        }
+    pub fn is_recapture(&self, m: ChessMove, last_dest: Square) -> bool {
+        m.get_dest() == last_dest && self.color_on(last_dest) == Some(!self.side_to_move)
     }
 
-    /// Who's turn is it?
+    /// All the legal moves of the piece on `sq`.  This is empty if there is no piece there, or if
+    /// it belongs to the side not to move.
     ///
     /// ```
-    /// use chess::{Board, Color};
+    /// use chess::{Board, ChessMove, Square};
     ///
-    /// let mut board = Board::default();
-    /// assert_eq!(board.side_to_move(), Color::White);
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(
+    ///     board.legal_moves_from(Square::B1),
+    ///     vec![
+    ///         ChessMove::new(Square::B1, Square::A3, None),
+    ///         ChessMove::new(Square::B1, Square::C3, None),
+    ///     ]
+    /// );
+    /// assert!(board.legal_moves_from(Square::E4).is_empty());
+    /// assert!(board.legal_moves_from(Square::B8).is_empty());
     /// ```
-    #[inline]
-    pub fn side_to_move(&self) -> Color {
-        self.side_to_move
+    pub fn legal_moves_from(&self, sq: Square) -> Vec<ChessMove> {
+        let mut movegen = MoveGen::new_legal(self);
+        movegen.set_source_mask(BitBoard::from_square(sq));
+        movegen.collect()
     }
 
-    /// Grab my `CastleRights`.
+    /// All the legal promotions to a knight, bishop or rook.  These come in `PROMOTION_PIECES`
+    /// order for each destination, the same as `MoveGen`.
     ///
     /// ```
-    /// use chess::{Board, Color, CastleRights};
-    ///
-    /// let mut board = Board::default();
-    /// board.remove_castle_rights(Color::White, CastleRights::KingSide);
-    /// board.remove_castle_rights(Color::Black, CastleRights::QueenSide);
+    /// use chess::{Board, ChessMove, Piece, Square};
+    /// use std::str::FromStr;
     ///
-    /// assert_eq!(board.my_castle_rights(), board.castle_rights(Color::White));
+    /// let board = Board::from_str("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.underpromotion_moves(),
+    ///     vec![
+    ///         ChessMove::new(Square::B7, Square::B8, Some(Piece::Rook)),
+    ///         ChessMove::new(Square::B7, Square::B8, Some(Piece::Bishop)),
+    ///         ChessMove::new(Square::B7, Square::B8, Some(Piece::Knight)),
+    ///     ]
+    /// );
     /// ```
-    #[inline]
-    pub fn my_castle_rights(&self) -> CastleRights {
-        self.castle_rights(self.side_to_move())
-    }
-
-    /// Add to my `CastleRights`.  Note: This can make the position invalid.
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn add_my_castle_rights(&mut self, add: CastleRights) {
-        let color = self.side_to_move();
-        #[allow(deprecated)]
-        self.add_castle_rights(color, add);
+    pub fn underpromotion_moves(&self) -> Vec<ChessMove> {
+        let color = self.side_to_move;
+        let mut movegen = MoveGen::new_legal(self);
+        movegen.set_source_mask(
+            self.pieces(Piece::Pawn)
+                & self.color_combined(color)
+                & BitBoard::from_rank(color.to_seventh_rank()),
+        );
+        movegen
+            .filter(|m| m.get_promotion().is_some_and(|p| p != Piece::Queen))
+            .collect()
     }
 
-    /// Remove some of my `CastleRights`.
+    /// All the legal moves, grouped by the type of piece making them.  The result is indexed by
+    /// `Piece::to_index()`.
     ///
     /// ```
-    /// use chess::{Board, CastleRights};
+    /// use chess::{Board, Piece};
     ///
-    /// let mut board = Board::default();
-    /// assert_eq!(board.my_castle_rights(), CastleRights::Both);
+    /// let moves = Board::default().legal_moves_by_piece();
     ///
-    /// board.remove_my_castle_rights(CastleRights::KingSide);
-    /// assert_eq!(board.my_castle_rights(), CastleRights::QueenSide);
+    /// assert_eq!(moves[Piece::Knight.to_index()].len(), 4);
+    /// assert!(moves[Piece::Queen.to_index()].is_empty());
     /// ```
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn remove_my_castle_rights(&mut self, remove: CastleRights) {
-        let color = self.side_to_move();
-        #[allow(deprecated)]
-        self.remove_castle_rights(color, remove);
+    pub fn legal_moves_by_piece(&self) -> [Vec<ChessMove>; NUM_PIECES] {
+        let mut result: [Vec<ChessMove>; NUM_PIECES] = Default::default();
+        for m in MoveGen::new_legal(self) {
+            // the source square of a legal move always has a piece on it
+            let piece = self.piece_on(m.get_source()).unwrap();
+            result[piece.to_index()].push(m);
+        }
+        result
     }
 
-    /// My opponents `CastleRights`.
+    /// How many legal moves each type of piece has, indexed by `Piece::to_index()`.  This is
+    /// cheaper than `legal_moves_by_piece` when only the numbers are needed, such as for mobility
+    /// terms in an evaluation.
     ///
     /// ```
-    /// use chess::{Board, Color, CastleRights};
+    /// use chess::{Board, Piece};
     ///
-    /// let mut board = Board::default();
-    /// board.remove_castle_rights(Color::White, CastleRights::KingSide);
-    /// board.remove_castle_rights(Color::Black, CastleRights::QueenSide);
+    /// let counts = Board::default().legal_move_counts();
     ///
-    /// assert_eq!(board.their_castle_rights(), board.castle_rights(Color::Black));
+    /// assert_eq!(counts[Piece::Pawn.to_index()], 16);
+    /// assert_eq!(counts[Piece::Knight.to_index()], 4);
+    /// assert_eq!(counts[Piece::King.to_index()], 0);
     /// ```
-    #[inline]
-    pub fn their_castle_rights(&self) -> CastleRights {
-        self.castle_rights(!self.side_to_move())
-    }
-
-    /// Add to my opponents `CastleRights`. Note: This can make the position invalid.
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn add_their_castle_rights(&mut self, add: CastleRights) {
-        let color = !self.side_to_move();
-        #[allow(deprecated)]
-        self.add_castle_rights(color, add)
+    pub fn legal_move_counts(&self) -> [usize; NUM_PIECES] {
+        let mut result = [0; NUM_PIECES];
+        for m in MoveGen::new_legal(self) {
+            // the source square of a legal move always has a piece on it
+            let piece = self.piece_on(m.get_source()).unwrap();
+            result[piece.to_index()] += 1;
+        }
+        result
     }
 
-    /// Remove some of my opponents `CastleRights`.
+    /// How many legal captures are there?  A capture that promotes counts once for each piece
+    /// it can promote to, the same as in `MoveGen`, and en passant captures are included.  This
+    /// only generates captures, and doesn't collect them.
     ///
     /// ```
-    /// use chess::{Board, CastleRights};
+    /// use chess::Board;
+    /// use std::str::FromStr;
     ///
-    /// let mut board = Board::default();
-    /// assert_eq!(board.their_castle_rights(), CastleRights::Both);
+    /// assert_eq!(Board::default().legal_capture_count(), 0);
     ///
-    /// board.remove_their_castle_rights(CastleRights::KingSide);
-    /// assert_eq!(board.their_castle_rights(), CastleRights::QueenSide);
+    /// // exd5, Nxd5 and Qxd5
+    /// let board = Board::from_str("4k3/8/8/3p4/4P3/2N5/8/3QK3 w - - 0 1").unwrap();
+    /// assert_eq!(board.legal_capture_count(), 3);
     /// ```
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn remove_their_castle_rights(&mut self, remove: CastleRights) {
-        let color = !self.side_to_move();
-        #[allow(deprecated)]
-        self.remove_castle_rights(color, remove);
-    }
+    pub fn legal_capture_count(&self) -> usize {
+        let color = self.side_to_move;
+        let mut movegen = MoveGen::new_legal(self);
+        movegen.set_iterator_mask(*self.color_combined(!color));
+        let mut count = movegen.len();
 
-    /// Add or remove a piece from the bitboards in this struct.
-    fn xor(&mut self, piece: Piece, bb: BitBoard, color: Color) {
-        unsafe {
-            *self.pieces.get_unchecked_mut(piece.to_index()) ^= bb;
-            *self.color_combined.get_unchecked_mut(color.to_index()) ^= bb;
-            self.combined ^= bb;
-            self.hash ^= Zobrist::piece(piece, bb.to_square(), color);
+        if let Some(victim) = self.en_passant {
+            // Only an en passant capture puts a pawn on the square behind the victim
+            let mut movegen = MoveGen::new_legal(self);
+            movegen.set_source_mask(self.pieces_of(Piece::Pawn, color));
+            movegen.set_iterator_mask(BitBoard::from_square(victim.uforward(color)));
+            count += movegen.len();
         }
+
+        count
     }
 
-    /// For a chess UI: set a piece on a particular square.
+    /// The squares the piece on `sq` can legally move to.  A pawn that can promote on a square
+    /// only sets that square once, regardless of how many pieces it could promote to.
     ///
     /// ```
-    /// use chess::{Board, Piece, Color, Square};
+    /// use chess::{Board, BitBoard, Square};
     ///
     /// let board = Board::default();
     ///
-    /// let new_board = board.set_piece(Piece::Queen,
-    ///                                 Color::White,
-    ///                                 Square::E4)
-    ///                      .expect("Valid Position");
-    ///
-    /// assert_eq!(new_board.pieces(Piece::Queen).count(), 3);
+    /// assert_eq!(
+    ///     board.legal_destinations(Square::G1),
+    ///     BitBoard::from_square(Square::F3) | BitBoard::from_square(Square::H3)
+    /// );
     /// ```
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn set_piece(&self, piece: Piece, color: Color, square: Square) -> Option<Board> {
-        let mut result = *self;
-        let square_bb = BitBoard::from_square(square);
-        match self.piece_on(square) {
-            None => result.xor(piece, square_bb, color),
-            Some(x) => {
-                // remove x from the bitboard
-                if self.color_combined(Color::White) & square_bb == square_bb {
-                    result.xor(x, square_bb, Color::White);
-                } else {
-                    result.xor(x, square_bb, Color::Black);
-                }
-                // add piece to the bitboard
-                result.xor(piece, square_bb, color);
-            }
-        }
-
-        // If setting this piece down leaves my opponent in check, and it's my move, then the
-        // position is not a valid chess board
-        result.side_to_move = !result.side_to_move;
-        result.update_pin_info();
-        if result.checkers != EMPTY {
-            return None;
-        }
-
-        // undo our damage
-        result.side_to_move = !result.side_to_move;
-        result.update_pin_info();
-
-        Some(result)
+    pub fn legal_destinations(&self, sq: Square) -> BitBoard {
+        self.legal_moves_from(sq)
+            .iter()
+            .fold(EMPTY, |bb, m| bb | BitBoard::from_square(m.get_dest()))
     }
 
-    /// For a chess UI: clear a particular square.
+    /// The squares the piece on `sq` can legally move to without capturing anything.  Together
+    /// with `capture_destinations_from`, this splits up `legal_destinations`.
     ///
     /// ```
-    /// use chess::{Board, Square, Piece};
+    /// use chess::{Board, BitBoard, Square};
     ///
     /// let board = Board::default();
     ///
-    /// let new_board = board.clear_square(Square::A1)
-    ///                      .expect("Valid Position");
-    ///
-    /// assert_eq!(new_board.pieces(Piece::Rook).count(), 3);
+    /// assert_eq!(
+    ///     board.quiet_destinations_from(Square::E2),
+    ///     BitBoard::from_square(Square::E3) | BitBoard::from_square(Square::E4)
+    /// );
     /// ```
-    #[deprecated(
-        since = "3.1.0",
-        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
-    )]
-    #[inline]
-    pub fn clear_square(&self, square: Square) -> Option<Board> {
-        let mut result = *self;
-        let square_bb = BitBoard::from_square(square);
-        match self.piece_on(square) {
-            None => {}
-            Some(x) => {
-                // remove x from the bitboard
-                if self.color_combined(Color::White) & square_bb == square_bb {
-                    result.xor(x, square_bb, Color::White);
-                } else {
-                    result.xor(x, square_bb, Color::Black);
-                }
-            }
-        }
+    pub fn quiet_destinations_from(&self, sq: Square) -> BitBoard {
+        self.legal_moves_from(sq)
+            .iter()
+            .filter(|m| self.captured_piece(**m).is_none())
+            .fold(EMPTY, |bb, m| bb | BitBoard::from_square(m.get_dest()))
+    }
 
-        // If setting this piece down leaves my opponent in check, and it's my move, then the
-        // position is not a valid chess board
-        result.side_to_move = !result.side_to_move;
-        result.update_pin_info();
-        if result.checkers != EMPTY {
-            return None;
-        }
+    /// The squares the piece on `sq` can legally move to with a capture.  For an en passant
+    /// capture, this is the square the pawn lands on, not the square of the pawn it takes.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert_eq!(
+    ///     board.capture_destinations_from(Square::E4),
+    ///     BitBoard::from_square(Square::D5)
+    /// );
+    /// ```
+    pub fn capture_destinations_from(&self, sq: Square) -> BitBoard {
+        self.legal_moves_from(sq)
+            .iter()
+            .filter(|m| self.captured_piece(**m).is_some())
+            .fold(EMPTY, |bb, m| bb | BitBoard::from_square(m.get_dest()))
+    }
 
-        // undo our damage
-        result.side_to_move = !result.side_to_move;
-        result.update_pin_info();
+    /// Does the side to move have any legal moves at all?  This is cheaper than generating every
+    /// move, as it stops as soon as one is found.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// assert!(Board::default().has_legal_moves());
+    ///
+    /// // Stalemate
+    /// let board = Board::from_str("7k/5Q2/8/8/8/8/8/K7 b - - 0 1").unwrap();
+    /// assert!(!board.has_legal_moves());
+    /// ```
+    #[inline]
+    pub fn has_legal_moves(&self) -> bool {
+        MoveGen::has_legals(self)
+    }
 
-        Some(result)
+    /// How many legal moves get the side to move out of check?  0 means checkmate.
+    ///
+    /// This is only meaningful in check.  `MoveGen` already generates nothing but evasions when
+    /// in check, so out of check this is simply the number of legal moves.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// // Fool's mate
+    /// let board = Board::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+    ///     .unwrap();
+    /// assert_eq!(board.num_evasions(), 0);
+    /// ```
+    #[inline]
+    pub fn num_evasions(&self) -> usize {
+        MoveGen::new_legal(self).len()
     }
 
-    /// Switch the color of the player without actually making a move.  Returns None if the current
-    /// player is in check.
+    /// Some legal way out of check, or `None` if there isn't one, which means checkmate.  This
+    /// stops at the first evasion it finds, so it is much cheaper than generating them all just
+    /// to test for mate.  Which evasion comes back is unspecified.
     ///
-    /// Note that this erases the en-passant information, so applying this function twice does not
-    /// always give the same result back.
+    /// Out of check, this returns `None`.
     ///
     /// ```
-    /// use chess::{Board, Color};
+    /// use chess::Board;
+    /// use std::str::FromStr;
     ///
-    /// let board = Board::default();
+    /// // Fool's mate
+    /// let board = Board::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+    ///     .unwrap();
+    /// assert_eq!(board.first_evasion(), None);
     ///
-    /// assert_eq!(board.side_to_move(), Color::White);
+    /// // The h4 queen checks, but g3 blocks it
+    /// let board = Board::from_str("rnb1kbnr/pppp1ppp/8/4p3/7q/5P2/PPPPP1PP/RNBQKBNR w KQkq - 1 3")
+    ///     .unwrap();
+    /// assert!(board.first_evasion().is_some());
+    /// ```
+    pub fn first_evasion(&self) -> Option<ChessMove> {
+        if self.checkers == EMPTY {
+            return None;
+        }
+        MoveGen::first_legal(self)
+    }
+
+    /// If there is exactly one legal move, give me it.  Returns `None` when there are no legal
+    /// moves, or more than one.
     ///
-    /// let new_board = board.null_move().expect("Valid Position");
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
     ///
-    /// assert_eq!(new_board.side_to_move(), Color::Black);
+    /// // The rook checks the king, which only has b2 to go to
+    /// let board = Board::from_str("1k6/8/8/8/8/8/P7/K6r w - - 0 1").unwrap();
+    /// assert_eq!(board.only_move(), Some(ChessMove::new(Square::A1, Square::B2, None)));
+    ///
+    /// assert_eq!(Board::default().only_move(), None);
     /// ```
-    #[inline]
-    pub fn null_move(&self) -> Option<Board> {
-        if self.checkers != EMPTY {
-            None
+    pub fn only_move(&self) -> Option<ChessMove> {
+        let mut movegen = MoveGen::new_legal(self);
+        let first = movegen.next()?;
+        if movegen.next().is_none() {
+            Some(first)
         } else {
-            let mut result = *self;
-            result.side_to_move = !result.side_to_move;
-            result.remove_ep();
-            result.update_pin_info();
-            Some(result)
+            None
         }
     }
 
-    /// Does this board "make sense"?
-    /// Do all the pieces make sense, do the bitboards combine correctly, etc?
-    /// This is for sanity checking.
+    /// Pick one of the legal moves uniformly at random, or `None` if the game is over.  The
+    /// moves are never collected: the generator already knows how many there are, so this picks
+    /// an index and skips ahead to it.
+    ///
+    /// Only available with the `rand` feature.
     ///
     /// ```
-    /// use chess::{Board, Color, Piece, Square};
+    /// use chess::Board;
+    /// use rand::rngs::SmallRng;
+    /// use rand::SeedableRng;
     ///
+    /// let mut rng = SmallRng::seed_from_u64(1);
     /// let board = Board::default();
     ///
-    /// assert_eq!(board.is_sane(), true);
-    ///
-    /// // Remove the king
-    /// let bad_board = board.clear_square(Square::E1).expect("Valid Position");
-    /// assert_eq!(bad_board.is_sane(), false);
+    /// let m = board.random_move(&mut rng).expect("The game isn't over");
+    /// assert!(board.legal(m));
     /// ```
-    pub fn is_sane(&self) -> bool {
-        // make sure there is no square with multiple pieces on it
-        for x in ALL_PIECES.iter() {
-            for y in ALL_PIECES.iter() {
-                if *x != *y {
-                    if self.pieces(*x) & self.pieces(*y) != EMPTY {
-                        return false;
-                    }
-                }
-            }
-        }
-
-        // make sure the colors don't overlap, either
-        if self.color_combined(Color::White) & self.color_combined(Color::Black) != EMPTY {
-            return false;
-        }
-
-        // grab all the pieces by OR'ing together each piece() BitBoard
-        let combined = ALL_PIECES
-            .iter()
-            .fold(EMPTY, |cur, next| cur | self.pieces(*next));
-
-        // make sure that's equal to the combined bitboard
-        if combined != *self.combined() {
-            return false;
-        }
-
-        // make sure there is exactly one white king
-        if (self.pieces(Piece::King) & self.color_combined(Color::White)).popcnt() != 1 {
-            return false;
+    #[cfg(feature = "rand")]
+    pub fn random_move(&self, rng: &mut impl Rng) -> Option<ChessMove> {
+        let mut movegen = MoveGen::new_legal(self);
+        if movegen.len() == 0 {
+            return None;
         }
+        let index = rng.gen_range(0, movegen.len());
+        movegen.nth(index)
+    }
 
-        // make sure there is exactly one black king
-        if (self.pieces(Piece::King) & self.color_combined(Color::Black)).popcnt() != 1 {
-            return false;
-        }
+    /// All the legal moves that leave the opponent stalemated.  A side that is losing can look
+    /// for these to save a draw, and a side that is winning wants to avoid them.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("7k/5K2/8/8/4Q3/8/8/8 w - - 0 1").unwrap();
+    /// let stalemates = board.stalemate_in_one_moves();
+    ///
+    /// // Qg6 takes away h7, the last square the black king had
+    /// assert!(stalemates.contains(&ChessMove::new(Square::E4, Square::G6, None)));
+    /// // Qe8+ is check, not stalemate
+    /// assert!(!stalemates.contains(&ChessMove::new(Square::E4, Square::E8, None)));
+    /// ```
+    pub fn stalemate_in_one_moves(&self) -> Vec<ChessMove> {
+        MoveGen::new_legal(self)
+            .filter(|m| self.make_move_new(*m).status() == BoardStatus::Stalemate)
+            .collect()
+    }
 
-        // make sure the en_passant square has a pawn on it of the right color
-        match self.en_passant {
-            None => {}
-            Some(x) => {
-                if self.pieces(Piece::Pawn)
-                    & self.color_combined(!self.side_to_move)
-                    & BitBoard::from_square(x)
-                    == EMPTY
-                {
-                    return false;
-                }
-            }
-        }
+    /// Could the side to move be facing a perpetual check?  This is true when the side to move is
+    /// in check and, for a few moves ahead, every reply it has still lets the opponent give check
+    /// again.
+    ///
+    /// This is only a heuristic, not a proof: the search stops after a small fixed number of
+    /// moves, and it does not care whether the checks lead anywhere.  A lone queen against a bare
+    /// king will usually be a candidate.  Checkmate is never a candidate.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// // Qe8+ Kh7 Qe4+ Kh8 Qe8+ ... and black can't get away
+    /// let board = Board::from_str("4Q2k/6p1/7p/8/8/8/8/K7 b - - 0 1").unwrap();
+    /// assert!(board.is_perpetual_check_candidate());
+    ///
+    /// // Black isn't in check at all
+    /// assert!(!Board::default().is_perpetual_check_candidate());
+    /// ```
+    pub fn is_perpetual_check_candidate(&self) -> bool {
+        self.can_keep_checking(PERPETUAL_CHECK_DEPTH)
+    }
 
-        // make sure my opponent is not currently in check (because that would be illegal)
-        let mut board_copy = *self;
-        board_copy.side_to_move = !board_copy.side_to_move;
-        board_copy.update_pin_info();
-        if board_copy.checkers != EMPTY {
+    /// Does every reply to the current check allow another check, for `depth` more moves?
+    fn can_keep_checking(&self, depth: usize) -> bool {
+        if self.checkers == EMPTY || self.status() != BoardStatus::Ongoing {
             return false;
         }
-
-        // for each color, verify that, if they have castle rights, that they haven't moved their
-        // rooks or king
-        for color in ALL_COLORS.iter() {
-            // get the castle rights
-            let castle_rights = self.castle_rights(*color);
-
-            // the castle rights object will tell us which rooks shouldn't have moved yet.
-            // verify there are rooks on all those squares
-            if castle_rights.unmoved_rooks(*color)
-                & self.pieces(Piece::Rook)
-                & self.color_combined(*color)
-                != castle_rights.unmoved_rooks(*color)
-            {
-                return false;
-            }
-            // if we have castle rights, make sure we have a king on the (E, {1,8}) square,
-            // depending on the color
-            if castle_rights != CastleRights::NoRights {
-                if self.pieces(Piece::King) & self.color_combined(*color)
-                    != get_file(File::E) & get_rank(color.to_my_backrank())
-                {
-                    return false;
-                }
-            }
-        }
-
-        // we must make sure the kings aren't touching
-        if get_king_moves(self.king_square(Color::White)) & self.pieces(Piece::King) != EMPTY {
-            return false;
+        if depth == 0 {
+            return true;
         }
 
-        // it checks out
-        return true;
+        MoveGen::new_legal(self).all(|evasion| {
+            let reply = self.make_move_new(evasion);
+            MoveGen::new_legal(&reply)
+                .filter(|m| reply.gives_check(*m))
+                .any(|check| reply.make_move_new(check).can_keep_checking(depth - 1))
+        })
     }
 
-    /// Get a hash of the board.
+    /// Grab the "combined" `BitBoard`.  This is a `BitBoard` with every piece.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Rank, get_rank};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let combined_should_be = get_rank(Rank::First) |
+    ///                          get_rank(Rank::Second) |
+    ///                          get_rank(Rank::Seventh) |
+    ///                          get_rank(Rank::Eighth);
+    ///
+    /// assert_eq!(*board.combined(), combined_should_be);
+    /// ```
     #[inline]
-    pub fn get_hash(&self) -> u64 {
-        self.hash
-            ^ if let Some(ep) = self.en_passant {
-                Zobrist::en_passant(ep.get_file(), !self.side_to_move)
-            } else {
-                0
-            }
-            ^ Zobrist::castles(
-                self.castle_rights[self.side_to_move.to_index()],
-                self.side_to_move,
-            )
-            ^ Zobrist::castles(
-                self.castle_rights[(!self.side_to_move).to_index()],
-                !self.side_to_move,
-            )
-            ^ if self.side_to_move == Color::Black {
-                Zobrist::color()
-            } else {
-                0
-            }
+    pub fn combined(&self) -> &BitBoard {
+        &self.combined
     }
 
-    /// Get a pawn hash of the board (a hash that only changes on color change and pawn moves).
+    /// Grab the "color combined" `BitBoard`.  This is a `BitBoard` with every piece of a particular
+    /// color.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Rank, get_rank, Color};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let white_pieces = get_rank(Rank::First) |
+    ///                    get_rank(Rank::Second);
+    ///
+    /// let black_pieces = get_rank(Rank::Seventh) |
+    ///                    get_rank(Rank::Eighth);
     ///
-    /// Currently not implemented...
+    /// assert_eq!(*board.color_combined(Color::White), white_pieces);
+    /// assert_eq!(*board.color_combined(Color::Black), black_pieces);
+    /// ```
     #[inline]
-    pub fn get_pawn_hash(&self) -> u64 {
-        0
+    pub fn color_combined(&self, color: Color) -> &BitBoard {
+        unsafe { self.color_combined.get_unchecked(color.to_index()) }
     }
 
-    /// What piece is on a particular `Square`?  Is there even one?
+    /// Give me the `Square` the `color` king is on.
     ///
     /// ```
-    /// use chess::{Board, Piece, Square};
+    /// use chess::{Board, Square, Color};
     ///
     /// let board = Board::default();
     ///
-    /// assert_eq!(board.piece_on(Square::A1), Some(Piece::Rook));
-    /// assert_eq!(board.piece_on(Square::D4), None);
+    /// assert_eq!(board.king_square(Color::White), Square::E1);
+    /// assert_eq!(board.king_square(Color::Black), Square::E8);
     /// ```
     #[inline]
-    pub fn piece_on(&self, square: Square) -> Option<Piece> {
-        let opp = BitBoard::from_square(square);
-        if self.combined() & opp == EMPTY {
-            None
-        } else {
-            //naiive algorithm
-            /*
-            for p in ALL_PIECES {
-                if self.pieces(*p) & opp {
-                    return p;
-                }
-            } */
-            if (self.pieces(Piece::Pawn) ^ self.pieces(Piece::Knight) ^ self.pieces(Piece::Bishop))
-                & opp
-                != EMPTY
-            {
-                if self.pieces(Piece::Pawn) & opp != EMPTY {
-                    Some(Piece::Pawn)
-                } else if self.pieces(Piece::Knight) & opp != EMPTY {
-                    Some(Piece::Knight)
-                } else {
-                    Some(Piece::Bishop)
-                }
-            } else {
-                if self.pieces(Piece::Rook) & opp != EMPTY {
-                    Some(Piece::Rook)
-                } else if self.pieces(Piece::Queen) & opp != EMPTY {
-                    Some(Piece::Queen)
-                } else {
-                    Some(Piece::King)
-                }
-            }
-        }
+    pub fn king_square(&self, color: Color) -> Square {
+        (self.pieces(Piece::King) & self.color_combined(color)).to_square()
     }
 
-    /// What color piece is on a particular square?
+    /// Is the `color` king on one of the four center squares (d4, e4, d5 or e5)?  Reaching the
+    /// center wins a game of King of the Hill.
+    ///
+    /// ```
+    /// use chess::{Board, Color};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/4K3/8/8/8 w - - 0 1").unwrap();
+    ///
+    /// assert!(board.on_center_square(Color::White));
+    /// assert!(!board.on_center_square(Color::Black));
+    /// ```
     #[inline]
-    pub fn color_on(&self, square: Square) -> Option<Color> {
-        if (self.color_combined(Color::White) & BitBoard::from_square(square)) != EMPTY {
-            Some(Color::White)
-        } else if (self.color_combined(Color::Black) & BitBoard::from_square(square)) != EMPTY {
-            Some(Color::Black)
-        } else {
-            None
-        }
-    }
-
-    /// Unset the en_passant square.
-    fn remove_ep(&mut self) {
-        self.en_passant = None;
+    pub fn on_center_square(&self, color: Color) -> bool {
+        let center = (get_rank(Rank::Fourth) | get_rank(Rank::Fifth))
+            & (get_file(File::D) | get_file(File::E));
+        self.pieces(Piece::King) & self.color_combined(color) & center != EMPTY
     }
 
-    /// Give me the en_passant square, if it exists.
+    /// Grab the "pieces" `BitBoard`.  This is a `BitBoard` with every piece of a particular type.
     ///
     /// ```
-    /// use chess::{Board, ChessMove, Square};
+    /// use chess::{Board, BitBoard, Piece, Square};
     ///
-    /// let move1 = ChessMove::new(Square::D2,
-    ///                            Square::D4,
-    ///                            None);
+    /// // The rooks should be in each corner of the board
+    /// let rooks = BitBoard::from_square(Square::A1) |
+    ///             BitBoard::from_square(Square::H1) |
+    ///             BitBoard::from_square(Square::A8) |
+    ///             BitBoard::from_square(Square::H8);
     ///
-    /// let move2 = ChessMove::new(Square::H7,
-    ///                            Square::H5,
-    ///                            None);
+    /// let board = Board::default();
     ///
-    /// let move3 = ChessMove::new(Square::D4,
-    ///                            Square::D5,
-    ///                            None);
+    /// assert_eq!(*board.pieces(Piece::Rook), rooks);
+    /// ```
+    #[inline]
+    pub fn pieces(&self, piece: Piece) -> &BitBoard {
+        unsafe { self.pieces.get_unchecked(piece.to_index()) }
+    }
+
+    /// Grab the `BitBoard` of every `piece` of a particular `color`.
     ///
-    /// let move4 = ChessMove::new(Square::E7,
-    ///                            Square::E5,
-    ///                            None);
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Piece, Square};
     ///
-    /// let board = Board::default().make_move_new(move1)
-    ///                             .make_move_new(move2)
-    ///                             .make_move_new(move3)
-    ///                             .make_move_new(move4);
+    /// let board = Board::default();
     ///
-    /// assert_eq!(board.en_passant(), Some(Square::E5));
+    /// assert_eq!(
+    ///     board.pieces_of(Piece::Rook, Color::White),
+    ///     BitBoard::from_square(Square::A1) | BitBoard::from_square(Square::H1)
+    /// );
     /// ```
     #[inline]
-    pub fn en_passant(self) -> Option<Square> {
-        self.en_passant
+    pub fn pieces_of(&self, piece: Piece, color: Color) -> BitBoard {
+        self.pieces(piece) & self.color_combined(color)
     }
 
-    /// Set the en_passant square.  Note: This must only be called when self.en_passant is already
-    /// None.
-    fn set_ep(&mut self, sq: Square) {
-        // Only set self.en_passant if the pawn can actually be captured next move.
-        if get_adjacent_files(sq.get_file())
-            & get_rank(sq.get_rank())
-            & self.pieces(Piece::Pawn)
-            & self.color_combined(!self.side_to_move)
-            != EMPTY
-        {
-            self.en_passant = Some(sq);
-        }
-    }
-
-    /// Is a particular move legal?  This function is very slow, but will work on unsanitized
-    /// input.
+    /// Grab the `CastleRights` for a particular side.
     ///
     /// ```
-    /// use chess::{Board, ChessMove, Square, MoveGen};
+    /// use chess::{Board, Square, CastleRights, Color, ChessMove};
     ///
-    /// let move1 = ChessMove::new(Square::E2,
-    ///                            Square::E4,
+    /// let move1 = ChessMove::new(Square::A2,
+    ///                            Square::A4,
     ///                            None);
     ///
-    /// let move2 = ChessMove::new(Square::E2,
+    /// let move2 = ChessMove::new(Square::E7,
     ///                            Square::E5,
     ///                            None);
     ///
-    /// let board = Board::default();
+    /// let move3 = ChessMove::new(Square::A1,
+    ///                            Square::A2,
+    ///                            None);
     ///
-    /// assert_eq!(board.legal(move1), true);
-    /// assert_eq!(board.legal(move2), false);
+    /// let move4 = ChessMove::new(Square::E8,
+    ///                            Square::E7,
+    ///                            None);
+    ///
+    /// let mut board = Board::default();
+    ///
+    /// assert_eq!(board.castle_rights(Color::White), CastleRights::Both);
+    /// assert_eq!(board.castle_rights(Color::Black), CastleRights::Both);
+    ///
+    /// board = board.make_move_new(move1)
+    ///              .make_move_new(move2)
+    ///              .make_move_new(move3)
+    ///              .make_move_new(move4);
+    ///
+    /// assert_eq!(board.castle_rights(Color::White), CastleRights::KingSide);
+    /// assert_eq!(board.castle_rights(Color::Black), CastleRights::NoRights);
     /// ```
     #[inline]
-    pub fn legal(&self, m: ChessMove) -> bool {
-        MoveGen::new_legal(&self).find(|x| *x == m).is_some()
+    pub fn castle_rights(&self, color: Color) -> CastleRights {
+        unsafe { *self.castle_rights.get_unchecked(color.to_index()) }
     }
 
-    /// Make a chess move onto a new board.
+    /// Could `color` castle towards `side` right now?  Besides the castle rights, the squares
+    /// between the king and the rook must be empty, and the king must not be in check or pass
+    /// through or land on an attacked square.
     ///
-    /// panic!() if king is captured.
+    /// This doesn't depend on whose turn it is, so it can be asked of either player.
     ///
     /// ```
-    /// use chess::{Board, ChessMove, Square, Color};
+    /// use chess::{Board, CastleSide, Color};
+    /// use std::str::FromStr;
     ///
-    /// let m = ChessMove::new(Square::D2,
-    ///                        Square::D4,
-    ///                        None);
+    /// // The c4 bishop covers f1
+    /// let board = Board::from_str("r3k2r/8/8/8/2b5/8/8/R3K2R w KQkq - 0 1").unwrap();
+    ///
+    /// assert!(!board.can_castle(Color::White, CastleSide::KingSide));
+    /// assert!(board.can_castle(Color::White, CastleSide::QueenSide));
+    /// assert!(!Board::default().can_castle(Color::Black, CastleSide::QueenSide));
+    /// ```
+    pub fn can_castle(&self, color: Color, side: CastleSide) -> bool {
+        let rights = self.castle_rights(color);
+        let backrank = color.to_my_backrank();
+        let (allowed, empty, path) = match side {
+            CastleSide::KingSide => (
+                rights.has_kingside(),
+                rights.kingside_squares(color),
+                [File::E, File::F, File::G],
+            ),
+            CastleSide::QueenSide => (
+                rights.has_queenside(),
+                rights.queenside_squares(color),
+                [File::E, File::D, File::C],
+            ),
+        };
+
+        allowed
+            && empty & self.combined() == EMPTY
+            && path.iter().all(|file| {
+                self.attackers_to(Square::make_square(backrank, *file), !color) == EMPTY
+            })
+    }
+
+    /// Add castle rights for a particular side.  Note: this can create an invalid position.
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn add_castle_rights(&mut self, color: Color, add: CastleRights) {
+        unsafe {
+            *self.castle_rights.get_unchecked_mut(color.to_index()) =
+                self.castle_rights(color).add(add);
+        }
+    }
+
+    /// Remove castle rights for a particular side.
+    ///
+    /// ```
+    /// use chess::{Board, CastleRights, Color};
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.castle_rights(Color::White), CastleRights::Both);
+    ///
+    /// board.remove_castle_rights(Color::White, CastleRights::KingSide);
+    /// assert_eq!(board.castle_rights(Color::White), CastleRights::QueenSide);
+    /// ```
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn remove_castle_rights(&mut self, color: Color, remove: CastleRights) {
+        unsafe {
+            *self.castle_rights.get_unchecked_mut(color.to_index()) =
+                self.castle_rights(color).remove(remove);
+        }
+    }
+
+    /// Give me a copy of this board where `color` can no longer castle.  Taking castle rights
+    /// away always leaves a valid position, and the hash changes to match.
+    ///
+    /// ```
+    /// use chess::{Board, CastleRights, Color};
+    ///
+    /// let board = Board::default().without_castle_rights(Color::White);
+    ///
+    /// assert_eq!(board.castle_rights(Color::White), CastleRights::NoRights);
+    /// assert_eq!(board.castle_rights(Color::Black), CastleRights::Both);
+    /// assert_ne!(board.get_hash(), Board::default().get_hash());
+    /// ```
+    pub fn without_castle_rights(&self, color: Color) -> Board {
+        let mut result = *self;
+        result.castle_rights[color.to_index()] = CastleRights::NoRights;
+        result
+    }
+
+    /// Who's turn is it?
+    ///
+    /// ```
+    /// use chess::{Board, Color};
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.side_to_move(), Color::White);
+    /// ```
+    #[inline]
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// Is it white's turn?  This is the same as `side_to_move() == Color::White`, for when a
+    /// `bool` is more convenient, such as for a tempo bonus.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
     ///
     /// let board = Board::default();
-    /// assert_eq!(board.make_move_new(m).side_to_move(), Color::Black);
+    /// assert!(board.is_white_to_move());
+    ///
+    /// let board = board.make_move_new(ChessMove::new(Square::E2, Square::E4, None));
+    /// assert!(!board.is_white_to_move());
     /// ```
     #[inline]
-    pub fn make_move_new(&self, m: ChessMove) -> Board {
-        let mut result = mem::MaybeUninit::<Board>::uninit();
+    pub fn is_white_to_move(&self) -> bool {
+        self.side_to_move == Color::White
+    }
+
+    /// Give me a copy of this board that counts how many checks each side gives from now on, for
+    /// variants like Three-Check.  The counts start at zero.  Counting is off for a normal
+    /// `Board`, and this crate doesn't enforce any variant rules based on the counts.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap().track_checks();
+    /// let board = board.make_move_new(ChessMove::new(Square::A1, Square::A8, None));
+    ///
+    /// assert_eq!(board.check_count(Color::White), 1);
+    /// assert_eq!(board.check_count(Color::Black), 0);
+    /// ```
+    pub fn track_checks(&self) -> Board {
+        let mut result = *self;
+        result.check_counts = Some([0; NUM_COLORS]);
+        result
+    }
+
+    /// How many checks has `color` given since `track_checks` was called?  This is always 0 when
+    /// checks aren't being counted.
+    #[inline]
+    pub fn check_count(&self, color: Color) -> u8 {
+        self.check_counts
+            .map_or(0, |counts| counts[color.to_index()])
+    }
+
+    /// Grab my `CastleRights`.
+    ///
+    /// ```
+    /// use chess::{Board, Color, CastleRights};
+    ///
+    /// let mut board = Board::default();
+    /// board.remove_castle_rights(Color::White, CastleRights::KingSide);
+    /// board.remove_castle_rights(Color::Black, CastleRights::QueenSide);
+    ///
+    /// assert_eq!(board.my_castle_rights(), board.castle_rights(Color::White));
+    /// ```
+    #[inline]
+    pub fn my_castle_rights(&self) -> CastleRights {
+        self.castle_rights(self.side_to_move())
+    }
+
+    /// Add to my `CastleRights`.  Note: This can make the position invalid.
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn add_my_castle_rights(&mut self, add: CastleRights) {
+        let color = self.side_to_move();
+        #[allow(deprecated)]
+        self.add_castle_rights(color, add);
+    }
+
+    /// Remove some of my `CastleRights`.
+    ///
+    /// ```
+    /// use chess::{Board, CastleRights};
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.my_castle_rights(), CastleRights::Both);
+    ///
+    /// board.remove_my_castle_rights(CastleRights::KingSide);
+    /// assert_eq!(board.my_castle_rights(), CastleRights::QueenSide);
+    /// ```
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn remove_my_castle_rights(&mut self, remove: CastleRights) {
+        let color = self.side_to_move();
+        #[allow(deprecated)]
+        self.remove_castle_rights(color, remove);
+    }
+
+    /// My opponents `CastleRights`.
+    ///
+    /// ```
+    /// use chess::{Board, Color, CastleRights};
+    ///
+    /// let mut board = Board::default();
+    /// board.remove_castle_rights(Color::White, CastleRights::KingSide);
+    /// board.remove_castle_rights(Color::Black, CastleRights::QueenSide);
+    ///
+    /// assert_eq!(board.their_castle_rights(), board.castle_rights(Color::Black));
+    /// ```
+    #[inline]
+    pub fn their_castle_rights(&self) -> CastleRights {
+        self.castle_rights(!self.side_to_move())
+    }
+
+    /// Add to my opponents `CastleRights`. Note: This can make the position invalid.
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn add_their_castle_rights(&mut self, add: CastleRights) {
+        let color = !self.side_to_move();
+        #[allow(deprecated)]
+        self.add_castle_rights(color, add)
+    }
+
+    /// Remove some of my opponents `CastleRights`.
+    ///
+    /// ```
+    /// use chess::{Board, CastleRights};
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.their_castle_rights(), CastleRights::Both);
+    ///
+    /// board.remove_their_castle_rights(CastleRights::KingSide);
+    /// assert_eq!(board.their_castle_rights(), CastleRights::QueenSide);
+    /// ```
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn remove_their_castle_rights(&mut self, remove: CastleRights) {
+        let color = !self.side_to_move();
+        #[allow(deprecated)]
+        self.remove_castle_rights(color, remove);
+    }
+
+    /// Add or remove a piece from the bitboards in this struct.
+    fn xor(&mut self, piece: Piece, bb: BitBoard, color: Color) {
         unsafe {
-            self.make_move(m, &mut *result.as_mut_ptr());
-            result.assume_init()
+            *self.pieces.get_unchecked_mut(piece.to_index()) ^= bb;
+            *self.color_combined.get_unchecked_mut(color.to_index()) ^= bb;
+            self.combined ^= bb;
+            self.hash ^= Zobrist::piece(piece, bb.to_square(), color);
+
+            match piece {
+                Piece::Pawn | Piece::King => {
+                    self.pawn_hash ^= Zobrist::piece(piece, bb.to_square(), color);
+                }
+                _ => {
+                    let material = self.non_pawn_material.get_unchecked_mut(color.to_index());
+                    if self.pieces.get_unchecked(piece.to_index()) & bb == EMPTY {
+                        *material -= piece.value();
+                    } else {
+                        *material += piece.value();
+                    }
+                }
+            }
         }
     }
 
-    /// Make a chess move onto an already allocated `Board`.
-    ///
-    /// panic!() if king is captured.
-    ///
-    /// ```
-    /// use chess::{Board, ChessMove, Square, Color};
-    ///
-    /// let m = ChessMove::new(Square::D2,
-    ///                        Square::D4,
-    ///                        None);
-    ///
-    /// let board = Board::default();
-    /// let mut result = Board::default();
-    /// board.make_move(m, &mut result);
-    /// assert_eq!(result.side_to_move(), Color::Black);
-    /// ```
-    #[inline]
-    pub fn make_move(&self, m: ChessMove, result: &mut Board) {
-        *result = *self;
-        result.remove_ep();
-        result.checkers = EMPTY;
-        result.pinned = EMPTY;
-        let source = m.get_source();
-        let dest = m.get_dest();
+    /// For a chess UI: set a piece on a particular square.
+    ///
+    /// ```
+    /// use chess::{Board, Piece, Color, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let new_board = board.set_piece(Piece::Queen,
+    ///                                 Color::White,
+    ///                                 Square::E4)
+    ///                      .expect("Valid Position");
+    ///
+    /// assert_eq!(new_board.pieces(Piece::Queen).count(), 3);
+    /// ```
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn set_piece(&self, piece: Piece, color: Color, square: Square) -> Option<Board> {
+        let mut result = *self;
+        let square_bb = BitBoard::from_square(square);
+        match self.piece_on(square) {
+            None => result.xor(piece, square_bb, color),
+            Some(x) => {
+                // remove x from the bitboard
+                if self.color_combined(Color::White) & square_bb == square_bb {
+                    result.xor(x, square_bb, Color::White);
+                } else {
+                    result.xor(x, square_bb, Color::Black);
+                }
+                // add piece to the bitboard
+                result.xor(piece, square_bb, color);
+            }
+        }
+
+        // If setting this piece down leaves my opponent in check, and it's my move, then the
+        // position is not a valid chess board
+        result.side_to_move = !result.side_to_move;
+        result.update_pin_info();
+        if result.checkers != EMPTY {
+            return None;
+        }
+
+        // undo our damage
+        result.side_to_move = !result.side_to_move;
+        result.update_pin_info();
+
+        Some(result)
+    }
+
+    /// For a chess UI: clear a particular square.
+    ///
+    /// ```
+    /// use chess::{Board, Square, Piece};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let new_board = board.clear_square(Square::A1)
+    ///                      .expect("Valid Position");
+    ///
+    /// assert_eq!(new_board.pieces(Piece::Rook).count(), 3);
+    /// ```
+    #[deprecated(
+        since = "3.1.0",
+        note = "When doing board setup, use the BoardBuilder structure.  It ensures you don't end up with an invalid position."
+    )]
+    #[inline]
+    pub fn clear_square(&self, square: Square) -> Option<Board> {
+        let mut result = *self;
+        let square_bb = BitBoard::from_square(square);
+        match self.piece_on(square) {
+            None => {}
+            Some(x) => {
+                // remove x from the bitboard
+                if self.color_combined(Color::White) & square_bb == square_bb {
+                    result.xor(x, square_bb, Color::White);
+                } else {
+                    result.xor(x, square_bb, Color::Black);
+                }
+            }
+        }
+
+        // If setting this piece down leaves my opponent in check, and it's my move, then the
+        // position is not a valid chess board
+        result.side_to_move = !result.side_to_move;
+        result.update_pin_info();
+        if result.checkers != EMPTY {
+            return None;
+        }
+
+        // undo our damage
+        result.side_to_move = !result.side_to_move;
+        result.update_pin_info();
+
+        Some(result)
+    }
+
+    /// Switch the color of the player without actually making a move.  Returns None if the current
+    /// player is in check.
+    ///
+    /// Note that this erases the en-passant information, so applying this function twice does not
+    /// always give the same result back.
+    ///
+    /// ```
+    /// use chess::{Board, Color};
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.side_to_move(), Color::White);
+    ///
+    /// let new_board = board.null_move().expect("Valid Position");
+    ///
+    /// assert_eq!(new_board.side_to_move(), Color::Black);
+    /// ```
+    #[inline]
+    pub fn null_move(&self) -> Option<Board> {
+        if self.checkers != EMPTY {
+            None
+        } else {
+            let mut result = *self;
+            result.side_to_move = !result.side_to_move;
+            result.remove_ep();
+            result.update_pin_info();
+            Some(result)
+        }
+    }
+
+    /// Does this board "make sense"?
+    /// Do all the pieces make sense, do the bitboards combine correctly, etc?
+    /// This is for sanity checking.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Piece, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.is_sane(), true);
+    ///
+    /// // Remove the king
+    /// let bad_board = board.clear_square(Square::E1).expect("Valid Position");
+    /// assert_eq!(bad_board.is_sane(), false);
+    /// ```
+    pub fn is_sane(&self) -> bool {
+        self.is_sane_inner(false)
+    }
+
+    /// The checks behind `is_sane`.  When `allow_opponent_in_check` is set, the side not to move
+    /// may be in check.
+    fn is_sane_inner(&self, allow_opponent_in_check: bool) -> bool {
+        // make sure there is no square with multiple pieces on it
+        for x in ALL_PIECES.iter() {
+            for y in ALL_PIECES.iter() {
+                if *x != *y {
+                    if self.pieces(*x) & self.pieces(*y) != EMPTY {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        // make sure the colors don't overlap, either
+        if self.color_combined(Color::White) & self.color_combined(Color::Black) != EMPTY {
+            return false;
+        }
+
+        // grab all the pieces by OR'ing together each piece() BitBoard
+        let combined = ALL_PIECES
+            .iter()
+            .fold(EMPTY, |cur, next| cur | self.pieces(*next));
+
+        // make sure that's equal to the combined bitboard
+        if combined != *self.combined() {
+            return false;
+        }
+
+        // make sure there is exactly one white king
+        if (self.pieces(Piece::King) & self.color_combined(Color::White)).popcnt() != 1 {
+            return false;
+        }
+
+        // make sure there is exactly one black king
+        if (self.pieces(Piece::King) & self.color_combined(Color::Black)).popcnt() != 1 {
+            return false;
+        }
+
+        // make sure the en_passant square has a pawn on it of the right color
+        match self.en_passant {
+            None => {}
+            Some(x) => {
+                if self.pieces(Piece::Pawn)
+                    & self.color_combined(!self.side_to_move)
+                    & BitBoard::from_square(x)
+                    == EMPTY
+                {
+                    return false;
+                }
+            }
+        }
+
+        // make sure my opponent is not currently in check (because that would be illegal)
+        if !allow_opponent_in_check {
+            let mut board_copy = *self;
+            board_copy.side_to_move = !board_copy.side_to_move;
+            board_copy.update_pin_info();
+            if board_copy.checkers != EMPTY {
+                return false;
+            }
+        }
+
+        // for each color, verify that, if they have castle rights, that they haven't moved their
+        // rooks or king
+        for color in ALL_COLORS.iter() {
+            // get the castle rights
+            let castle_rights = self.castle_rights(*color);
+
+            // the castle rights object will tell us which rooks shouldn't have moved yet.
+            // verify there are rooks on all those squares
+            if castle_rights.unmoved_rooks(*color)
+                & self.pieces(Piece::Rook)
+                & self.color_combined(*color)
+                != castle_rights.unmoved_rooks(*color)
+            {
+                return false;
+            }
+            // if we have castle rights, make sure we have a king on the (E, {1,8}) square,
+            // depending on the color
+            if castle_rights != CastleRights::NoRights {
+                if self.pieces(Piece::King) & self.color_combined(*color)
+                    != get_file(File::E) & get_rank(color.to_my_backrank())
+                {
+                    return false;
+                }
+            }
+        }
+
+        // we must make sure the kings aren't touching
+        if get_king_moves(self.king_square(Color::White)) & self.pieces(Piece::King) != EMPTY {
+            return false;
+        }
+
+        // it checks out
+        return true;
+    }
+
+    /// Work out the piece part of the hash from scratch, for checking the one that `xor` keeps up
+    /// to date.
+    fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for sq in *self.combined() {
+            hash ^= Zobrist::piece(self.piece_on(sq).unwrap(), sq, self.color_on(sq).unwrap());
+        }
+        hash
+    }
+
+    /// Panic if any of the board's cached state disagrees with a from-scratch recomputation.
+    ///
+    /// This checks that the hash matches the pieces on the board, that the per-color and
+    /// combined `BitBoard`s agree with the per-piece ones, that each side has exactly one king,
+    /// and that the checkers and pinned pieces are correct.  It is slow, and meant for catching
+    /// bugs in code that builds or updates boards, not for use during search.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let board = Board::default().make_move_new(ChessMove::new(Square::E2, Square::E4, None));
+    /// board.assert_invariants();
+    /// ```
+    pub fn assert_invariants(&self) {
+        assert_eq!(
+            self.hash,
+            self.compute_hash(),
+            "hash does not match the pieces on the board"
+        );
+
+        let mut pawn_hash = 0;
+        for sq in self.pieces(Piece::Pawn) | self.pieces(Piece::King) {
+            pawn_hash ^= Zobrist::piece(self.piece_on(sq).unwrap(), sq, self.color_on(sq).unwrap());
+        }
+        assert_eq!(
+            self.pawn_hash, pawn_hash,
+            "pawn hash does not match the pawns and kings on the board"
+        );
+
+        for color in ALL_COLORS.iter() {
+            let material: i32 = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+                .iter()
+                .map(|p| {
+                    (self.pieces(*p) & self.color_combined(*color)).popcnt() as i32 * p.value()
+                })
+                .sum();
+            assert_eq!(
+                self.non_pawn_material(*color),
+                material,
+                "non-pawn material for {:?} is out of date",
+                color
+            );
+        }
+
+        let white = self.color_combined(Color::White);
+        let black = self.color_combined(Color::Black);
+        assert_eq!(white & black, EMPTY, "a square holds both colors");
+        assert_eq!(
+            white | black,
+            *self.combined(),
+            "colors do not add up to combined"
+        );
+
+        let mut pieces = EMPTY;
+        for piece in ALL_PIECES.iter() {
+            assert_eq!(
+                pieces & self.pieces(*piece),
+                EMPTY,
+                "a square holds two pieces"
+            );
+            pieces |= self.pieces(*piece);
+        }
+        assert_eq!(pieces, *self.combined(), "pieces do not add up to combined");
+
+        for color in ALL_COLORS.iter() {
+            assert_eq!(
+                (self.pieces(Piece::King) & self.color_combined(*color)).popcnt(),
+                1,
+                "{:?} does not have exactly one king",
+                color
+            );
+        }
+
+        let mut fresh = *self;
+        fresh.update_pin_info();
+        assert_eq!(self.checkers, fresh.checkers, "checkers are out of date");
+        assert_eq!(self.pinned, fresh.pinned, "pinned pieces are out of date");
+    }
+
+    /// Get a hash of the board.
+    #[inline]
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+            ^ if let Some(ep) = self.en_passant {
+                Zobrist::en_passant(ep.get_file(), !self.side_to_move)
+            } else {
+                0
+            }
+            ^ Zobrist::castles(
+                self.castle_rights[self.side_to_move.to_index()],
+                self.side_to_move,
+            )
+            ^ Zobrist::castles(
+                self.castle_rights[(!self.side_to_move).to_index()],
+                !self.side_to_move,
+            )
+            ^ if self.side_to_move == Color::Black {
+                Zobrist::color()
+            } else {
+                0
+            }
+    }
+
+    /// A short name for this position, for logs and test fixtures: `get_hash()` as 16 lowercase
+    /// hex digits.  The Zobrist keys are generated from a fixed seed when the crate is built, so
+    /// a position gets the same id on every run.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let id = Board::default().position_id();
+    /// assert_eq!(id.len(), 16);
+    /// assert_eq!(u64::from_str_radix(&id, 16).unwrap(), Board::default().get_hash());
+    /// ```
+    pub fn position_id(&self) -> String {
+        format!("{:016x}", self.get_hash())
+    }
+
+    /// Split `get_hash()` into its parts, for tracking down a hash that doesn't match.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let board = Board::default().make_move_new(ChessMove::new(Square::E2, Square::E4, None));
+    /// let parts = board.zobrist_breakdown();
+    ///
+    /// assert_eq!(
+    ///     parts.pieces ^ parts.castling ^ parts.en_passant ^ parts.side_to_move,
+    ///     board.get_hash()
+    /// );
+    /// assert_eq!(parts.en_passant, 0);
+    /// assert_ne!(parts.side_to_move, 0);
+    /// ```
+    pub fn zobrist_breakdown(&self) -> ZobristBreakdown {
+        ZobristBreakdown {
+            pieces: self.hash,
+            castling: Zobrist::castles(self.castle_rights[Color::White.to_index()], Color::White)
+                ^ Zobrist::castles(self.castle_rights[Color::Black.to_index()], Color::Black),
+            en_passant: self.en_passant.map_or(0, |ep| {
+                Zobrist::en_passant(ep.get_file(), !self.side_to_move)
+            }),
+            side_to_move: if self.side_to_move == Color::Black {
+                Zobrist::color()
+            } else {
+                0
+            },
+        }
+    }
+
+    /// A hash that is the same for positions that are mirror images of each other, for spotting
+    /// duplicates in puzzle sets and opening books.  It is the smallest `get_hash` of the
+    /// position and its symmetric copies, with the colors left as they are:
+    ///
+    /// * With castle rights on either side, there are no symmetries, since castling only works
+    ///   one way.  This is the plain `get_hash`.
+    /// * With pawns but no castle rights, the position may be mirrored left to right.
+    /// * With no pawns and no castle rights, the position may also be flipped top to bottom,
+    ///   and turned on its side, for all eight symmetries of the square.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// let a = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// let b = Board::from_str("3k4/8/8/8/8/8/8/3K3R w - - 0 1").unwrap();
+    ///
+    /// assert_ne!(a.get_hash(), b.get_hash());
+    /// assert_eq!(a.canonical_hash(), b.canonical_hash());
+    /// ```
+    pub fn canonical_hash(&self) -> u64 {
+        if self.castle_rights != [CastleRights::NoRights; NUM_COLORS] {
+            return self.get_hash();
+        }
+
+        // Each symmetry maps a (file, rank) pair to a new one.  The first two keep the ranks,
+        // so pawns still move the right way.
+        let symmetries: [Symmetry; 8] = [
+            |f, r| (f, r),
+            |f, r| (7 - f, r),
+            |f, r| (f, 7 - r),
+            |f, r| (7 - f, 7 - r),
+            |f, r| (r, f),
+            |f, r| (7 - r, f),
+            |f, r| (r, 7 - f),
+            |f, r| (7 - r, 7 - f),
+        ];
+        let count = if *self.pieces(Piece::Pawn) == EMPTY {
+            symmetries.len()
+        } else {
+            2
+        };
+
+        let transform = |symmetry: Symmetry, sq: Square| {
+            let (file, rank) = symmetry(sq.get_file().to_index(), sq.get_rank().to_index());
+            Square::make_square(Rank::from_index(rank), File::from_index(file))
+        };
+
+        let mailbox = self.mailbox();
+        symmetries[..count]
+            .iter()
+            .map(|symmetry| {
+                let mut builder = BoardBuilder::new();
+                builder.side_to_move(self.side_to_move);
+                for sq in ALL_SQUARES.iter() {
+                    if let Some((piece, color)) = mailbox[sq.to_index()] {
+                        builder.piece(transform(*symmetry, *sq), piece, color);
+                    }
+                }
+                builder.en_passant(
+                    self.en_passant
+                        .map(|sq| transform(*symmetry, sq).get_file()),
+                );
+                builder.zobrist()
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Get a pawn hash of the board.  This is the same as `pawn_hash()`.
+    #[inline]
+    pub fn get_pawn_hash(&self) -> u64 {
+        self.pawn_hash
+    }
+
+    /// A Zobrist hash of just the pawns and kings, for pawn structure caches.  It is kept up to
+    /// date as moves are made, and only changes when a pawn or king moves, or a pawn is captured
+    /// or promotes.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let board = Board::default();
+    /// let knight = board.make_move_new(ChessMove::new(Square::G1, Square::F3, None));
+    /// let pawn = board.make_move_new(ChessMove::new(Square::E2, Square::E4, None));
+    ///
+    /// assert_eq!(knight.pawn_hash(), board.pawn_hash());
+    /// assert_ne!(pawn.pawn_hash(), board.pawn_hash());
+    /// ```
+    #[inline]
+    pub fn pawn_hash(&self) -> u64 {
+        self.pawn_hash
+    }
+
+    /// The total `Piece::value()` of `color`'s knights, bishops, rooks and queens.  It is kept up
+    /// to date as moves are made.
+    ///
+    /// ```
+    /// use chess::{Board, Color};
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.non_pawn_material(Color::White), 2 * 300 + 2 * 300 + 2 * 500 + 900);
+    /// ```
+    #[inline]
+    pub fn non_pawn_material(&self, color: Color) -> i32 {
+        unsafe { *self.non_pawn_material.get_unchecked(color.to_index()) }
+    }
+
+    /// Does the side to move have nothing but its king and pawns?  That is when zugzwang is
+    /// likely, so a search should not try null-move pruning.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// assert!(!Board::default().zugzwang_risk());
+    ///
+    /// let board = Board::from_str("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    /// assert!(board.zugzwang_risk());
+    /// ```
+    #[inline]
+    pub fn zugzwang_risk(&self) -> bool {
+        self.non_pawn_material(self.side_to_move) == 0
+    }
+
+    /// What piece is on a particular `Square`?  Is there even one?
+    ///
+    /// ```
+    /// use chess::{Board, Piece, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.piece_on(Square::A1), Some(Piece::Rook));
+    /// assert_eq!(board.piece_on(Square::D4), None);
+    /// ```
+    #[inline]
+    pub fn piece_on(&self, square: Square) -> Option<Piece> {
+        let opp = BitBoard::from_square(square);
+        if self.combined() & opp == EMPTY {
+            None
+        } else {
+            //naiive algorithm
+            /*
+            for p in ALL_PIECES {
+                if self.pieces(*p) & opp {
+                    return p;
+                }
+            } */
+            if (self.pieces(Piece::Pawn) ^ self.pieces(Piece::Knight) ^ self.pieces(Piece::Bishop))
+                & opp
+                != EMPTY
+            {
+                if self.pieces(Piece::Pawn) & opp != EMPTY {
+                    Some(Piece::Pawn)
+                } else if self.pieces(Piece::Knight) & opp != EMPTY {
+                    Some(Piece::Knight)
+                } else {
+                    Some(Piece::Bishop)
+                }
+            } else {
+                if self.pieces(Piece::Rook) & opp != EMPTY {
+                    Some(Piece::Rook)
+                } else if self.pieces(Piece::Queen) & opp != EMPTY {
+                    Some(Piece::Queen)
+                } else {
+                    Some(Piece::King)
+                }
+            }
+        }
+    }
+
+    /// What color piece is on a particular square?
+    #[inline]
+    pub fn color_on(&self, square: Square) -> Option<Color> {
+        if (self.color_combined(Color::White) & BitBoard::from_square(square)) != EMPTY {
+            Some(Color::White)
+        } else if (self.color_combined(Color::Black) & BitBoard::from_square(square)) != EMPTY {
+            Some(Color::Black)
+        } else {
+            None
+        }
+    }
+
+    /// Does this position look like it comes from Chess960 (Fischer Random Chess)?
+    ///
+    /// `Board` only supports standard castling, so this goes by piece placement: it is true when
+    /// a side's back rank holds all eight of its original pieces, but not in the standard order.
+    /// Once pieces have left the back rank, a Chess960 game can't be told apart from a standard
+    /// one.  When loading a FEN, `BoardBuilder::is_chess960` also looks at the castling notation.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// assert!(!Board::default().is_chess960());
+    ///
+    /// let board = Board::from_str("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w - - 0 1").unwrap();
+    /// assert!(board.is_chess960());
+    /// ```
+    pub fn is_chess960(&self) -> bool {
+        BoardBuilder::from(self).is_chess960()
+    }
+
+    /// Write the FEN with standard castle rights ("KQkq").  See `BoardBuilder::to_standard_fen`.
+    ///
+    /// A `Board` only allows standard castling, so this only fails for a board that was put
+    /// together without being checked, such as with the deprecated `Board::add_castle_rights`.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// assert_eq!(
+    ///     Board::default().to_standard_fen().unwrap(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// );
+    /// ```
+    pub fn to_standard_fen(&self) -> Result<String, Error> {
+        BoardBuilder::from(self).to_standard_fen()
+    }
+
+    /// Write the FEN with castle rights as the files of the castling rooks (Shredder-FEN).  See
+    /// `BoardBuilder::to_shredder_fen`.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// assert_eq!(
+    ///     Board::default().to_shredder_fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+    /// );
+    /// ```
+    pub fn to_shredder_fen(&self) -> String {
+        BoardBuilder::from(self).to_shredder_fen()
+    }
+
+    /// Pack the board into a few bytes, for storing lots of positions.  The halfmove clock,
+    /// fullmove number and check counts are not kept.  The layout is:
+    ///
+    /// * bytes 0-7: the occupied squares, as a little-endian `u64` with a1 as bit 0
+    /// * byte 8: the side to move, 0 for white and 1 for black
+    /// * byte 9: the castle rights, white's `CastleRights::to_index()` plus black's times 4
+    /// * byte 10: the en passant file, 0-7 for a-h, or 255 for none
+    /// * then 4 bits for each occupied square, from a1 to h8, two to a byte with the low bits
+    ///   first: the `Piece::to_index()`, plus 8 for black
+    ///
+    /// So a position with all 32 pieces takes 27 bytes.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let bytes = Board::default().to_bytes();
+    /// assert_eq!(bytes.len(), 27);
+    /// assert_eq!(Board::from_bytes(&bytes).unwrap(), Board::default());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.combined().0.to_le_bytes().to_vec();
+        bytes.push(self.side_to_move.to_index() as u8);
+        bytes.push(
+            (self.castle_rights(Color::White).to_index()
+                | self.castle_rights(Color::Black).to_index() << 2) as u8,
+        );
+        bytes.push(
+            self.en_passant_file()
+                .map_or(255, |file| file.to_index() as u8),
+        );
+
+        for (i, sq) in self.combined().enumerate() {
+            // every square in `combined` has a piece on it
+            let piece = self.piece_on(sq).unwrap();
+            let color = self.color_on(sq).unwrap();
+            let code = (piece.to_index() | color.to_index() << 3) as u8;
+            if i % 2 == 0 {
+                bytes.push(code);
+            } else {
+                *bytes.last_mut().unwrap() |= code << 4;
+            }
+        }
+
+        bytes
+    }
+
+    /// Unpack a board written by `to_bytes`.  Fails with `Error::InvalidBoardBytes` if the bytes
+    /// can't be decoded, or `Error::InvalidBoard` if they decode to an illegal position.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq e3 0 1").unwrap();
+    /// assert_eq!(Board::from_bytes(&board.to_bytes()).unwrap(), board);
+    ///
+    /// assert!(Board::from_bytes(&[]).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Board, Error> {
+        if bytes.len() < 11 {
+            return Err(Error::InvalidBoardBytes);
+        }
+        let mut occupied = [0; 8];
+        occupied.copy_from_slice(&bytes[..8]);
+        let occupied = BitBoard::new(u64::from_le_bytes(occupied));
+        let codes = &bytes[11..];
+        if codes.len() != (occupied.popcnt() as usize).div_ceil(2) || bytes[8] > 1 || bytes[9] > 15
+        {
+            return Err(Error::InvalidBoardBytes);
+        }
+
+        let mut builder = BoardBuilder::new();
+        builder
+            .side_to_move(ALL_COLORS[bytes[8] as usize])
+            .castle_rights(
+                Color::White,
+                CastleRights::from_index(bytes[9] as usize & 3),
+            )
+            .castle_rights(
+                Color::Black,
+                CastleRights::from_index(bytes[9] as usize >> 2),
+            );
+        match bytes[10] {
+            255 => {}
+            file if file < 8 => {
+                builder.en_passant(Some(File::from_index(file as usize)));
+            }
+            _ => return Err(Error::InvalidBoardBytes),
+        }
+
+        for (i, sq) in occupied.enumerate() {
+            let code = (codes[i / 2] >> (i % 2 * 4)) as usize & 15;
+            if code & 7 >= NUM_PIECES {
+                return Err(Error::InvalidBoardBytes);
+            }
+            builder.piece(sq, ALL_PIECES[code & 7], ALL_COLORS[code >> 3]);
+        }
+
+        Board::try_from(builder)
+    }
+
+    /// What is on every square, indexed by `Square::to_index()`?  This is built in a single
+    /// pass over the `BitBoard`s, so it is cheaper than calling `piece_on` and `color_on` for all
+    /// 64 squares.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Piece, Square};
+    ///
+    /// let mailbox = Board::default().mailbox();
+    ///
+    /// assert_eq!(mailbox[0], Some((Piece::Rook, Color::White)));
+    /// assert_eq!(mailbox[Square::E8.to_index()], Some((Piece::King, Color::Black)));
+    /// assert_eq!(mailbox[Square::E4.to_index()], None);
+    /// ```
+    pub fn mailbox(&self) -> [Option<(Piece, Color)>; 64] {
+        let mut result = [None; 64];
+        for piece in ALL_PIECES.iter() {
+            for color in ALL_COLORS.iter() {
+                for sq in self.pieces(*piece) & self.color_combined(*color) {
+                    result[sq.to_index()] = Some((*piece, *color));
+                }
+            }
+        }
+        result
+    }
+
+    /// Unset the en_passant square.
+    fn remove_ep(&mut self) {
+        self.en_passant = None;
+    }
+
+    /// Give me the en_passant square, if it exists.
+    ///
+    /// Note: this is the square of the pawn that may be captured, not the square the capturing
+    /// pawn moves to.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let move1 = ChessMove::new(Square::D2,
+    ///                            Square::D4,
+    ///                            None);
+    ///
+    /// let move2 = ChessMove::new(Square::H7,
+    ///                            Square::H5,
+    ///                            None);
+    ///
+    /// let move3 = ChessMove::new(Square::D4,
+    ///                            Square::D5,
+    ///                            None);
+    ///
+    /// let move4 = ChessMove::new(Square::E7,
+    ///                            Square::E5,
+    ///                            None);
+    ///
+    /// let board = Board::default().make_move_new(move1)
+    ///                             .make_move_new(move2)
+    ///                             .make_move_new(move3)
+    ///                             .make_move_new(move4);
+    ///
+    /// assert_eq!(board.en_passant(), Some(Square::E5));
+    /// ```
+    #[inline]
+    pub fn en_passant(self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// Give me the square of the pawn that can be captured en passant, if any.
+    ///
+    /// This is the square the pawn landed on after its double push, which is one rank behind the
+    /// en-passant target square written in a FEN.  It's the same square `en_passant()` returns,
+    /// under a name that doesn't leave you guessing.
+    ///
+    /// ```
+    /// use chess::{Board, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3")
+    ///     .expect("Valid FEN");
+    ///
+    /// assert_eq!(board.en_passant_victim(), Some(Square::E5));
+    /// assert_eq!(Board::default().en_passant_victim(), None);
+    /// ```
+    #[inline]
+    pub fn en_passant_victim(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// Give me the file on which an en passant capture can be made, if any.  This is all that
+    /// goes into the hash, and all a FEN needs, since the rank follows from the side to move.
+    ///
+    /// ```
+    /// use chess::{Board, File};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3")
+    ///     .expect("Valid FEN");
+    ///
+    /// assert_eq!(board.en_passant_file(), Some(File::E));
+    /// assert_eq!(Board::default().en_passant_file(), None);
+    /// ```
+    #[inline]
+    pub fn en_passant_file(&self) -> Option<File> {
+        self.en_passant.map(|sq| sq.get_file())
+    }
+
+    /// Would this move give the opponent the chance to capture en passant?  If so, this returns
+    /// the en passant target square, the one the pawn skipped over, as written in a FEN.  Like
+    /// the en passant square of a `Board`, this is only set when an enemy pawn stands next to
+    /// the pawn's destination, ready to make the capture.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/3p4/8/2P1P3/4K3 w - - 0 1").unwrap();
+    ///
+    /// // The d4 pawn could take on e3 or c3
+    /// let e2e4 = ChessMove::new(Square::E2, Square::E4, None);
+    /// assert_eq!(board.creates_en_passant(e2e4), Some(Square::E3));
+    ///
+    /// // A single push never does
+    /// let e2e3 = ChessMove::new(Square::E2, Square::E3, None);
+    /// assert_eq!(board.creates_en_passant(e2e3), None);
+    /// ```
+    pub fn creates_en_passant(&self, m: ChessMove) -> Option<Square> {
+        let color = self.side_to_move;
+        let source = m.get_source();
+        let dest = m.get_dest();
+        if self.piece_on(source) != Some(Piece::Pawn)
+            || source.get_rank() != color.to_second_rank()
+            || dest != Square::make_square(color.to_fourth_rank(), source.get_file())
+        {
+            return None;
+        }
+
+        let enemy_pawns = self.pieces(Piece::Pawn) & self.color_combined(!color);
+        if get_adjacent_files(dest.get_file()) & get_rank(dest.get_rank()) & enemy_pawns == EMPTY {
+            return None;
+        }
+        Some(between(source, dest).to_square())
+    }
+
+    /// Set the en_passant square.  Note: This must only be called when self.en_passant is already
+    /// None.
+    fn set_ep(&mut self, sq: Square) {
+        // Only set self.en_passant if the pawn can actually be captured next move.
+        if get_adjacent_files(sq.get_file())
+            & get_rank(sq.get_rank())
+            & self.pieces(Piece::Pawn)
+            & self.color_combined(!self.side_to_move)
+            != EMPTY
+        {
+            self.en_passant = Some(sq);
+        }
+    }
+
+    /// Is a particular move legal?  This function is very slow, but will work on unsanitized
+    /// input.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square, MoveGen};
+    ///
+    /// let move1 = ChessMove::new(Square::E2,
+    ///                            Square::E4,
+    ///                            None);
+    ///
+    /// let move2 = ChessMove::new(Square::E2,
+    ///                            Square::E5,
+    ///                            None);
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.legal(move1), true);
+    /// assert_eq!(board.legal(move2), false);
+    /// ```
+    #[inline]
+    pub fn legal(&self, m: ChessMove) -> bool {
+        MoveGen::new_legal(&self).find(|x| *x == m).is_some()
+    }
+
+    /// Every legal move, written in UCI notation (such as "e2e4" or "e7e8q"), in `MoveGen`
+    /// order.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let moves = Board::default().legal_uci_moves();
+    ///
+    /// assert_eq!(moves.len(), 20);
+    /// assert!(moves.contains(&"e2e4".to_string()));
+    /// assert!(moves.contains(&"g1f3".to_string()));
+    /// ```
+    pub fn legal_uci_moves(&self) -> Vec<String> {
+        MoveGen::new_legal(self).map(|m| m.to_string()).collect()
+    }
+
+    /// Keep only the legal moves from `candidates`, such as moves read from an opening book, in
+    /// their original order.  This generates the legal moves once, so it is much faster than
+    /// calling `legal` for each candidate.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let e2e4 = ChessMove::new(Square::E2, Square::E4, None);
+    /// let e2e5 = ChessMove::new(Square::E2, Square::E5, None);
+    ///
+    /// assert_eq!(Board::default().filter_legal(&[e2e5, e2e4]), vec![e2e4]);
+    /// ```
+    pub fn filter_legal(&self, candidates: &[ChessMove]) -> Vec<ChessMove> {
+        let legal: Vec<ChessMove> = MoveGen::new_legal(self).collect();
+        candidates
+            .iter()
+            .filter(|m| legal.contains(m))
+            .copied()
+            .collect()
+    }
+
+    /// Does `m` get the side to move out of check?  This is false whenever the side to move is
+    /// not in check.  Otherwise, `m` must move the king to safety, block the check, or take the
+    /// only checking piece.  Like `legal`, this works on unsanitized input.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The e8 rook checks the white king
+    /// let board = Board::from_str("4r1k1/8/8/8/8/8/3N4/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(board.is_evasion(ChessMove::new(Square::D2, Square::E4, None)));
+    /// assert!(!board.is_evasion(ChessMove::new(Square::D2, Square::F3, None)));
+    /// assert!(!Board::default().is_evasion(ChessMove::new(Square::E2, Square::E4, None)));
+    /// ```
+    pub fn is_evasion(&self, m: ChessMove) -> bool {
+        self.checkers != EMPTY && self.legal(m)
+    }
+
+    /// Convert a legal move into SAN (Standard Algebraic Notation), including the `+` or `#`
+    /// suffix.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.san(ChessMove::new(Square::G1, Square::F3, None)), "Nf3");
+    /// assert_eq!(board.san(ChessMove::new(Square::E2, Square::E4, None)), "e4");
+    /// ```
+    #[inline]
+    pub fn san(&self, m: ChessMove) -> String {
+        self.san_with_suffix(m, true)
+    }
+
+    /// Convert a legal move into SAN (Standard Algebraic Notation).
+    ///
+    /// Deciding between `+` and `#` requires checking if the opponent has any legal replies,
+    /// which is the expensive part of producing SAN.  When `include_suffix` is false, the check
+    /// and checkmate markers are skipped entirely.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("rnbqkbnr/ppppp2p/5p2/6p1/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3")
+    ///     .expect("Valid FEN");
+    /// let mate = ChessMove::new(Square::D1, Square::H5, None);
+    ///
+    /// assert_eq!(board.san_with_suffix(mate, true), "Qh5#");
+    /// assert_eq!(board.san_with_suffix(mate, false), "Qh5");
+    /// ```
+    pub fn san_with_suffix(&self, m: ChessMove, include_suffix: bool) -> String {
+        let source = m.get_source();
+        let dest = m.get_dest();
+        let piece = self.piece_on(source).unwrap();
+        let move_bb = BitBoard::from_square(source) ^ BitBoard::from_square(dest);
+
+        let mut san = if piece == Piece::King && move_bb & get_castle_moves() == move_bb {
+            if dest.get_file() == File::G {
+                "O-O".to_owned()
+            } else {
+                "O-O-O".to_owned()
+            }
+        } else if piece == Piece::Pawn {
+            let mut san = String::new();
+            if source.get_file() != dest.get_file() {
+                san.push_str(&source.to_string()[..1]);
+                san.push('x');
+            }
+            san.push_str(&dest.to_string());
+            if let Some(promotion) = m.get_promotion() {
+                san.push('=');
+                san.push_str(&promotion.to_string(Color::White));
+            }
+            san
+        } else {
+            let mut san = piece.to_string(Color::White);
+
+            // Are there any other pieces of the same type that can move to the same square?
+            let mut ambiguous = false;
+            let mut same_file = false;
+            let mut same_rank = false;
+            for other in MoveGen::new_legal(self) {
+                if other.get_dest() == dest
+                    && other.get_source() != source
+                    && self.piece_on(other.get_source()) == Some(piece)
+                {
+                    ambiguous = true;
+                    same_file |= other.get_source().get_file() == source.get_file();
+                    same_rank |= other.get_source().get_rank() == source.get_rank();
+                }
+            }
+
+            if ambiguous {
+                let source_str = source.to_string();
+                if !same_file {
+                    san.push_str(&source_str[..1]);
+                } else if !same_rank {
+                    san.push_str(&source_str[1..]);
+                } else {
+                    san.push_str(&source_str);
+                }
+            }
+
+            if self.piece_on(dest).is_some() {
+                san.push('x');
+            }
+            san.push_str(&dest.to_string());
+            san
+        };
+
+        if include_suffix {
+            let result = self.make_move_new(m);
+            if *result.checkers() != EMPTY {
+                if result.status() == BoardStatus::Checkmate {
+                    san.push('#');
+                } else {
+                    san.push('+');
+                }
+            }
+        }
+
+        san
+    }
+
+    /// Make a chess move onto a new board.
+    ///
+    /// panic!() if king is captured.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square, Color};
+    ///
+    /// let m = ChessMove::new(Square::D2,
+    ///                        Square::D4,
+    ///                        None);
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.make_move_new(m).side_to_move(), Color::Black);
+    /// ```
+    #[inline]
+    pub fn make_move_new(&self, m: ChessMove) -> Board {
+        let mut result = mem::MaybeUninit::<Board>::uninit();
+        unsafe {
+            self.make_move(m, &mut *result.as_mut_ptr());
+            result.assume_init()
+        }
+    }
+
+    /// Play a sequence of SAN moves, one after another, and give me the resulting `Board`.
+    ///
+    /// If a move can't be played, this returns `Error::InvalidSanSequence` with the index of
+    /// that move in `moves`.
+    ///
+    /// ```
+    /// use chess::{Board, BoardStatus, Error};
+    ///
+    /// let board = Board::default()
+    ///     .apply_san_sequence(&["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"])
+    ///     .expect("Legal moves");
+    /// assert_eq!(board.status(), BoardStatus::Checkmate);
+    ///
+    /// match Board::default().apply_san_sequence(&["e4", "e5", "Ke3"]) {
+    ///     Err(Error::InvalidSanSequence { index, .. }) => assert_eq!(index, 2),
+    ///     _ => panic!("Ke3 is illegal"),
+    /// }
+    /// ```
+    pub fn apply_san_sequence(&self, moves: &[&str]) -> Result<Board, Error> {
+        let mut board = *self;
+        for (index, san) in moves.iter().enumerate() {
+            let m = ChessMove::from_san(&board, san).map_err(|_| Error::InvalidSanSequence {
+                index,
+                san: san.to_string(),
+            })?;
+            board = board.make_move_new(m);
+        }
+        Ok(board)
+    }
+
+    /// Play a sequence of moves, one after another, checking each one, and give me the resulting
+    /// `Board`.  If a move isn't legal, this returns its index in `moves`, along with the error
+    /// from `make_move_checked`.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Error, Square};
+    ///
+    /// let e2e4 = ChessMove::new(Square::E2, Square::E4, None);
+    /// let e7e5 = ChessMove::new(Square::E7, Square::E5, None);
+    ///
+    /// let board = Board::default().try_play(&[e2e4, e7e5]).unwrap();
+    /// assert_eq!(board, Board::default().make_move_new(e2e4).make_move_new(e7e5));
+    ///
+    /// assert!(matches!(
+    ///     Board::default().try_play(&[e2e4, e2e4]),
+    ///     Err((1, Error::IllegalMove))
+    /// ));
+    /// ```
+    pub fn try_play(&self, moves: &[ChessMove]) -> Result<Board, (usize, Error)> {
+        let mut board = *self;
+        for (index, m) in moves.iter().enumerate() {
+            board = board.make_move_checked(*m).map_err(|e| (index, e))?;
+        }
+        Ok(board)
+    }
+
+    /// Turn a line of UCI moves, such as an engine's principal variation, into SAN.  Each move
+    /// is written against the position before it, and then played.
+    ///
+    /// This returns whatever error `ChessMove::from_str` gives for a move that can't be parsed,
+    /// or the error from `make_move_checked` for a move that isn't legal where it is played.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let san = Board::default().uci_line_to_san(&["e2e4", "e7e5", "g1f3"]).unwrap();
+    /// assert_eq!(san, vec!["e4", "e5", "Nf3"]);
+    /// ```
+    pub fn uci_line_to_san(&self, uci_moves: &[&str]) -> Result<Vec<String>, Error> {
+        let mut board = *self;
+        let mut result = Vec::with_capacity(uci_moves.len());
+        for uci in uci_moves {
+            let m = ChessMove::from_str(uci)?;
+            let next = board.make_move_checked(m)?;
+            result.push(board.san(m));
+            board = next;
+        }
+        Ok(result)
+    }
+
+    /// Make a chess move onto a new board, checking that it is legal first.  Unlike
+    /// `make_move_new`, this is safe to call with moves from untrusted input, but it is much
+    /// slower.
+    ///
+    /// If the piece can legally go to the destination square, but not with the promotion given
+    /// (or without one), this returns `Error::InvalidPromotion`.  Any other illegal move gives
+    /// `Error::IllegalMove`.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Error, Piece, Square, Color};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let m = ChessMove::new(Square::D2, Square::D4, None);
+    /// assert_eq!(board.make_move_checked(m).unwrap().side_to_move(), Color::Black);
+    ///
+    /// let m = ChessMove::new(Square::D2, Square::D5, None);
+    /// assert!(matches!(board.make_move_checked(m), Err(Error::IllegalMove)));
+    ///
+    /// let m = ChessMove::new(Square::D2, Square::D4, Some(Piece::Queen));
+    /// assert!(matches!(board.make_move_checked(m), Err(Error::InvalidPromotion)));
+    /// ```
+    pub fn make_move_checked(&self, m: ChessMove) -> Result<Board, Error> {
+        if self.legal(m) {
+            Ok(self.make_move_new(m))
+        } else if MoveGen::new_legal(self)
+            .any(|x| x.get_source() == m.get_source() && x.get_dest() == m.get_dest())
+        {
+            Err(Error::InvalidPromotion)
+        } else {
+            Err(Error::IllegalMove)
+        }
+    }
+
+    /// Make a chess move onto an already allocated `Board`.
+    ///
+    /// The hash is updated as the pieces move, rather than worked out again from scratch.  Only
+    /// the pieces are hashed as they go: castle rights, en passant and the side to move are
+    /// mixed in by `get_hash`.  Debug builds check the result against a full recomputation.
+    ///
+    /// panic!() if king is captured.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square, Color};
+    ///
+    /// let m = ChessMove::new(Square::D2,
+    ///                        Square::D4,
+    ///                        None);
+    ///
+    /// let board = Board::default();
+    /// let mut result = Board::default();
+    /// board.make_move(m, &mut result);
+    /// assert_eq!(result.side_to_move(), Color::Black);
+    /// ```
+    #[inline]
+    pub fn make_move(&self, m: ChessMove, result: &mut Board) {
+        *result = *self;
+        result.remove_ep();
+        result.checkers = EMPTY;
+        result.pinned = EMPTY;
+        let source = m.get_source();
+        let dest = m.get_dest();
+
+        let source_bb = BitBoard::from_square(source);
+        let dest_bb = BitBoard::from_square(dest);
+        let move_bb = source_bb ^ dest_bb;
+        let moved = self.piece_on(source).unwrap();
+
+        result.xor(moved, source_bb, self.side_to_move);
+        result.xor(moved, dest_bb, self.side_to_move);
+        if let Some(captured) = self.piece_on(dest) {
+            result.xor(captured, dest_bb, !self.side_to_move);
+        }
+
+        #[allow(deprecated)]
+        result.remove_their_castle_rights(CastleRights::square_to_castle_rights(
+            !self.side_to_move,
+            dest,
+        ));
+
+        #[allow(deprecated)]
+        result.remove_my_castle_rights(CastleRights::square_to_castle_rights(
+            self.side_to_move,
+            source,
+        ));
+
+        let opp_king = result.pieces(Piece::King) & result.color_combined(!result.side_to_move);
+
+        let castles = moved == Piece::King && (move_bb & get_castle_moves()) == move_bb;
+
+        let ksq = opp_king.to_square();
+
+        const CASTLE_ROOK_START: [File; 8] = [
+            File::A,
+            File::A,
+            File::A,
+            File::A,
+            File::H,
+            File::H,
+            File::H,
+            File::H,
+        ];
+        const CASTLE_ROOK_END: [File; 8] = [
+            File::D,
+            File::D,
+            File::D,
+            File::D,
+            File::F,
+            File::F,
+            File::F,
+            File::F,
+        ];
+
+        if moved == Piece::Knight {
+            result.checkers ^= get_knight_moves(ksq) & dest_bb;
+        } else if moved == Piece::Pawn {
+            if let Some(Piece::Knight) = m.get_promotion() {
+                result.xor(Piece::Pawn, dest_bb, self.side_to_move);
+                result.xor(Piece::Knight, dest_bb, self.side_to_move);
+                result.checkers ^= get_knight_moves(ksq) & dest_bb;
+            } else if let Some(promotion) = m.get_promotion() {
+                result.xor(Piece::Pawn, dest_bb, self.side_to_move);
+                result.xor(promotion, dest_bb, self.side_to_move);
+            } else if (source_bb & get_pawn_source_double_moves()) != EMPTY
+                && (dest_bb & get_pawn_dest_double_moves()) != EMPTY
+            {
+                result.set_ep(dest);
+                result.checkers ^= get_pawn_attacks(ksq, !result.side_to_move, dest_bb);
+            } else if Some(dest.ubackward(self.side_to_move)) == self.en_passant {
+                result.xor(
+                    Piece::Pawn,
+                    BitBoard::from_square(dest.ubackward(self.side_to_move)),
+                    !self.side_to_move,
+                );
+                result.checkers ^= get_pawn_attacks(ksq, !result.side_to_move, dest_bb);
+            } else {
+                result.checkers ^= get_pawn_attacks(ksq, !result.side_to_move, dest_bb);
+            }
+        } else if castles {
+            let my_backrank = self.side_to_move.to_my_backrank();
+            let index = dest.get_file().to_index();
+            let start = BitBoard::set(my_backrank, unsafe {
+                *CASTLE_ROOK_START.get_unchecked(index)
+            });
+            let end = BitBoard::set(my_backrank, unsafe {
+                *CASTLE_ROOK_END.get_unchecked(index)
+            });
+            result.xor(Piece::Rook, start, self.side_to_move);
+            result.xor(Piece::Rook, end, self.side_to_move);
+        }
+        // now, lets see if we're in check or pinned
+        let attackers = result.color_combined(result.side_to_move)
+            & ((get_bishop_rays(ksq)
+                & (result.pieces(Piece::Bishop) | result.pieces(Piece::Queen)))
+                | (get_rook_rays(ksq)
+                    & (result.pieces(Piece::Rook) | result.pieces(Piece::Queen))));
+
+        for sq in attackers {
+            let between = between(sq, ksq) & result.combined();
+            if between == EMPTY {
+                result.checkers ^= BitBoard::from_square(sq);
+            } else if between.popcnt() == 1 {
+                result.pinned ^= between;
+            }
+        }
+
+        if result.checkers != EMPTY {
+            if let Some(ref mut counts) = result.check_counts {
+                let count = &mut counts[self.side_to_move.to_index()];
+                *count = count.saturating_add(1);
+            }
+        }
+
+        result.side_to_move = !result.side_to_move;
+
+        debug_assert_eq!(
+            result.hash,
+            result.compute_hash(),
+            "hash was not updated correctly by {}",
+            m
+        );
+    }
+
+    /// Update the pin information.
+    fn update_pin_info(&mut self) {
+        self.pinned = EMPTY;
+        self.checkers = EMPTY;
+
+        // A hand-built board may be missing its king.  `is_sane` will reject it, but we must not
+        // index the lookup tables with a bogus square on the way there.
+        let king = self.pieces(Piece::King) & self.color_combined(self.side_to_move);
+        if king.popcnt() != 1 {
+            return;
+        }
+        let ksq = king.to_square();
+
+        let pinners = self.color_combined(!self.side_to_move)
+            & ((get_bishop_rays(ksq) & (self.pieces(Piece::Bishop) | self.pieces(Piece::Queen)))
+                | (get_rook_rays(ksq) & (self.pieces(Piece::Rook) | self.pieces(Piece::Queen))));
+
+        for sq in pinners {
+            let between = between(sq, ksq) & self.combined();
+            if between == EMPTY {
+                self.checkers ^= BitBoard::from_square(sq);
+            } else if between.popcnt() == 1 {
+                self.pinned ^= between;
+            }
+        }
+
+        self.checkers ^= get_knight_moves(ksq)
+            & self.color_combined(!self.side_to_move)
+            & self.pieces(Piece::Knight);
+
+        self.checkers ^= get_pawn_attacks(
+            ksq,
+            self.side_to_move,
+            self.color_combined(!self.side_to_move) & self.pieces(Piece::Pawn),
+        );
+    }
+
+    /// Give me the `BitBoard` of my pinned pieces.
+    #[inline]
+    pub fn pinned(&self) -> &BitBoard {
+        &self.pinned
+    }
+
+    /// Give me the `BitBoard` of enemy sliders that pin a piece of `color` to `color`s king.
+    ///
+    /// When `color` is the side to move, these are the pieces responsible for `pinned()`.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The e7 knight is pinned by the e1 rook
+    /// let board = Board::from_str("4k3/4n3/8/8/8/8/8/4RK2 b - - 0 1").unwrap();
+    ///
+    /// assert_eq!(board.pinners(Color::Black), BitBoard::from_square(Square::E1));
+    /// assert_eq!(*board.pinned(), BitBoard::from_square(Square::E7));
+    /// ```
+    pub fn pinners(&self, color: Color) -> BitBoard {
+        let ksq = self.king_square(color);
+
+        let sliders = self.color_combined(!color)
+            & ((get_bishop_rays(ksq) & (self.pieces(Piece::Bishop) | self.pieces(Piece::Queen)))
+                | (get_rook_rays(ksq) & (self.pieces(Piece::Rook) | self.pieces(Piece::Queen))));
+
+        let mut result = EMPTY;
+        for sq in sliders {
+            let between = between(sq, ksq) & self.combined();
+            if between.popcnt() == 1 && between & self.color_combined(color) != EMPTY {
+                result ^= BitBoard::from_square(sq);
+            }
+        }
+        result
+    }
+
+    /// Every pinned piece of the side to move, with the squares it is still allowed to move to
+    /// along its pin: the squares between its king and the pinner, and the pinner itself.  The
+    /// pinned piece's own square is part of the ray.  Pins come in order of the pinner's square.
+    ///
+    /// ```
+    /// use chess::{BitBoard, Board, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The e7 knight is pinned by the e5 rook
+    /// let board = Board::from_str("4k3/4n3/8/4R3/8/8/8/5K2 b - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.pins(),
+    ///     vec![(
+    ///         Square::E7,
+    ///         BitBoard::from_square(Square::E7)
+    ///             | BitBoard::from_square(Square::E6)
+    ///             | BitBoard::from_square(Square::E5)
+    ///     )]
+    /// );
+    /// ```
+    pub fn pins(&self) -> Vec<(Square, BitBoard)> {
+        let ksq = self.king_square(self.side_to_move);
+        self.pinners(self.side_to_move)
+            .map(|pinner| {
+                let between = between(pinner, ksq);
+                (
+                    (between & self.combined()).to_square(),
+                    between | BitBoard::from_square(pinner),
+                )
+            })
+            .collect()
+    }
+
+    /// Every enemy slider that lines up with `color`s king along a line it moves on, with the
+    /// squares between the two.  It doesn't matter what stands on those squares, so this
+    /// includes sliders giving check, real pins (see `pinners`), and lines with several pieces in
+    /// the way that could become pins later.  The sliders come in order of their square.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The a8 rook doesn't line up with the e1 king, but the e8 one does
+    /// let board = Board::from_str("r3r1k1/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    ///
+    /// let lines = board.potential_pin_lines(Color::White);
+    /// assert_eq!(lines.len(), 1);
+    /// assert_eq!(lines[0].0, Square::E8);
+    /// assert_eq!(lines[0].1.popcnt(), 6);
+    /// ```
+    pub fn potential_pin_lines(&self, color: Color) -> Vec<(Square, BitBoard)> {
+        let ksq = self.king_square(color);
+        let sliders = self.color_combined(!color)
+            & ((get_bishop_rays(ksq) & (self.pieces(Piece::Bishop) | self.pieces(Piece::Queen)))
+                | (get_rook_rays(ksq) & (self.pieces(Piece::Rook) | self.pieces(Piece::Queen))));
+
+        sliders.map(|sq| (sq, between(sq, ksq))).collect()
+    }
+
+    /// Give me the `Bitboard` of the pieces putting me in check.
+    #[inline]
+    pub fn checkers(&self) -> &BitBoard {
+        &self.checkers
+    }
+
+    /// How many pieces are putting me in check?  Legal positions never have more than two, but
+    /// a hand-built position may.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.checker_count(), 0);
+    /// ```
+    #[inline]
+    pub fn checker_count(&self) -> u32 {
+        self.checkers.popcnt()
+    }
+
+    /// The squares a piece other than the king may move to without leaving the king in check:
+    /// the checking piece, and the squares between it and the king.  This is every square when
+    /// not in check, and no squares in double check, where only the king can move.
+    ///
+    /// An en passant capture of a checking pawn lands behind the pawn, not on it, so custom
+    /// move generators have to allow for that themselves.  Pins are not taken into account.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The a1 rook checks the king on e1
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+    /// let expected = [Square::A1, Square::B1, Square::C1, Square::D1]
+    ///     .iter()
+    ///     .fold(BitBoard::new(0), |bb, sq| bb | BitBoard::from_square(*sq));
+    /// assert_eq!(board.check_block_mask(), expected);
+    ///
+    /// assert_eq!(Board::default().check_block_mask(), !BitBoard::new(0));
+    /// ```
+    pub fn check_block_mask(&self) -> BitBoard {
+        match self.checkers.popcnt() {
+            0 => !EMPTY,
+            1 => {
+                let checker = self.checkers.to_square();
+                self.checkers | between(checker, self.king_square(self.side_to_move))
+            }
+            _ => EMPTY,
+        }
+    }
+
+    /// Am I in check?
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    ///
+    /// let board = Board::default()
+    ///     .make_move_new(ChessMove::new(Square::F2, Square::F3, None))
+    ///     .make_move_new(ChessMove::new(Square::E7, Square::E5, None))
+    ///     .make_move_new(ChessMove::new(Square::G2, Square::G4, None))
+    ///     .make_move_new(ChessMove::new(Square::D8, Square::H4, None));
+    ///
+    /// assert!(board.in_check());
+    /// assert!(!board.in_double_check());
+    /// ```
+    #[inline]
+    pub fn in_check(&self) -> bool {
+        self.checkers != EMPTY
+    }
+
+    /// Am I in check from more than one piece?  When this is true, only king moves are legal.
+    #[inline]
+    pub fn in_double_check(&self) -> bool {
+        self.checker_count() > 1
+    }
+
+    /// For each piece putting me in check, the squares between it and my king.  These are the
+    /// squares a check could be blocked on.  Knights and pawns are next to the king or a knight's
+    /// jump away, so their ray is empty.
+    ///
+    /// The rays are in the same order as the squares of `checkers()`.
+    ///
+    /// ```
+    /// use chess::{Board, EMPTY};
+    /// use std::str::FromStr;
+    ///
+    /// // A knight check
+    /// let board = Board::from_str("4k3/8/8/8/8/5n2/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.check_rays(), vec![EMPTY]);
+    ///
+    /// assert!(Board::default().check_rays().is_empty());
+    /// ```
+    pub fn check_rays(&self) -> Vec<BitBoard> {
+        let ksq = self.king_square(self.side_to_move);
+        self.checkers
+            .into_iter()
+            .map(|checker| between(checker, ksq))
+            .collect()
+    }
+
+    /// What squares does a `piece` of `color` on `sq` attack, given the current occupancy?
+    fn piece_attacks(&self, piece: Piece, color: Color, sq: Square) -> BitBoard {
+        Board::piece_attacks_through(piece, color, sq, *self.combined())
+    }
+
+    /// What squares does a `piece` of `color` on `sq` attack, if the board held only `occupied`?
+    fn piece_attacks_through(
+        piece: Piece,
+        color: Color,
+        sq: Square,
+        occupied: BitBoard,
+    ) -> BitBoard {
+        match piece {
+            Piece::Pawn => get_pawn_attacks(sq, color, !EMPTY),
+            Piece::Knight => get_knight_moves(sq),
+            Piece::Bishop => get_bishop_moves(sq, occupied),
+            Piece::Rook => get_rook_moves(sq, occupied),
+            Piece::Queen => get_bishop_moves(sq, occupied) | get_rook_moves(sq, occupied),
+            Piece::King => get_king_moves(sq),
+        }
+    }
+
+    /// What is on `sq`, and which squares does it attack?  This is everything a GUI needs to
+    /// highlight a piece's attacks when the mouse hovers over it.  Returns `None` for an empty
+    /// square.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Piece, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let (piece, color, attacks) = board.hover_info(Square::G1).unwrap();
+    /// assert_eq!((piece, color), (Piece::Knight, Color::White));
+    /// assert_eq!(attacks.popcnt(), 3);
+    ///
+    /// assert_eq!(board.hover_info(Square::E4), None);
+    /// ```
+    pub fn hover_info(&self, sq: Square) -> Option<(Piece, Color, BitBoard)> {
+        let piece = self.piece_on(sq)?;
+        let color = self.color_on(sq)?;
+        Some((piece, color, self.piece_attacks(piece, color, sq)))
+    }
+
+    /// Every square attacked by a `piece` of `color`, given the current occupancy.  Pawns attack
+    /// the squares they could capture on, not the squares they push to.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Piece, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let pawn_attacks = board.piece_type_attacks(Piece::Pawn, Color::White);
+    /// assert_eq!(pawn_attacks, chess::get_rank(chess::Rank::Third));
+    ///
+    /// // The rooks are boxed in, but still attack the pieces next to them
+    /// assert_eq!(board.piece_type_attacks(Piece::Rook, Color::Black).popcnt(), 4);
+    /// ```
+    pub fn piece_type_attacks(&self, piece: Piece, color: Color) -> BitBoard {
+        let mut result = EMPTY;
+        for src in self.pieces(piece) & self.color_combined(color) {
+            result |= self.piece_attacks(piece, color, src);
+        }
+        result
+    }
+
+    /// For every square, count how many pieces of `color` attack it.  The result is indexed by
+    /// `Square::to_index()`.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Square};
+    ///
+    /// let board = Board::default();
+    /// let control = board.control(Color::White);
+    ///
+    /// // The g1 knight, and the e2 and g2 pawns
+    /// assert_eq!(control[Square::F3.to_index()], 3);
+    /// assert_eq!(control[Square::E4.to_index()], 0);
+    /// ```
+    pub fn control(&self, color: Color) -> [u8; 64] {
+        let mut result = [0u8; 64];
+        for piece in ALL_PIECES.iter() {
+            for src in self.pieces(*piece) & self.color_combined(color) {
+                for dest in self.piece_attacks(*piece, color, src) {
+                    result[dest.to_index()] += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// Give me the `BitBoard` of every piece of `color` that attacks at least one enemy piece.
+    /// Pins are ignored.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The d5 pawn attacks the e6 knight, but the a1 rook attacks nothing
+    /// let board = Board::from_str("4k3/8/4n3/3P4/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.attackers(Color::White), BitBoard::from_square(Square::D5));
+    /// ```
+    pub fn attackers(&self, color: Color) -> BitBoard {
+        let targets = *self.color_combined(!color);
+        let mut result = EMPTY;
+        for piece in ALL_PIECES.iter() {
+            for src in self.pieces(*piece) & self.color_combined(color) {
+                if self.piece_attacks(*piece, color, src) & targets != EMPTY {
+                    result |= BitBoard::from_square(src);
+                }
+            }
+        }
+        result
+    }
+
+    /// How many king moves does `color`s king need to reach `target`, if everything else stays
+    /// where it is?  The king may only walk over squares that are free of its own pieces and not
+    /// attacked by the enemy.  Enemy pieces on undefended squares may be taken on the way.
+    /// Returns `None` if `target` can't be reached at all.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("7k/8/8/8/4P3/8/8/4K3 w - - 0 1").unwrap();
+    ///
+    /// // e6 is a key square for the e4 pawn
+    /// assert_eq!(board.king_distance_to(Color::White, Square::E6), Some(5));
+    /// // The king can't stand on its own pawn
+    /// assert_eq!(board.king_distance_to(Color::White, Square::E4), None);
+    /// ```
+    pub fn king_distance_to(&self, color: Color, target: Square) -> Option<u8> {
+        let king = self.king_square(color);
+        let occupied = *self.combined() ^ BitBoard::from_square(king);
+        let allowed = !self.color_combined(color) & !self.attacked_by(!color, occupied);
+
+        let target = BitBoard::from_square(target);
+        let mut reached = BitBoard::from_square(king);
+        let mut frontier = reached;
+        let mut distance = 0;
+        while frontier != EMPTY {
+            if reached & target != EMPTY {
+                return Some(distance);
+            }
+
+            let mut next = EMPTY;
+            for sq in frontier {
+                next |= get_king_moves(sq);
+            }
+            frontier = next & allowed & !reached;
+            reached |= frontier;
+            distance += 1;
+        }
+        None
+    }
+
+    /// Every square attacked by a piece of `color`, if the board held only `occupied`.
+    fn attacked_by(&self, color: Color, occupied: BitBoard) -> BitBoard {
+        let mut result = EMPTY;
+        for piece in ALL_PIECES.iter() {
+            for src in self.pieces(*piece) & self.color_combined(color) {
+                result |= Board::piece_attacks_through(*piece, color, src, occupied);
+            }
+        }
+        result
+    }
+
+    /// Are all the squares strictly between `from` and `to` empty?  This is true for adjacent
+    /// squares, and for squares that don't share a rank, file or diagonal, as nothing lies
+    /// between them.
+    ///
+    /// ```
+    /// use chess::{Board, Square};
+    ///
+    /// let board = Board::default();
+    /// assert!(board.is_path_clear(Square::A2, Square::A7));
+    /// assert!(!board.is_path_clear(Square::A1, Square::A8));
+    /// ```
+    pub fn is_path_clear(&self, from: Square, to: Square) -> bool {
+        between(from, to) & self.combined() == EMPTY
+    }
+
+    /// Give me the `BitBoard` of every piece of `color` that attacks `sq`, whether or not `sq` is
+    /// occupied.  Pins are ignored.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// // The g1 knight, and the e2 and g2 pawns
+    /// assert_eq!(
+    ///     board.attackers_to(Square::F3, Color::White),
+    ///     BitBoard::from_square(Square::G1)
+    ///         | BitBoard::from_square(Square::E2)
+    ///         | BitBoard::from_square(Square::G2)
+    /// );
+    /// assert_eq!(board.attackers_to(Square::F3, Color::Black), BitBoard::new(0));
+    /// ```
+    pub fn attackers_to(&self, sq: Square, color: Color) -> BitBoard {
+        let bishops = self.pieces(Piece::Bishop) | self.pieces(Piece::Queen);
+        let rooks = self.pieces(Piece::Rook) | self.pieces(Piece::Queen);
+
+        self.color_combined(color)
+            & ((get_pawn_attacks(sq, !color, !EMPTY) & self.pieces(Piece::Pawn))
+                | (get_knight_moves(sq) & self.pieces(Piece::Knight))
+                | (get_bishop_moves(sq, *self.combined()) & bishops)
+                | (get_rook_moves(sq, *self.combined()) & rooks)
+                | (get_king_moves(sq) & self.pieces(Piece::King)))
+    }
+
+    /// The same pieces as `attackers_to`, split up by type and indexed by `Piece::to_index()`.
+    /// An evaluation can use this to weigh a threat from a pawn differently from a threat from a
+    /// queen.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Piece, Square};
+    ///
+    /// let attackers = Board::default().attackers_to_by_piece(Square::F3, Color::White);
+    ///
+    /// assert_eq!(attackers[Piece::Knight.to_index()], BitBoard::from_square(Square::G1));
+    /// assert_eq!(attackers[Piece::Pawn.to_index()].popcnt(), 2);
+    /// assert_eq!(attackers[Piece::Queen.to_index()], BitBoard::new(0));
+    /// ```
+    pub fn attackers_to_by_piece(&self, sq: Square, color: Color) -> [BitBoard; NUM_PIECES] {
+        let attackers = self.attackers_to(sq, color);
+        let mut result = [EMPTY; NUM_PIECES];
+        for piece in ALL_PIECES.iter() {
+            result[piece.to_index()] = attackers & self.pieces(*piece);
+        }
+        result
+    }
+
+    /// Static exchange evaluation: if both sides keep capturing on the destination of `m`, always
+    /// with their least valuable piece, and each may stop when it suits them, does the side
+    /// making `m` come out at least `threshold` ahead?  Values come from `Piece::value`.
+    ///
+    /// Pins are not taken into account, so this is an estimate, but a cheap one.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The e5 pawn is defended by the d6 pawn
+    /// let board = Board::from_str("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1").unwrap();
+    /// let rxe5 = ChessMove::new(Square::E1, Square::E5, None);
+    ///
+    /// assert!(board.see_ge(rxe5, -400));
+    /// assert!(!board.see_ge(rxe5, 0));
+    /// ```
+    pub fn see_ge(&self, m: ChessMove, threshold: i32) -> bool {
+        // The king can never be taken, so it is worth more than anything it could win
+        fn value(piece: Piece) -> i32 {
+            match piece {
+                Piece::King => 100_000,
+                _ => piece.value(),
+            }
+        }
+
+        let from = m.get_source();
+        let to = m.get_dest();
+        let mover = match m.get_promotion() {
+            Some(promotion) => promotion,
+            None => match self.piece_on(from) {
+                Some(piece) => piece,
+                None => return threshold <= 0,
+            },
+        };
+
+        let mut swap = self.captured_piece(m).map_or(0, value) - threshold;
+        if let Some(promotion) = m.get_promotion() {
+            swap += value(promotion) - value(Piece::Pawn);
+        }
+        if swap < 0 {
+            return false;
+        }
+        swap = value(mover) - swap;
+        if swap <= 0 {
+            return true;
+        }
+
+        let mut occupied =
+            *self.combined() ^ BitBoard::from_square(from) | BitBoard::from_square(to);
+        if self.piece_on(from) == Some(Piece::Pawn) && self.captured_piece(m).is_some() {
+            if let Some(ep) = self.en_passant {
+                if ep.get_file() == to.get_file() && self.piece_on(to).is_none() {
+                    occupied ^= BitBoard::from_square(ep);
+                }
+            }
+        }
+
+        let bishops = self.pieces(Piece::Bishop) | self.pieces(Piece::Queen);
+        let rooks = self.pieces(Piece::Rook) | self.pieces(Piece::Queen);
+        let mut attackers = (get_pawn_attacks(to, Color::Black, !EMPTY)
+            & self.pieces_of(Piece::Pawn, Color::White))
+            | (get_pawn_attacks(to, Color::White, !EMPTY)
+                & self.pieces_of(Piece::Pawn, Color::Black))
+            | (get_knight_moves(to) & self.pieces(Piece::Knight))
+            | (get_bishop_moves(to, occupied) & bishops)
+            | (get_rook_moves(to, occupied) & rooks)
+            | (get_king_moves(to) & self.pieces(Piece::King));
+
+        let mut side = self.side_to_move;
+        let mut result = true;
+        loop {
+            side = !side;
+            attackers &= occupied;
+            let ours = attackers & self.color_combined(side);
+            if ours == EMPTY {
+                break;
+            }
+
+            let piece = ALL_PIECES
+                .iter()
+                .copied()
+                .find(|p| ours & self.pieces(*p) != EMPTY)
+                .unwrap();
+
+            if piece == Piece::King {
+                // Taking with the king only works if nothing can take it back
+                if attackers & self.color_combined(!side) & occupied != EMPTY {
+                    result = !result;
+                }
+                return !result;
+            }
+
+            result = !result;
+            swap = value(piece) - swap;
+            if swap < i32::from(result) {
+                break;
+            }
+
+            occupied ^= BitBoard::from_square((ours & self.pieces(piece)).to_square());
+            // Moving a piece off the square's lines may uncover a slider behind it
+            attackers |=
+                (get_bishop_moves(to, occupied) & bishops) | (get_rook_moves(to, occupied) & rooks);
+        }
+
+        result
+    }
+
+    /// Every legal move, best first by static exchange evaluation.  Quiet moves count as an
+    /// exchange worth 0, so winning captures come first, then quiet moves and even trades, then
+    /// losing captures.  Moves that score the same stay in `MoveGen` order.
+    ///
+    /// This is a cheap move ordering for a simple engine.  See `see_ge` for how exchanges are
+    /// scored.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, MoveGen, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // Qxd5 wins a free pawn
+    /// let board = Board::from_str("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+    ///
+    /// let moves = board.legal_moves_by_see();
+    /// assert_eq!(moves[0], ChessMove::new(Square::D1, Square::D5, None));
+    /// assert_eq!(moves.len(), MoveGen::new_legal(&board).len());
+    /// ```
+    pub fn legal_moves_by_see(&self) -> Vec<ChessMove> {
+        let mut moves: Vec<ChessMove> = MoveGen::new_legal(self).collect();
+        moves.sort_by_cached_key(|m| -self.see(*m));
+        moves
+    }
+
+    /// The value of the exchange started by `m`, or 0 for a quiet move.  This searches for the
+    /// highest threshold `see_ge` accepts.
+    fn see(&self, m: ChessMove) -> i32 {
+        if self.captured_piece(m).is_none() && m.get_promotion().is_none() {
+            return 0;
+        }
+
+        // Nothing can win more than a queen plus a promotion, or lose more than a queen
+        let mut low = -2 * Piece::Queen.value();
+        let mut high = 2 * Piece::Queen.value();
+        while low < high {
+            let mid = low + (high - low + 1) / 2;
+            if self.see_ge(m, mid) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        low
+    }
+
+    /// Give me the `BitBoard` of the sliders of `color` that would attack `target` if the piece
+    /// on `mover` moved out of the way, but that don't attack it now.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The d1 rook is behind the d4 knight, aiming at the d8 queen
+    /// let board = Board::from_str("3qk3/8/8/8/3N4/8/8/3RK3 w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.discovered_attackers(Square::D4, Square::D8, Color::White),
+    ///     BitBoard::from_square(Square::D1)
+    /// );
+    /// ```
+    pub fn discovered_attackers(&self, mover: Square, target: Square, color: Color) -> BitBoard {
+        let occupied = self.combined() & !BitBoard::from_square(mover);
+        let bishops = self.pieces(Piece::Bishop) | self.pieces(Piece::Queen);
+        let rooks = self.pieces(Piece::Rook) | self.pieces(Piece::Queen);
+
+        let sliders = (get_bishop_moves(target, occupied) & bishops)
+            | (get_rook_moves(target, occupied) & rooks);
+
+        sliders & self.color_combined(color) & !self.attackers_to(target, color)
+    }
+
+    /// Is `sq` attacked by a pawn of `color`?
+    ///
+    /// ```
+    /// use chess::{Board, Color, Square};
+    ///
+    /// let board = Board::default();
+    /// assert!(board.attacked_by_pawn(Square::D3, Color::White));
+    /// assert!(!board.attacked_by_pawn(Square::D4, Color::White));
+    /// ```
+    #[inline]
+    pub fn attacked_by_pawn(&self, sq: Square, color: Color) -> bool {
+        get_pawn_attacks(
+            sq,
+            !color,
+            self.pieces(Piece::Pawn) & self.color_combined(color),
+        ) != EMPTY
+    }
+
+    /// Is the piece on `sq` hanging?  It is if the opponent attacks it and either nothing
+    /// defends it, or it can be taken by a piece worth less than itself (so that trading
+    /// doesn't help).  Kings, and empty squares, are never hanging.
+    ///
+    /// ```
+    /// use chess::{Board, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The e5 knight is attacked by the d4 pawn, even though the f6 pawn defends it
+    /// let board = Board::from_str("4k3/8/5p2/4n3/3P4/8/8/4K3 w - - 0 1").unwrap();
+    /// assert!(board.is_hanging(Square::E5));
+    /// assert!(!board.is_hanging(Square::D4));
+    /// ```
+    pub fn is_hanging(&self, sq: Square) -> bool {
+        let (piece, color) = match (self.piece_on(sq), self.color_on(sq)) {
+            (Some(Piece::King), _) | (None, _) | (_, None) => return false,
+            (Some(piece), Some(color)) => (piece, color),
+        };
+
+        let attackers = self.attackers_to(sq, !color);
+        if attackers == EMPTY {
+            return false;
+        }
+        if self.attackers_to(sq, color) == EMPTY {
+            return true;
+        }
+
+        // A king can't take a defended piece, so only the other attackers count here
+        (attackers & !self.pieces(Piece::King))
+            .into_iter()
+            .filter_map(|attacker| self.piece_on(attacker))
+            .any(|attacker| attacker.value() < piece.value())
+    }
+
+    /// All of `color`s pieces that are hanging, in the sense of `is_hanging`.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Square, BitBoard};
+    /// use std::str::FromStr;
+    ///
+    /// // The e5 knight is attacked by the d4 pawn, even though the f6 pawn defends it
+    /// let board = Board::from_str("4k3/8/5p2/4n3/3P4/8/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.hanging_pieces(Color::Black), BitBoard::from_square(Square::E5));
+    /// assert_eq!(board.hanging_pieces(Color::White), BitBoard::new(0));
+    /// ```
+    pub fn hanging_pieces(&self, color: Color) -> BitBoard {
+        let mut result = EMPTY;
+        for sq in self.color_combined(color) & !self.pieces(Piece::King) {
+            if self.is_hanging(sq) {
+                result |= BitBoard::from_square(sq);
+            }
+        }
+        result
+    }
+
+    /// Every square attacked by the opponents of `color`, looking straight through `color`s
+    /// king.  A king can never move to one of these squares: a slider that checks the king still
+    /// covers the squares behind it, even though the king itself is in the way right now.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Square, BitBoard};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+    /// let danger = board.king_danger_squares(Color::White);
+    ///
+    /// // f1 is behind the king, from the a1 rook's point of view
+    /// assert_ne!(danger & BitBoard::from_square(Square::F1), BitBoard::new(0));
+    /// ```
+    pub fn king_danger_squares(&self, color: Color) -> BitBoard {
+        let king = self.pieces(Piece::King) & self.color_combined(color);
+        self.attacked_by(!color, self.combined() & !king)
+    }
+
+    /// The squares the king of the side to move can step to: those next to it that are empty or
+    /// hold an enemy piece, and that no enemy piece attacks, even once the king has moved out of
+    /// the way.  Castling isn't included.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // The h8 king is boxed in by its own pawns, except for g8
+    /// let board = Board::from_str("7k/6pp/8/8/8/8/8/K7 b - - 0 1").unwrap();
+    /// assert_eq!(board.king_escape_squares(), BitBoard::from_square(Square::G8));
+    ///
+    /// // A rook on the back rank covers g8 as well
+    /// let board = Board::from_str("R6k/6pp/8/8/8/8/8/K7 b - - 0 1").unwrap();
+    /// assert_eq!(board.king_escape_squares(), BitBoard::new(0));
+    /// ```
+    pub fn king_escape_squares(&self) -> BitBoard {
+        let color = self.side_to_move;
+        get_king_moves(self.king_square(color))
+            & !self.color_combined(color)
+            & !self.king_danger_squares(color)
+    }
+
+    /// The legal moves of the king of the side to move, castling included, in `MoveGen` order.
+    /// This skips generating moves for every other piece.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/8/8/3PPP2/4K2R w K - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.king_moves(),
+    ///     vec![
+    ///         ChessMove::new(Square::E1, Square::D1, None),
+    ///         ChessMove::new(Square::E1, Square::F1, None),
+    ///         ChessMove::new(Square::E1, Square::G1, None),
+    ///     ]
+    /// );
+    /// ```
+    pub fn king_moves(&self) -> Vec<ChessMove> {
+        let color = self.side_to_move;
+        let ksq = self.king_square(color);
+        let danger = self.king_danger_squares(color);
+        let mut dests = get_king_moves(ksq) & !self.color_combined(color) & !danger;
+
+        if self.checkers == EMPTY {
+            // The king may not pass through or land on an attacked square
+            let safe =
+                |dest: Square| (between(ksq, dest) | BitBoard::from_square(dest)) & danger == EMPTY;
+            let rights = self.my_castle_rights();
+            let kingside = ksq.uright().uright();
+            if rights.has_kingside()
+                && self.combined & rights.kingside_squares(color) == EMPTY
+                && safe(kingside)
+            {
+                dests |= kingside;
+            }
+            let queenside = ksq.uleft().uleft();
+            if rights.has_queenside()
+                && self.combined & rights.queenside_squares(color) == EMPTY
+                && safe(queenside)
+            {
+                dests |= queenside;
+            }
+        }
+
+        dests.map(|dest| ChessMove::new(ksq, dest, None)).collect()
+    }
+
+    /// A measure of how much space `color` controls, in the style of Stockfish.
+    ///
+    /// Consider the squares on the c, d, e and f files on `color`s 2nd, 3rd and 4th ranks.  Each
+    /// of those squares that is not occupied by one of our pawns and is not attacked by an enemy
+    /// pawn is "safe", and counts once.  A safe square that is also up to three squares behind
+    /// one of our pawns, and is not attacked by any enemy piece, counts a second time.
+    ///
+    /// ```
+    /// use chess::{Board, Color};
+    ///
+    /// let board = Board::default();
+    ///
+    /// // c3-f3 and c4-f4 are safe, nothing is behind a pawn
+    /// assert_eq!(board.space(Color::White), 8);
+    /// assert_eq!(board.space(Color::Black), 8);
+    /// ```
+    pub fn space(&self, color: Color) -> u32 {
+        let central_files =
+            get_file(File::C) | get_file(File::D) | get_file(File::E) | get_file(File::F);
+        let my_ranks = match color {
+            Color::White => get_rank(Rank::Second) | get_rank(Rank::Third) | get_rank(Rank::Fourth),
+            Color::Black => get_rank(Rank::Seventh) | get_rank(Rank::Sixth) | get_rank(Rank::Fifth),
+        };
+
+        let my_pawns = self.pieces(Piece::Pawn) & self.color_combined(color);
+        let their_pawns = self.pieces(Piece::Pawn) & self.color_combined(!color);
+
+        let mut their_pawn_attacks = EMPTY;
+        for sq in their_pawns {
+            their_pawn_attacks |= get_pawn_attacks(sq, !color, !EMPTY);
+        }
+
+        let safe = central_files & my_ranks & !my_pawns & !their_pawn_attacks;
+
+        let mut behind = my_pawns;
+        for _ in 0..3 {
+            behind |= match color {
+                Color::White => BitBoard(behind.0 >> 8),
+                Color::Black => BitBoard(behind.0 << 8),
+            };
+        }
+
+        safe.popcnt() + (behind & safe & !self.attacked_by(!color, *self.combined())).popcnt()
+    }
+
+    /// Every empty square that a pawn of `color` could move to with a single push.  This only
+    /// considers occupancy; pins and checks are ignored.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Rank, get_rank};
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.pawn_single_pushes(Color::White), get_rank(Rank::Third));
+    /// ```
+    pub fn pawn_single_pushes(&self, color: Color) -> BitBoard {
+        let pawns = self.pieces(Piece::Pawn) & self.color_combined(color);
+        Board::shift_forward(pawns, color) & !self.combined()
+    }
+
+    /// Every empty square that a pawn of `color` could move to with a double push.  The pawn must
+    /// be on its second rank, and both squares in front of it must be empty.  Like
+    /// `pawn_single_pushes`, pins and checks are ignored.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Rank, get_rank};
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.pawn_double_pushes(Color::Black), get_rank(Rank::Fifth));
+    /// ```
+    pub fn pawn_double_pushes(&self, color: Color) -> BitBoard {
+        let pawns = self.pieces(Piece::Pawn)
+            & self.color_combined(color)
+            & get_rank(color.to_second_rank());
+        let single = Board::shift_forward(pawns, color) & !self.combined();
+        Board::shift_forward(single, color) & !self.combined()
+    }
+
+    /// Every pawn of `color` that can't push, because a piece of either color stands directly
+    /// in front of it.  Captures aren't considered.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // After 1. e4 e5, the e-pawns block each other
+    /// let board = Board::from_str("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2")
+    ///     .unwrap();
+    /// assert_eq!(board.blocked_pawns(Color::White), BitBoard::from_square(Square::E4));
+    /// assert_eq!(board.blocked_pawns(Color::Black), BitBoard::from_square(Square::E5));
+    /// ```
+    pub fn blocked_pawns(&self, color: Color) -> BitBoard {
+        let pawns = self.pieces(Piece::Pawn) & self.color_combined(color);
+        pawns & Board::shift_forward(*self.combined(), !color)
+    }
+
+    /// Shift every square in `bb` one rank towards `color`s opponent.
+    fn shift_forward(bb: BitBoard, color: Color) -> BitBoard {
+        match color {
+            Color::White => BitBoard(bb.0 << 8),
+            Color::Black => BitBoard(bb.0 >> 8),
+        }
+    }
+
+    /// Is the pawn on `sq` a passed pawn?  A pawn is passed when no enemy pawn stands in front of
+    /// it on its own file or on an adjacent file.  Returns `false` if `sq` does not hold a pawn.
+    ///
+    /// ```
+    /// use chess::{Board, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/3P4/8/5p2/4P3/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(board.is_passed_pawn(Square::D5));
+    /// assert!(!board.is_passed_pawn(Square::E2)); // f3 covers e3
+    /// assert!(!board.is_passed_pawn(Square::E1)); // not a pawn
+    /// ```
+    pub fn is_passed_pawn(&self, sq: Square) -> bool {
+        if self.piece_on(sq) != Some(Piece::Pawn) {
+            return false;
+        }
+        let color = self.color_on(sq).unwrap();
+
+        let span = Board::ranks_in_front(sq, color)
+            & (get_file(sq.get_file()) | get_adjacent_files(sq.get_file()));
+
+        (span & self.pieces(Piece::Pawn) & self.color_combined(!color)) == EMPTY
+    }
+
+    /// Every square on a rank in front of `sq`, from `color`s point of view.
+    fn ranks_in_front(sq: Square, color: Color) -> BitBoard {
+        let rank = sq.get_rank().to_index() as u32;
+        match color {
+            Color::White => BitBoard((!0u64).checked_shl(8 * (rank + 1)).unwrap_or(0)),
+            Color::Black => BitBoard((1u64 << (8 * rank)) - 1),
+        }
+    }
+
+    /// Every outpost for `color`: a square in the opponent's half of the board that one of our
+    /// pawns defends, and that no enemy pawn can ever attack, either now or by advancing.
+    ///
+    /// ```
+    /// use chess::{Board, BitBoard, Color, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/2p5/8/8/4P3/8/8/4K3 w - - 0 1").unwrap();
+    ///
+    /// // c7-c6 would cover d5, but nothing can cover f5
+    /// assert_eq!(board.outpost_squares(Color::White), BitBoard::from_square(Square::F5));
+    /// ```
+    pub fn outpost_squares(&self, color: Color) -> BitBoard {
+        let their_half = match color {
+            Color::White => BitBoard(0xffff_ffff_0000_0000),
+            Color::Black => BitBoard(0x0000_0000_ffff_ffff),
+        };
+
+        let mut supported = EMPTY;
+        for sq in self.pieces(Piece::Pawn) & self.color_combined(color) {
+            supported |= get_pawn_attacks(sq, color, !EMPTY);
+        }
+
+        let mut attackable = EMPTY;
+        for sq in self.pieces(Piece::Pawn) & self.color_combined(!color) {
+            attackable |= Board::ranks_in_front(sq, !color) & get_adjacent_files(sq.get_file());
+        }
+
+        their_half & supported & !attackable
+    }
+
+    /// White's material minus Black's material, in centipawns, using `Piece::value`.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// assert_eq!(Board::default().material_balance(), 0);
+    /// ```
+    pub fn material_balance(&self) -> i32 {
+        ALL_PIECES
+            .iter()
+            .map(|piece| {
+                let white = (self.pieces(*piece) & self.color_combined(Color::White)).popcnt();
+                let black = (self.pieces(*piece) & self.color_combined(Color::Black)).popcnt();
+                piece.value() * (white as i32 - black as i32)
+            })
+            .sum()
+    }
+
+    /// How far from the endgame is this position?  Each knight and bishop counts 1, each rook 2
+    /// and each queen 4, so the starting position has `MAX_PHASE`, and a position with only
+    /// kings and pawns has 0.  Positions with extra pieces from promotions are capped at
+    /// `MAX_PHASE`.
+    ///
+    /// ```
+    /// use chess::{Board, MAX_PHASE};
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(Board::default().phase(), MAX_PHASE);
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.phase(), 2);
+    /// ```
+    pub fn phase(&self) -> i32 {
+        let phase = self.pieces(Piece::Knight).popcnt()
+            + self.pieces(Piece::Bishop).popcnt()
+            + 2 * self.pieces(Piece::Rook).popcnt()
+            + 4 * self.pieces(Piece::Queen).popcnt();
+        (phase as i32).min(MAX_PHASE)
+    }
+
+    /// White's material minus Black's material, blended between the midgame values `mg` and the
+    /// endgame values `eg` by `phase`.  At `MAX_PHASE` this uses only `mg`, and at 0 only `eg`.
+    ///
+    /// ```
+    /// use chess::{Board, PieceValues};
+    /// use std::str::FromStr;
+    ///
+    /// let mg = PieceValues([80, 320, 330, 480, 950, 0]);
+    /// let eg = PieceValues([120, 280, 300, 520, 950, 0]);
+    ///
+    /// // White is a pawn up in a pawn ending
+    /// let board = Board::from_str("4k3/p7/8/8/8/8/PP6/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.tapered_material(&mg, &eg), 120);
+    /// ```
+    pub fn tapered_material(&self, mg: &PieceValues, eg: &PieceValues) -> i32 {
+        let mut mg_score = 0;
+        let mut eg_score = 0;
+        for piece in ALL_PIECES.iter() {
+            let white = (self.pieces(*piece) & self.color_combined(Color::White)).popcnt();
+            let black = (self.pieces(*piece) & self.color_combined(Color::Black)).popcnt();
+            let count = white as i32 - black as i32;
+            mg_score += mg.get(*piece) * count;
+            eg_score += eg.get(*piece) * count;
+        }
+
+        let phase = self.phase();
+        (mg_score * phase + eg_score * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+
+    /// The HalfKP features of this board, as seen by `perspective`: every piece other than the
+    /// kings, with its color and square.  For black, the board is flipped top to bottom so that
+    /// black's king starts at the bottom, as it does for white.  Colors are left as they are.
+    ///
+    /// ```
+    /// use chess::{Board, Color, Piece, Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// let white = board.halfkp_features(Color::White);
+    /// let black = board.halfkp_features(Color::Black);
+    /// assert_eq!(white.len(), 30);
+    /// assert!(white.contains(&(Piece::Queen, Color::White, Square::D1)));
+    /// assert!(black.contains(&(Piece::Queen, Color::Black, Square::D1)));
+    /// ```
+    pub fn halfkp_features(&self, perspective: Color) -> Vec<(Piece, Color, Square)> {
+        let mut features = vec![];
+        for sq in *self.combined() & !self.pieces(Piece::King) {
+            let oriented = match perspective {
+                Color::White => sq,
+                Color::Black => Square::make_square(
+                    Rank::from_index(7 - sq.get_rank().to_index()),
+                    sq.get_file(),
+                ),
+            };
+            // every square in `combined` has a piece and a color
+            features.push((
+                self.piece_on(sq).unwrap(),
+                self.color_on(sq).unwrap(),
+                oriented,
+            ));
+        }
+        features
+    }
+
+    /// A number identifying the material on the board: how many of each piece each side has,
+    /// wherever they stand.  Boards with the same material always have the same key.
+    ///
+    /// Each count takes 4 bits, starting from the lowest bits, in the order of `ALL_PIECES` for
+    /// white and then for black.  The layout is stable, so keys can be stored.
+    ///
+    /// ```
+    /// use chess::Board;
+    /// use std::str::FromStr;
+    ///
+    /// // One white king and one black king
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.material_key(), (1 << 20) | (1 << 44));
+    /// ```
+    pub fn material_key(&self) -> u64 {
+        let mut key = 0;
+        for (i, color) in ALL_COLORS.iter().enumerate() {
+            for (j, piece) in ALL_PIECES.iter().enumerate() {
+                let count = (self.pieces(*piece) & self.color_combined(*color)).popcnt() as u64;
+                key |= count.min(15) << ((i * NUM_PIECES + j) * 4);
+            }
+        }
+        key
+    }
+
+    /// The material on the board, written the way endgame tablebases name it: white's pieces,
+    /// then "v", then black's pieces, each side starting with the king and going from the most
+    /// valuable piece to pawns.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// assert_eq!(Board::default().material_signature(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+    /// ```
+    pub fn material_signature(&self) -> String {
+        let order = [
+            Piece::King,
+            Piece::Queen,
+            Piece::Rook,
+            Piece::Bishop,
+            Piece::Knight,
+            Piece::Pawn,
+        ];
+
+        let side = |color: Color| -> String {
+            let mut result = String::new();
+            for piece in order.iter() {
+                let count = (self.pieces(*piece) & self.color_combined(color)).popcnt();
+                for _ in 0..count {
+                    result.push_str(&piece.to_string(Color::White));
+                }
+            }
+            result
+        };
+
+        format!("{}v{}", side(Color::White), side(Color::Black))
+    }
+
+    /// List every square whose contents differ between `self` and `other`, along with what was
+    /// on that square in `self` and what is on it in `other`.  This is meant as a debugging aid,
+    /// for example when checking that an incremental update matches a full rebuild.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Color, Piece, Square};
+    ///
+    /// let board = Board::default();
+    /// let after = board.make_move_new(ChessMove::new(Square::G1, Square::F3, None));
+    ///
+    /// assert_eq!(
+    ///     board.diff(&after),
+    ///     vec![
+    ///         (Square::G1, Some((Piece::Knight, Color::White)), None),
+    ///         (Square::F3, None, Some((Piece::Knight, Color::White))),
+    ///     ]
+    /// );
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn diff(
+        &self,
+        other: &Board,
+    ) -> Vec<(Square, Option<(Piece, Color)>, Option<(Piece, Color)>)> {
+        let mut result = vec![];
+        for sq in ALL_SQUARES.iter() {
+            let before = self.piece_on(*sq).map(|p| (p, self.color_on(*sq).unwrap()));
+            let after = other
+                .piece_on(*sq)
+                .map(|p| (p, other.color_on(*sq).unwrap()));
+            if before != after {
+                result.push((*sq, before, after));
+            }
+        }
+        result
+    }
+
+    /// Draw the board as text, from white's point of view.  White pieces are uppercase, black
+    /// pieces are lowercase, and empty squares are dots.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let ascii = Board::default().render_ascii();
+    /// assert!(ascii.starts_with("8 r n b q k b n r\n7 p p p p p p p p\n6 . . . . . . . .\n"));
+    /// assert!(ascii.ends_with("1 R N B Q K B N R\n  a b c d e f g h\n"));
+    /// ```
+    pub fn render_ascii(&self) -> String {
+        let mailbox = self.mailbox();
+        let mut result = String::new();
+        for rank in Rank::iter().rev() {
+            result.push((b'1' + rank.to_u8()) as char);
+            for file in File::iter() {
+                result.push(' ');
+                match mailbox[Square::make_square(rank, file).to_index()] {
+                    Some((piece, color)) => result.push_str(&piece.to_string(color)),
+                    None => result.push('.'),
+                }
+            }
+            result.push('\n');
+        }
+        result.push_str("  a b c d e f g h\n");
+        result
+    }
+
+    /// Describe this board for debugging: the board itself, the FEN, the side to move, and the
+    /// checking and pinned pieces.
+    ///
+    /// ```
+    /// use chess::Board;
+    ///
+    /// let debug = Board::default().debug_string();
+    /// assert!(debug.contains("Side to move: White\n"));
+    /// assert!(debug.contains("Checkers: -\n"));
+    /// ```
+    pub fn debug_string(&self) -> String {
+        let squares = |bb: BitBoard| {
+            if bb == EMPTY {
+                "-".to_string()
+            } else {
+                bb.into_iter()
+                    .map(|sq| sq.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+        };
+
+        format!(
+            "{}FEN: {}\nSide to move: {:?}\nCheckers: {}\nPinned: {}\n",
+            self.render_ascii(),
+            self,
+            self.side_to_move,
+            squares(self.checkers),
+            squares(self.pinned)
+        )
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fen: BoardBuilder = self.into();
+        write!(f, "{}", fen)
+    }
+}
+
+/// `{:?}` shows the FEN, the same as `Display` does, wrapped so it reads as a `Board`.  `{:#?}`
+/// draws the board underneath as well, like `render_ascii`.
+///
+/// ```
+/// use chess::Board;
+///
+/// let board = Board::default();
+/// assert_eq!(
+///     format!("{:?}", board),
+///     "Board(\"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\")"
+/// );
+/// assert!(format!("{:#?}", board).ends_with(&board.render_ascii()));
+/// ```
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Board(\"{}\")", self)?;
+        if f.alternate() {
+            write!(f, "\n{}", self.render_ascii())?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&BoardBuilder> for Board {
+    type Error = Error;
+
+    fn try_from(fen: &BoardBuilder) -> Result<Self, Self::Error> {
+        let board = Board::from_builder_unchecked(fen);
+
+        if board.is_sane() {
+            Ok(board)
+        } else {
+            Err(Error::InvalidBoard)
+        }
+    }
+}
+
+impl TryFrom<&mut BoardBuilder> for Board {
+    type Error = Error;
+
+    fn try_from(fen: &mut BoardBuilder) -> Result<Self, Self::Error> {
+        (&*fen).try_into()
+    }
+}
+
+impl TryFrom<BoardBuilder> for Board {
+    type Error = Error;
+
+    fn try_from(fen: BoardBuilder) -> Result<Self, Self::Error> {
+        (&fen).try_into()
+    }
+}
+
+impl FromStr for Board {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(BoardBuilder::from_str(value)?.try_into()?)
+    }
+}
+
+#[test]
+fn test_null_move_en_passant() {
+    let start =
+        Board::from_str("rnbqkbnr/pppp2pp/8/4pP2/8/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 0").unwrap();
+    let expected =
+        Board::from_str("rnbqkbnr/pppp2pp/8/4pP2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 0").unwrap();
+    assert_eq!(start.null_move().unwrap(), expected);
+}
+
+#[test]
+fn test_legal_moves_into_reuses_buffer() {
+    let mut moves = Vec::with_capacity(256);
+    let capacity = moves.capacity();
+
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+    ];
+    let expected = [20, 48, 0];
+
+    for (fen, count) in positions.iter().zip(expected.iter()) {
+        let board = Board::from_str(fen).unwrap();
+        board.legal_moves_into(&mut moves);
+        assert_eq!(moves.len(), *count);
+        assert_eq!(moves, MoveGen::new_legal(&board).collect::<Vec<_>>());
+        assert_eq!(moves.capacity(), capacity);
+    }
+}
+
+#[test]
+fn test_san_suffix() {
+    let positions = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppppp2p/5p2/6p1/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3",
+        "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1",
+        "R6R/8/8/1k6/8/8/8/R3K2R w - - 0 1",
+    ];
+
+    let mut checks = 0;
+    for fen in positions.iter() {
+        let board = Board::from_str(fen).unwrap();
+        for m in MoveGen::new_legal(&board) {
+            let with_suffix = board.san_with_suffix(m, true);
+            let without_suffix = board.san_with_suffix(m, false);
+            assert_eq!(
+                with_suffix.trim_end_matches(&['+', '#'][..]),
+                without_suffix
+            );
+            if with_suffix != without_suffix {
+                checks += 1;
+            }
+            assert_eq!(ChessMove::from_san(&board, &with_suffix).unwrap(), m);
+        }
+    }
+    assert!(checks > 0);
+}
+
+#[test]
+fn test_control() {
+    let board = Board::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R b KQkq - 0 3")
+        .unwrap();
+
+    let white = board.control(Color::White);
+    let black = board.control(Color::Black);
+
+    // d4 pawn, f3 knight
+    assert_eq!(white[Square::E5.to_index()], 2);
+    // e4 pawn
+    assert_eq!(white[Square::D5.to_index()], 1);
+    assert_eq!(white[Square::F5.to_index()], 1);
+    // d1 queen, e1 king, c1 bishop, b1 knight, f3 knight
+    assert_eq!(white[Square::D2.to_index()], 5);
+    // c6 knight, e5 pawn
+    assert_eq!(black[Square::D4.to_index()], 2);
+    // c6 knight, f8 bishop
+    assert_eq!(black[Square::B4.to_index()], 2);
+    assert_eq!(black[Square::E4.to_index()], 0);
+}
+
+#[test]
+fn test_space() {
+    // Giuoco Pianissimo, with a closed center
+    let board =
+        Board::from_str("r1bq1rk1/ppp2ppp/2np1n2/2b1p3/2B1P3/2PP1N2/PP3PPP/RNBQ1RK1 w - - 0 7")
+            .unwrap();
+
+    // c2, d2, e2, e3, f3 and c4 are safe (d4 and f4 are hit by the e5 pawn).  c2, d2, e2 and e3
+    // are behind white pawns, but e3 is attacked by the c5 bishop.
+    assert_eq!(board.space(Color::White), 9);
+}
+
+#[test]
+fn test_is_passed_pawn() {
+    let board = Board::from_str("4k3/1p6/8/P1P5/6p1/7P/4p3/4K3 w - - 0 1").unwrap();
+
+    // Nothing in front of the black e2 pawn, which is about to promote
+    assert!(board.is_passed_pawn(Square::E2));
+    // g4 and h3 stop each other
+    assert!(!board.is_passed_pawn(Square::G4));
+    // b7 covers both the a and c files
+    assert!(!board.is_passed_pawn(Square::A5));
+    assert!(!board.is_passed_pawn(Square::C5));
+    assert!(!board.is_passed_pawn(Square::H3));
+    // ...and a5 and c5 stand in front of b7 in turn
+    assert!(!board.is_passed_pawn(Square::B7));
+    // Not a pawn
+    assert!(!board.is_passed_pawn(Square::E1));
+}
+
+#[test]
+fn test_diff_capture() {
+    let board =
+        Board::from_str("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+    let after = board.make_move_new(ChessMove::new(Square::E4, Square::D5, None));
+
+    assert_eq!(
+        board.diff(&after),
+        vec![
+            (Square::E4, Some((Piece::Pawn, Color::White)), None),
+            (
+                Square::D5,
+                Some((Piece::Pawn, Color::Black)),
+                Some((Piece::Pawn, Color::White))
+            ),
+        ]
+    );
+    assert!(after.diff(&after).is_empty());
+}
+
+#[test]
+fn test_triple_check_only_king_moves() {
+    // The white king on e1 is attacked by the e8 rook, the b4 bishop and the d3 knight
+    let board: Board = BoardBuilder::from_str("4r1k1/8/8/8/1b6/3n4/8/4K3 w - - 0 1")
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    assert_eq!(board.checker_count(), 3);
+    assert!(board.in_check());
+    assert!(board.in_double_check());
+
+    let moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    assert!(!moves.is_empty());
+    assert!(moves.iter().all(|m| m.get_source() == Square::E1));
+    assert_eq!(MoveGen::new_legal(&board).len(), moves.len());
+}
+
+#[test]
+fn test_has_legal_moves() {
+    // Stalemate, checkmate, and positions where only one piece type can move
+    let cases = [
+        ("7k/5Q2/8/8/8/8/8/K7 b - - 0 1", false),
+        ("7k/5Q2/8/8/8/8/8/K7 w - - 0 1", true),
+        (
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            false,
+        ),
+        ("7k/5Q2/8/8/8/1p6/8/K7 b - - 0 1", true),
+        ("7k/5Q2/8/8/8/2r5/8/K7 b - - 0 1", true),
+        ("6rk/5Q2/8/8/8/8/8/K7 b - - 0 1", true),
+    ];
+    for (fen, expected) in cases.iter() {
+        let board = Board::from_str(fen).unwrap();
+        assert_eq!(board.has_legal_moves(), *expected, "{}", fen);
+        assert_eq!(MoveGen::new_legal(&board).len() > 0, *expected, "{}", fen);
+    }
+}
+
+#[test]
+fn test_material_balance() {
+    assert_eq!(Board::default().material_balance(), 0);
+
+    let no_black_queen =
+        Board::from_str("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert_eq!(no_black_queen.material_balance(), 900);
+
+    let no_white_rook_or_pawn =
+        Board::from_str("rnbqkbnr/pppppppp/8/8/8/8/1PPPPPPP/1NBQKBNR w Kkq - 0 1").unwrap();
+    assert_eq!(no_white_rook_or_pawn.material_balance(), -600);
+}
+
+#[test]
+fn test_pinners() {
+    // The d2 bishop is pinned by the a5 queen, and the e2 knight by the e8 rook.  The h4 bishop
+    // is lined up with the king, but the f2 and g3 pawns are both in the way.
+    let board = Board::from_str("4r1k1/8/8/q7/7b/6P1/3BNP2/4K3 w - - 0 1").unwrap();
+
+    assert_eq!(
+        board.pinners(Color::White),
+        BitBoard::from_square(Square::A5) | BitBoard::from_square(Square::E8)
+    );
+    assert_eq!(
+        *board.pinned(),
+        BitBoard::from_square(Square::D2) | BitBoard::from_square(Square::E2)
+    );
+    assert_eq!(board.pinners(Color::Black), EMPTY);
+}
+
+#[test]
+fn test_assert_invariants_after_moves() {
+    // Castling, en passant, promotion with capture, and checks along the way
+    let board = Board::from_str("r3k2r/1P3ppp/8/3pP3/8/8/5PPP/R3K2R w KQkq d6 0 1").unwrap();
+    board.assert_invariants();
+
+    let moves = ["e5d6", "e8g8", "b7a8q", "f8a8", "e1c1", "a8a1", "c1d2"];
+    let mut board = board;
+    for m in moves.iter() {
+        let m = ChessMove::from_str(m).unwrap();
+        assert!(board.legal(m), "{}", m);
+        board = board.make_move_new(m);
+        board.assert_invariants();
+    }
+}
+
+#[test]
+fn test_pawn_pushes() {
+    let board = Board::default();
+    assert_eq!(board.pawn_single_pushes(Color::White).popcnt(), 8);
+    assert_eq!(board.pawn_double_pushes(Color::White).popcnt(), 8);
+
+    // The e-pawns block each other, the d3 knight blocks d2, and the c-pawn has already moved
+    let board =
+        Board::from_str("rnbqkbnr/pppp1ppp/8/8/4p3/2PN4/PP1PPPPP/RNBQKB1R w KQkq - 0 1").unwrap();
+    assert_eq!(
+        board.pawn_single_pushes(Color::White),
+        get_rank(Rank::Third)
+            & !(BitBoard::from_square(Square::D3) | BitBoard::from_square(Square::C3))
+            | BitBoard::from_square(Square::C4)
+    );
+    assert_eq!(
+        board.pawn_double_pushes(Color::White),
+        get_rank(Rank::Fourth)
+            & !(BitBoard::from_square(Square::C4)
+                | BitBoard::from_square(Square::D4)
+                | BitBoard::from_square(Square::E4))
+    );
+}
+
+#[test]
+fn test_legal_moves_from() {
+    let board = Board::default();
+    assert_eq!(
+        board.legal_moves_from(Square::B1),
+        vec![
+            ChessMove::new(Square::B1, Square::A3, None),
+            ChessMove::new(Square::B1, Square::C3, None),
+        ]
+    );
+    assert!(board.legal_moves_from(Square::A1).is_empty());
+
+    // The pinned knight can't move, and the pawn's promotions all come out
+    let board = Board::from_str("4r2k/1P6/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    assert!(board.legal_moves_from(Square::E2).is_empty());
+    assert_eq!(board.legal_moves_from(Square::B7).len(), 4);
+
+    let all: usize = ALL_SQUARES
+        .iter()
+        .map(|sq| board.legal_moves_from(*sq).len())
+        .sum();
+    assert_eq!(all, MoveGen::new_legal(&board).len());
+}
+
+#[test]
+fn test_legal_destinations() {
+    let board = Board::default();
+    assert_eq!(
+        board.legal_destinations(Square::E2),
+        BitBoard::from_square(Square::E3) | BitBoard::from_square(Square::E4)
+    );
+    assert_eq!(board.legal_destinations(Square::E7), EMPTY);
+
+    // Four promotions, one square
+    let board = Board::from_str("4r2k/1P6/8/8/8/8/8/3K4 w - - 0 1").unwrap();
+    assert_eq!(
+        board.legal_destinations(Square::B7),
+        BitBoard::from_square(Square::B8)
+    );
+}
+
+#[test]
+fn test_king_danger_squares() {
+    // The a1 rook checks the e1 king along the first rank
+    let board = Board::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+    let danger = board.king_danger_squares(Color::White);
+
+    let first_rank = get_rank(Rank::First) & !BitBoard::from_square(Square::A1);
+    assert_eq!(danger & get_rank(Rank::First), first_rank);
+
+    // With the king in the way, the plain attack map stops at e1
+    let blocked = board.attacked_by(Color::Black, *board.combined());
+    assert_eq!(blocked & BitBoard::from_square(Square::F1), EMPTY);
+
+    // ... and the king really can't step to f1
+    assert!(!board.legal(ChessMove::new(Square::E1, Square::F1, None)));
+    assert!(board.legal(ChessMove::new(Square::E1, Square::E2, None)));
+}
+
+#[test]
+fn test_is_hanging_undefended() {
+    // The d5 rook is attacked by the d1 rook, and nothing defends it
+    let board = Board::from_str("4k3/8/8/3r4/8/8/8/3RK3 w - - 0 1").unwrap();
+    assert!(board.is_hanging(Square::D5));
+    // ... but the d1 rook is defended by the king
+    assert!(!board.is_hanging(Square::D1));
+    assert!(!board.is_hanging(Square::E1));
+    assert!(!board.is_hanging(Square::A1));
+}
+
+#[test]
+fn test_is_hanging_defended() {
+    // The d5 rook is defended by the e6 pawn, and only an equal rook attacks it
+    let board = Board::from_str("4k3/8/4p3/3r4/8/8/8/3RK3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.attackers_to(Square::D5, Color::Black),
+        BitBoard::from_square(Square::E6)
+    );
+    assert!(!board.is_hanging(Square::D5));
+
+    // A king can't take a defended piece
+    let board = Board::from_str("4k3/8/8/8/8/2p5/3p4/4K3 w - - 0 1").unwrap();
+    assert!(!board.is_hanging(Square::D2));
+}
+
+#[test]
+fn test_debug_string_in_check() {
+    // The b4 bishop checks the e1 king, and the e4 knight is pinned by the e8 rook
+    let board = Board::from_str("4r2k/8/8/8/1b2N3/8/8/4K3 w - - 0 1").unwrap();
+    let debug = board.debug_string();
+
+    assert!(debug.contains("Checkers: b4\n"));
+    assert!(debug.contains("Pinned: e4\n"));
+    assert!(debug.contains("FEN: 4r2k/8/8/8/1b2N3/8/8/4K3 w - - 0 1\n"));
+    assert!(debug.contains("4 . b . . N . . .\n"));
+}
+
+#[test]
+fn test_is_reversible() {
+    let board = Board::from_str("4k3/8/8/3p4/4P3/8/8/1N2K3 w - - 0 1").unwrap();
+
+    // Pawn push
+    assert!(!board.is_reversible(ChessMove::new(Square::E4, Square::E5, None)));
+    // Capture
+    assert!(!board.is_reversible(ChessMove::new(Square::E4, Square::D5, None)));
+    // Knight move
+    assert!(board.is_reversible(ChessMove::new(Square::B1, Square::C3, None)));
+    // King move
+    assert!(board.is_reversible(ChessMove::new(Square::E1, Square::E2, None)));
+}
+
+#[test]
+fn test_num_evasions_single_check() {
+    // The e8 rook checks the e1 king: the king can step aside, or the knight can block on e2
+    let board = Board::from_str("4r2k/8/8/8/8/8/8/2N1K3 w - - 0 1").unwrap();
+    assert_eq!(board.checker_count(), 1);
+
+    assert_eq!(board.num_evasions(), MoveGen::new_legal(&board).count());
+    assert_eq!(board.num_evasions(), 5);
+}
+
+#[test]
+fn test_is_path_clear() {
+    let board = Board::default();
+
+    // The a1 rook is boxed in by its own pawn
+    assert!(!board.is_path_clear(Square::A1, Square::A3));
+    assert!(board.is_path_clear(Square::A1, Square::A2));
+    assert!(!board.is_path_clear(Square::A1, Square::D1));
+    assert!(board.is_path_clear(Square::A1, Square::B1));
+
+    // Pushing the a-pawn still leaves it in the way
+    let board = board.make_move_new(ChessMove::new(Square::A2, Square::A4, None));
+    assert!(!board.is_path_clear(Square::A1, Square::A7));
+
+    // Without it, the file is open
+    let board = Board::from_str("4k3/8/8/8/8/8/1PPPPPPP/R3K3 w - - 0 1").unwrap();
+    assert!(board.is_path_clear(Square::A1, Square::A8));
+}
+
+#[test]
+fn test_check_rays() {
+    // The a1 rook checks the e1 king along the first rank
+    let board = Board::from_str("4k3/8/8/8/8/8/8/r3K3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.check_rays(),
+        vec![
+            BitBoard::from_square(Square::B1)
+                | BitBoard::from_square(Square::C1)
+                | BitBoard::from_square(Square::D1)
+        ]
+    );
+
+    // Double check from the h4 bishop and the d3 knight
+    let board = Board::from_str("4k3/8/8/8/7b/3n4/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.check_rays(),
+        vec![
+            EMPTY,
+            BitBoard::from_square(Square::F2) | BitBoard::from_square(Square::G3)
+        ]
+    );
+}
+
+#[test]
+fn test_make_move_checked() {
+    // The e2 knight is pinned by the e8 rook
+    let board = Board::from_str("4r2k/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+
+    let pinned = ChessMove::new(Square::E2, Square::C3, None);
+    assert!(matches!(
+        board.make_move_checked(pinned),
+        Err(Error::IllegalMove)
+    ));
+
+    let king = ChessMove::new(Square::E1, Square::D2, None);
+    assert_eq!(
+        board.make_move_checked(king).unwrap(),
+        board.make_move_new(king)
+    );
+}
+
+#[test]
+fn test_make_move_checked_promotions() {
+    let board = Board::from_str("7k/4P3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+    for piece in [Piece::King, Piece::Pawn].iter() {
+        let m = ChessMove::new(Square::E7, Square::E8, Some(*piece));
+        assert!(matches!(
+            board.make_move_checked(m),
+            Err(Error::InvalidPromotion)
+        ));
+    }
+
+    // The pawn has to promote on the last rank, and nothing else can
+    let no_promotion = ChessMove::new(Square::E7, Square::E8, None);
+    assert!(matches!(
+        board.make_move_checked(no_promotion),
+        Err(Error::InvalidPromotion)
+    ));
+    let king = ChessMove::new(Square::E1, Square::D2, Some(Piece::Queen));
+    assert!(matches!(
+        board.make_move_checked(king),
+        Err(Error::InvalidPromotion)
+    ));
+
+    // A move nothing can make is still just illegal
+    let nowhere = ChessMove::new(Square::E7, Square::D8, Some(Piece::Queen));
+    assert!(matches!(
+        board.make_move_checked(nowhere),
+        Err(Error::IllegalMove)
+    ));
+
+    let queen = ChessMove::new(Square::E7, Square::E8, Some(Piece::Queen));
+    assert_eq!(
+        board.make_move_checked(queen).unwrap(),
+        board.make_move_new(queen)
+    );
+}
+
+#[test]
+fn test_can_castle_attacked_transit() {
+    // The a5 bishop covers d8, which the black king passes through going queenside, and the h4
+    // rook only attacks the h8 rook, which is allowed
+    let board = Board::from_str("r3k2r/8/8/B7/7R/8/8/4K3 b kq - 0 1").unwrap();
+    assert!(!board.can_castle(Color::Black, CastleSide::QueenSide));
+    assert!(board.can_castle(Color::Black, CastleSide::KingSide));
+
+    // b1 may be attacked: only the king's squares matter
+    let board = Board::from_str("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    assert!(board.can_castle(Color::White, CastleSide::QueenSide));
+    assert!(!board.can_castle(Color::White, CastleSide::KingSide));
+
+    // In check
+    let board = Board::from_str("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(!board.can_castle(Color::White, CastleSide::QueenSide));
+    assert!(!board.can_castle(Color::White, CastleSide::KingSide));
+
+    // Agrees with move generation
+    let board = Board::from_str("r3k2r/8/8/8/2b5/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert!(!board.legal(ChessMove::new(Square::E1, Square::G1, None)));
+    assert!(board.legal(ChessMove::new(Square::E1, Square::C1, None)));
+}
+
+#[test]
+fn test_attackers_fork() {
+    // The c7 knight forks the a8 rook and the e8 king, and the b7 bishop attacks the a8 rook too.
+    // The g2 pawn attacks nothing.
+    let board = Board::from_str("r3k3/1BN5/8/8/8/8/6P1/4K3 b - - 0 1").unwrap();
+    let attackers = board.attackers(Color::White);
+
+    assert_eq!(
+        attackers,
+        BitBoard::from_square(Square::C7) | BitBoard::from_square(Square::B7)
+    );
+
+    // Black's rook and king don't reach any white pieces
+    assert_eq!(board.attackers(Color::Black), EMPTY);
+}
+
+#[test]
+fn test_status_fast_path() {
+    let cases = [
+        // A free pawn push
+        (
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            true,
+            BoardStatus::Ongoing,
+        ),
+        // The only pawn is pinned along the rank, and every king square is covered
+        (
+            "8/8/8/8/1k6/8/KP5r/7r w - - 0 1",
+            false,
+            BoardStatus::Stalemate,
+        ),
+        // The pinned pawn is skipped, but the king can step aside
+        (
+            "4r2k/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            true,
+            BoardStatus::Ongoing,
+        ),
+        // The pawn is blocked, and the king is boxed in
+        (
+            "7k/5Q2/8/8/8/p7/P7/K7 b - - 0 1",
+            false,
+            BoardStatus::Stalemate,
+        ),
+        // No pawn push and no king move, but the knight can move
+        (
+            "7k/5Q2/8/8/8/8/8/K5n1 b - - 0 1",
+            false,
+            BoardStatus::Ongoing,
+        ),
+        // Capturing a defended piece isn't a safe king move
+        (
+            "k7/1N1N4/1K6/8/8/8/8/8 b - - 0 1",
+            false,
+            BoardStatus::Stalemate,
+        ),
+    ];
+
+    for (fen, obvious, status) in cases.iter() {
+        let board = Board::from_str(fen).expect(fen);
+        assert_eq!(board.has_obvious_move(), *obvious, "{}", fen);
+        assert_eq!(board.status(), *status, "{}", fen);
+        if board.has_obvious_move() {
+            assert!(MoveGen::new_legal(&board).len() > 0, "{}", fen);
+        }
+    }
+}
+
+#[test]
+fn test_material_signature() {
+    let kk = Board::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(kk.material_signature(), "KvK");
+
+    let kqkr = Board::from_str("4k3/8/8/2r5/8/8/8/3QK3 w - - 0 1").unwrap();
+    assert_eq!(kqkr.material_signature(), "KQvKR");
+    assert_ne!(kqkr.material_key(), kk.material_key());
+
+    // Material keys don't care where the pieces are, or whose move it is
+    let moved = Board::from_str("1k6/8/8/8/5r2/8/Q7/6K1 b - - 0 1").unwrap();
+    assert_eq!(moved.material_key(), kqkr.material_key());
+
+    // ... but they do care about color
+    let krkq = Board::from_str("4k3/8/8/2R5/8/8/8/3qK3 w - - 0 1").unwrap();
+    assert_eq!(krkq.material_signature(), "KRvKQ");
+    assert_ne!(krkq.material_key(), kqkr.material_key());
+}
+
+#[test]
+fn test_raw_parts_round_trip() {
+    let boards = [
+        Board::default(),
+        // Castle rights, and a pawn that can be captured en passant
+        Board::from_str("r3k2r/8/8/8/3pP3/8/8/R3K2R b KQkq e3 0 1").unwrap(),
+        // In check
+        Board::from_str("4r2k/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap(),
+    ];
+
+    for board in boards.iter() {
+        let parts = board.raw_parts();
+        let rebuilt = Board::from_raw_parts(parts).unwrap();
+        assert_eq!(rebuilt, *board);
+        assert_eq!(rebuilt.get_hash(), board.get_hash());
+        assert_eq!(rebuilt.raw_parts(), parts);
+    }
+
+    // The en passant square must hold a pawn of the side that just moved
+    let mut parts = Board::default().raw_parts();
+    parts.en_passant = Some(Square::E2);
+    assert!(Board::from_raw_parts(parts).is_err());
+
+    // A piece can't be in two places at once
+    let mut parts = Board::default().raw_parts();
+    parts.pieces[Piece::Queen.to_index()] |= BitBoard::from_square(Square::A1);
+    assert!(Board::from_raw_parts(parts).is_err());
+}
+
+#[test]
+fn test_attacked_by_pawn() {
+    // White pawns on c4 and e4 support d5, which black's c6 pawn covers too.  Only white's e4
+    // pawn reaches f5.
+    let board = Board::from_str("4k3/8/2p5/8/2P1P3/8/8/4K3 w - - 0 1").unwrap();
+
+    assert!(board.attacked_by_pawn(Square::D5, Color::White));
+    assert!(board.attacked_by_pawn(Square::D5, Color::Black));
+    assert!(board.attacked_by_pawn(Square::F5, Color::White));
+    assert!(!board.attacked_by_pawn(Square::F5, Color::Black));
+
+    // Pawns only attack forwards
+    assert!(!board.attacked_by_pawn(Square::D3, Color::White));
+    assert!(!board.attacked_by_pawn(Square::B7, Color::Black));
+}
+
+#[test]
+fn test_outpost_squares() {
+    // The classic d5 hole: black has played e5 and d6, and has no c-pawn left to cover d5
+    let board = Board::from_str("4k3/8/3p4/4p3/2P1P3/8/8/4K3 w - - 0 1").unwrap();
+    let outposts = board.outpost_squares(Color::White);
+
+    assert_ne!(outposts & BitBoard::from_square(Square::D5), EMPTY);
+    // c4 supports b5, and e4 supports f5, and no black pawn can reach either
+    assert_eq!(
+        outposts,
+        BitBoard::from_square(Square::B5)
+            | BitBoard::from_square(Square::D5)
+            | BitBoard::from_square(Square::F5)
+    );
+
+    // Black's d4 is just as good: e5 supports it, and the white pawns have passed it
+    assert_eq!(
+        board.outpost_squares(Color::Black),
+        BitBoard::from_square(Square::D4) | BitBoard::from_square(Square::F4)
+    );
+
+    // With a c-pawn, black can kick a piece off d5 and b5 with c7-c6
+    let board = Board::from_str("4k3/2p5/3p4/4p3/2P1P3/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.outpost_squares(Color::White),
+        BitBoard::from_square(Square::F5)
+    );
+}
+
+#[test]
+fn test_from_builder_lenient() {
+    // Black to move, but the white king is in check from the b4 bishop
+    let builder = BoardBuilder::from_str("4k3/8/8/8/1b6/8/8/4K3 b - - 0 1").unwrap();
+    assert!(Board::try_from(&builder).is_err());
+
+    let board = Board::from_builder_lenient(builder).unwrap();
+    assert_eq!(board.side_to_move(), Color::Black);
+    assert_eq!(board.piece_on(Square::B4), Some(Piece::Bishop));
+
+    // Structural problems are still rejected: a missing king, touching kings, and a pawn on the
+    // back rank
+    for fen in [
+        "8/8/8/8/1b6/8/8/4K3 b - - 0 1",
+        "8/8/8/8/8/8/3k4/4K3 w - - 0 1",
+        "4k2P/8/8/8/8/8/8/4K3 w - - 0 1",
+    ]
+    .iter()
+    {
+        let builder = BoardBuilder::from_str(fen).unwrap();
+        assert!(Board::from_builder_lenient(builder).is_err(), "{}", fen);
+    }
+}
+
+#[test]
+fn test_discovered_attackers() {
+    // The b2 bishop is behind the d4 knight, on the long diagonal towards the h8 rook
+    let board = Board::from_str("4k2r/8/8/8/3N4/8/1B6/4K3 w - - 0 1").unwrap();
+
+    assert_eq!(
+        board.discovered_attackers(Square::D4, Square::H8, Color::White),
+        BitBoard::from_square(Square::B2)
+    );
+
+    // Moving something that isn't in the way uncovers nothing
+    assert_eq!(
+        board.discovered_attackers(Square::E1, Square::H8, Color::White),
+        EMPTY
+    );
+
+    // Nothing is uncovered for black
+    assert_eq!(
+        board.discovered_attackers(Square::D4, Square::H8, Color::Black),
+        EMPTY
+    );
+}
+
+#[test]
+fn test_eq_and_hash_ignore_clocks() {
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash_of(board: &Board) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        board.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a = Board::from_str("r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 0 1").unwrap();
+    let b = Board::from_str("r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 12 40").unwrap();
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+
+    // Side to move, castle rights and en passant all count
+    let black = Board::from_str("r3k2r/8/8/8/4p3/8/3P4/R3K2R b KQkq - 0 1").unwrap();
+    let no_castle = Board::from_str("r3k2r/8/8/8/4p3/8/3P4/R3K2R w Kkq - 0 1").unwrap();
+    assert_ne!(a, black);
+    assert_ne!(hash_of(&a), hash_of(&black));
+    assert_ne!(a, no_castle);
+    assert_ne!(hash_of(&a), hash_of(&no_castle));
+
+    let ep = a.make_move_new(ChessMove::new(Square::D2, Square::D4, None));
+    let no_ep = Board::from_str("r3k2r/8/8/8/3Pp3/8/8/R3K2R b KQkq - 0 1").unwrap();
+    assert_ne!(ep, no_ep);
+    assert_ne!(hash_of(&ep), hash_of(&no_ep));
+
+    // An en passant square nobody can use is not recorded at all
+    let far = Board::from_str("r3k2r/8/8/8/8/8/P7/R3K2R w KQkq - 0 1")
+        .unwrap()
+        .make_move_new(ChessMove::new(Square::A2, Square::A4, None));
+    let far_fen = Board::from_str("r3k2r/8/8/8/P7/8/8/R3K2R b KQkq a3 0 1").unwrap();
+    assert_eq!(far, far_fen);
+    assert_eq!(hash_of(&far), hash_of(&far_fen));
+}
+
+#[test]
+fn test_is_recapture() {
+    // Black just played ...Bxd4
+    let board = Board::from_str("4k3/8/8/1p6/P2b4/8/2N5/3QK3 w - - 0 1").unwrap();
+
+    assert!(board.is_recapture(ChessMove::new(Square::D1, Square::D4, None), Square::D4));
+    assert!(board.is_recapture(ChessMove::new(Square::C2, Square::D4, None), Square::D4));
+
+    // A capture somewhere else is not a recapture
+    assert!(!board.is_recapture(ChessMove::new(Square::A4, Square::B5, None), Square::D4));
+
+    // Neither is a quiet move to the square the opponent's last move came from
+    assert!(!board.is_recapture(ChessMove::new(Square::D1, Square::D3, None), Square::D3));
+}
+
+#[test]
+fn test_incremental_pawn_hash_and_material() {
+    fn check(board: &Board) {
+        let rebuilt = Board::from_str(&board.to_string()).unwrap();
+        assert_eq!(board.pawn_hash(), rebuilt.pawn_hash());
+        for color in ALL_COLORS.iter() {
+            assert_eq!(
+                board.non_pawn_material(*color),
+                rebuilt.non_pawn_material(*color)
+            );
+        }
+        board.assert_invariants();
+    }
+
+    // Pawn captures, piece captures and capturing promotions for both sides
+    let mut board = Board::from_str("r3k3/1P6/8/3p4/4P3/8/6p1/4KN2 w - - 0 1").unwrap();
+    for (from, to, promotion) in [
+        (Square::E4, Square::D5, None),
+        (Square::E8, Square::D6, None),
+        (Square::E1, Square::E2, None),
+        (Square::G2, Square::F1, Some(Piece::Queen)),
+        (Square::E2, Square::F1, None),
+        (Square::D6, Square::D5, None),
+        (Square::B7, Square::A8, Some(Piece::Queen)),
+    ]
+    .iter()
+    {
+        board = board.make_move_new(ChessMove::new(*from, *to, *promotion));
+        check(&board);
+    }
+
+    assert_eq!(board.non_pawn_material(Color::White), 900);
+    assert_eq!(board.non_pawn_material(Color::Black), 0);
+
+    // En passant
+    let board = Board::from_str("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+    let board = board.make_move_new(ChessMove::new(Square::D4, Square::E3, None));
+    check(&board);
+}
+
+#[test]
+fn test_legal_moves_by_piece() {
+    let moves = Board::default().legal_moves_by_piece();
+    assert_eq!(moves[Piece::Pawn.to_index()].len(), 16);
+    assert_eq!(moves[Piece::Knight.to_index()].len(), 4);
+    for piece in [Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King].iter() {
+        assert!(moves[piece.to_index()].is_empty());
+    }
+
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let moves = board.legal_moves_by_piece();
+    let total: usize = moves.iter().map(|m| m.len()).sum();
+    assert_eq!(total, MoveGen::movegen_perft_test(&board, 1));
+    assert!(moves[Piece::King.to_index()].contains(&ChessMove::new(Square::E1, Square::G1, None)));
+}
+
+#[test]
+fn test_halfkp_features_mirror() {
+    let board =
+        Board::from_str("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQK2R w KQkq - 0 1")
+            .unwrap();
+    let white = board.halfkp_features(Color::White);
+    let black = board.halfkp_features(Color::Black);
+    assert_eq!(white.len(), 30);
+    assert_eq!(white.len(), black.len());
+
+    let flip = |sq: Square| {
+        Square::make_square(
+            Rank::from_index(7 - sq.get_rank().to_index()),
+            sq.get_file(),
+        )
+    };
+    for (piece, color, sq) in white.iter() {
+        assert!(black.contains(&(*piece, *color, flip(*sq))));
+    }
+    assert!(white.contains(&(Piece::Bishop, Color::Black, Square::C5)));
+    assert!(black.contains(&(Piece::Bishop, Color::Black, Square::C4)));
+}
+
+#[test]
+fn test_stalemate_in_one_moves() {
+    // Qc7 takes every square from the black king, while Qc8 is mate
+    let board = Board::from_str("k7/8/1K6/8/8/8/2Q5/8 w - - 0 1").unwrap();
+    let stalemates = board.stalemate_in_one_moves();
+    assert!(stalemates.contains(&ChessMove::new(Square::C2, Square::C7, None)));
+    assert!(!stalemates.contains(&ChessMove::new(Square::C2, Square::C8, None)));
+    for m in stalemates.iter() {
+        let child = board.make_move_new(*m);
+        assert_eq!(child.status(), BoardStatus::Stalemate);
+        assert_eq!(*child.checkers(), EMPTY);
+    }
+
+    assert!(Board::default().stalemate_in_one_moves().is_empty());
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_random_move_covers_every_move() {
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    let mut rng = SmallRng::seed_from_u64(0x5eed);
+    let board = Board::default();
+    let moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+
+    let mut counts = vec![0; moves.len()];
+    for _ in 0..4000 {
+        let m = board.random_move(&mut rng).unwrap();
+        counts[moves.iter().position(|x| *x == m).unwrap()] += 1;
+    }
+    // 200 draws are expected for each move, so every move should show up plenty of times
+    assert!(counts.iter().all(|c| *c > 100 && *c < 300));
+
+    // Checkmate and stalemate have no moves to pick from
+    let mate =
+        Board::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    assert_eq!(mate.random_move(&mut rng), None);
+    let stalemate = Board::from_str("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(stalemate.random_move(&mut rng), None);
+}
+
+#[test]
+fn test_check_counts() {
+    let moves = [
+        (Square::E2, Square::E4),
+        (Square::E7, Square::E5),
+        (Square::F1, Square::C4),
+        (Square::D8, Square::H4),
+        (Square::C4, Square::F7), // check
+        (Square::E8, Square::F7),
+        (Square::D2, Square::D3),
+        (Square::H4, Square::F2), // check
+        (Square::E1, Square::F2),
+        (Square::G8, Square::F6),
+        (Square::D1, Square::H5), // check
+    ];
+
+    let mut counted = Board::default().track_checks();
+    let mut plain = Board::default();
+    for (from, to) in moves.iter() {
+        counted = counted.make_move_new(ChessMove::new(*from, *to, None));
+        plain = plain.make_move_new(ChessMove::new(*from, *to, None));
+    }
+
+    assert_eq!(counted.check_count(Color::White), 2);
+    assert_eq!(counted.check_count(Color::Black), 1);
+    assert_eq!(plain.check_count(Color::White), 0);
+    assert_eq!(plain.check_count(Color::Black), 0);
+    assert_eq!(counted.get_hash(), plain.get_hash());
+}
+
+#[test]
+fn test_on_center_square() {
+    let board = Board::from_str("8/8/3k4/8/4K3/8/8/8 w - - 0 1").unwrap();
+    assert!(board.on_center_square(Color::White));
+    assert!(!board.on_center_square(Color::Black));
+
+    let board = Board::from_str("8/8/8/3k4/8/4K3/8/8 w - - 0 1").unwrap();
+    assert!(!board.on_center_square(Color::White));
+    assert!(board.on_center_square(Color::Black));
+}
+
+#[test]
+fn test_only_move() {
+    // In check from the rook, with g2 as the only way out
+    let board = Board::from_str("k7/8/8/8/8/8/7P/r6K w - - 0 1").unwrap();
+    assert_eq!(
+        board.only_move(),
+        Some(ChessMove::new(Square::H1, Square::G2, None))
+    );
+
+    // Plenty of moves
+    let board = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert_eq!(board.only_move(), None);
+
+    // No moves at all
+    let board = Board::from_str("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(board.only_move(), None);
+}
+
+#[test]
+fn test_debug_is_fen() {
+    let board = Board::from_str("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+    let debug = format!("{:?}", board);
+    assert!(debug.contains("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1"));
+    assert!(!debug.contains('\n'));
+
+    let pretty = format!("{:#?}", board);
+    assert!(pretty.contains("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1"));
+    assert!(pretty.contains("4 . . . . P . . .\n"));
+}
+
+#[test]
+fn test_zobrist_breakdown() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/8/8/8/3pP3/8/8/R3K2R b Kq e3 0 1",
+        "4k3/8/8/8/8/8/8/4K3 b - - 0 1",
+    ];
+    for fen in fens.iter() {
+        let board = Board::from_str(fen).unwrap();
+        let parts = board.zobrist_breakdown();
+        assert_eq!(
+            parts.pieces ^ parts.castling ^ parts.en_passant ^ parts.side_to_move,
+            board.get_hash()
+        );
+    }
+
+    // Moves only touch the parts they should
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let before = board.zobrist_breakdown();
+    let after = board
+        .make_move_new(ChessMove::new(Square::A1, Square::A2, None))
+        .zobrist_breakdown();
+    assert_ne!(before.pieces, after.pieces);
+    assert_ne!(before.castling, after.castling);
+    assert_eq!(before.en_passant, after.en_passant);
+    assert_ne!(before.side_to_move, after.side_to_move);
+}
+
+#[test]
+fn test_perpetual_check_candidate() {
+    // The queen checks from e8 and e4 while the black king runs between h8 and h7
+    let board = Board::from_str("4Q2k/6p1/7p/8/8/8/8/K7 b - - 0 1").unwrap();
+    assert!(board.is_perpetual_check_candidate());
+
+    // Black can take the checking rook, and white has nothing left to check with
+    let board = Board::from_str("3rR2k/6pp/8/8/8/8/8/K7 b - - 0 1").unwrap();
+    assert!(!board.is_perpetual_check_candidate());
+
+    // Back rank mate is the end of the game, not a perpetual
+    let board = Board::from_str("4R2k/6pp/8/8/8/8/8/K7 b - - 0 1").unwrap();
+    assert!(!board.is_perpetual_check_candidate());
+}
+
+#[test]
+fn test_uci_line_to_san() {
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(
+        board.uci_line_to_san(&["e1g1", "a8a1", "f1a1"]).unwrap(),
+        vec!["O-O", "Rxa1", "Rxa1"]
+    );
+
+    assert!(matches!(
+        Board::default().uci_line_to_san(&["e2e4", "e2e4"]),
+        Err(Error::IllegalMove)
+    ));
+    assert!(Board::default().uci_line_to_san(&["e2"]).is_err());
+}
+
+#[test]
+fn test_dead_positions() {
+    let dead = |fen: &str| Board::from_str(fen).unwrap().is_dead_position();
+
+    // King against king
+    assert!(dead("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+    // King and knight against king
+    assert!(dead("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+    assert!(dead("4k1n1/8/8/8/8/8/8/4K3 b - - 0 1"));
+    // King and bishop against king
+    assert!(dead("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+    // King and bishop against king and bishop, both on dark squares
+    assert!(dead("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+    // Three light-squared bishops against a lone king
+    assert!(dead("4k3/8/8/8/8/8/4B3/3BKB2 w - - 0 1"));
+
+    // Opposite colored bishops can be mated into a corner
+    assert!(!dead("b3k3/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+    // Knight against knight, or knight against bishop
+    assert!(!dead("4k1n1/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+    assert!(!dead("4kb2/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+    // Two knights can't force mate, but a mate is still possible
+    assert!(!dead("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1"));
+    // Anything with a pawn, rook or queen
+    assert!(!dead("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+    assert!(!dead("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"));
+    assert!(!Board::default().is_dead_position());
+}
+
+#[test]
+fn test_legal_move_counts() {
+    assert_eq!(Board::default().legal_move_counts(), [16, 4, 0, 0, 0, 0]);
+
+    // Every promotion counts as its own move
+    let board = Board::from_str("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let counts = board.legal_move_counts();
+    assert_eq!(counts[Piece::Pawn.to_index()], 4);
+    assert_eq!(counts[Piece::King.to_index()], 5);
+
+    let by_piece = board.legal_moves_by_piece();
+    for piece in ALL_PIECES.iter() {
+        assert_eq!(counts[piece.to_index()], by_piece[piece.to_index()].len());
+    }
+}
+
+#[test]
+fn test_underpromotion_moves() {
+    // The b7 pawn can push or take on a8 or c8, and the black pawn on g2 is not white's to move
+    let board = Board::from_str("n1n1k3/1P6/8/8/8/8/6p1/K7 w - - 0 1").unwrap();
+    let moves = board.underpromotion_moves();
+    assert_eq!(moves.len(), 9);
+    for dest in [Square::A8, Square::B8, Square::C8].iter() {
+        for piece in [Piece::Rook, Piece::Bishop, Piece::Knight].iter() {
+            assert!(moves.contains(&ChessMove::new(Square::B7, *dest, Some(*piece))));
+        }
+    }
+
+    // Black's g2 pawn has nothing to take, so it can only push
+    let board = Board::from_str("n1n1k3/1P6/8/8/8/8/6p1/K7 b - - 0 1").unwrap();
+    assert_eq!(board.underpromotion_moves().len(), 3);
+    assert!(Board::default().underpromotion_moves().is_empty());
+}
+
+#[test]
+fn test_king_distance_to() {
+    let board = Board::from_str("7k/8/8/8/4P3/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.king_distance_to(Color::White, Square::E1), Some(0));
+    assert_eq!(board.king_distance_to(Color::White, Square::D6), Some(5));
+    assert_eq!(board.king_distance_to(Color::Black, Square::E6), Some(3));
+    // The squares next to the black king are attacked
+    assert_eq!(board.king_distance_to(Color::White, Square::G7), None);
+
+    // The rook on the second rank, defended by the b3 pawn, cuts the white king off
+    let board = Board::from_str("7k/8/8/8/4P3/1p6/r7/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.king_distance_to(Color::White, Square::E6), None);
+    assert_eq!(board.king_distance_to(Color::White, Square::H1), Some(3));
+}
+
+#[test]
+fn test_hanging_pieces() {
+    // The b5 bishop is attacked by the a6 pawn, and nothing defends the h4 rook from the queen.
+    // Nothing attacks the d4 knight or the e3 pawn.
+    let board = Board::from_str("6k1/8/p7/1B6/3N3R/4P3/8/4K2q w - - 0 1").unwrap();
+    assert_eq!(
+        board.hanging_pieces(Color::White),
+        BitBoard::from_square(Square::B5) | BitBoard::from_square(Square::H4)
+    );
+    for sq in board.color_combined(Color::White) {
+        let expected = BitBoard::from_square(sq) & board.hanging_pieces(Color::White) != EMPTY;
+        assert_eq!(board.is_hanging(sq), expected);
+    }
+}
+
+#[test]
+fn test_check_block_mask() {
+    // A rook check along the file can be blocked on e2 through e7, or the rook taken
+    let board = Board::from_str("4r1k1/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let file = BitBoard::from_file(File::E) & !BitBoard::from_square(Square::E1);
+    assert_eq!(board.check_block_mask(), file);
+
+    // Every legal move by a piece other than the king lands in the mask
+    let board = Board::from_str("4r1k1/8/8/8/8/8/3N4/R3K3 w - - 0 1").unwrap();
+    let mask = board.check_block_mask();
+    for m in MoveGen::new_legal(&board) {
+        if board.piece_on(m.get_source()) != Some(Piece::King) {
+            assert_ne!(BitBoard::from_square(m.get_dest()) & mask, EMPTY);
+        }
+    }
+
+    // A knight check can't be blocked
+    let board = Board::from_str("6k1/8/8/8/8/5n2/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.check_block_mask(), BitBoard::from_square(Square::F3));
+
+    // Double check: only the king can move
+    let board = Board::from_str("4r1k1/8/8/8/8/5n2/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.check_block_mask(), EMPTY);
+}
+
+#[test]
+fn test_attackers_to_by_piece() {
+    // Ruy Lopez, after 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6
+    let board =
+        Board::from_str("r1bqk2r/2p1bppp/p1np1n2/1p2p3/4P3/1B3N2/PPPP1PPP/RNBQR1K1 w kq - 0 8")
+            .unwrap();
+
+    let white = board.attackers_to_by_piece(Square::D5, Color::White);
+    assert_eq!(
+        white[Piece::Pawn.to_index()],
+        BitBoard::from_square(Square::E4)
+    );
+    assert_eq!(
+        white[Piece::Bishop.to_index()],
+        BitBoard::from_square(Square::B3)
+    );
+    assert_eq!(white[Piece::Knight.to_index()], EMPTY);
+    assert_eq!(white[Piece::Queen.to_index()], EMPTY);
+
+    let black = board.attackers_to_by_piece(Square::D5, Color::Black);
+    assert_eq!(
+        black[Piece::Knight.to_index()],
+        BitBoard::from_square(Square::F6)
+    );
+    assert_eq!(black[Piece::Pawn.to_index()], EMPTY);
+
+    // Together, the pieces make up `attackers_to`
+    for color in ALL_COLORS.iter() {
+        let split = board.attackers_to_by_piece(Square::D5, *color);
+        let joined = split.iter().fold(EMPTY, |acc, bb| acc | *bb);
+        assert_eq!(joined, board.attackers_to(Square::D5, *color));
+    }
+}
+
+#[test]
+fn test_is_evasion() {
+    // The e8 rook checks the white king
+    let board = Board::from_str("4r1k1/8/8/8/8/8/3N4/R3K3 w - - 0 1").unwrap();
+
+    // Blocking, and stepping off the file
+    assert!(board.is_evasion(ChessMove::new(Square::D2, Square::E4, None)));
+    assert!(board.is_evasion(ChessMove::new(Square::E1, Square::F2, None)));
+
+    // Staying on the file, or ignoring the check
+    assert!(!board.is_evasion(ChessMove::new(Square::E1, Square::E2, None)));
+    assert!(!board.is_evasion(ChessMove::new(Square::D2, Square::B3, None)));
+    assert!(!board.is_evasion(ChessMove::new(Square::A1, Square::A8, None)));
+
+    // Taking the only checker
+    let board = Board::from_str("4r1k1/8/8/1B6/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(board.is_evasion(ChessMove::new(Square::B5, Square::E8, None)));
+    assert!(!board.is_evasion(ChessMove::new(Square::B5, Square::C6, None)));
+}
+
+#[test]
+fn test_en_passant_file() {
+    let board =
+        Board::from_str("rnbqkbnr/ppp1pppp/8/8/2Pp4/4P3/PP1P1PPP/RNBQKBNR b KQkq c3 0 3").unwrap();
+    assert_eq!(board.en_passant_file(), Some(File::C));
+
+    // No black pawn can take on c3, so the en passant square is dropped
+    let board =
+        Board::from_str("rnbqkbnr/pppppppp/8/8/2P5/8/PP1PPPPP/RNBQKBNR b KQkq c3 0 1").unwrap();
+    assert_eq!(board.en_passant_file(), None);
+}
+
+#[test]
+fn test_capture_gain() {
+    // En passant wins a pawn, even though d6 is empty
+    let board = Board::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert_eq!(
+        board.capture_gain(ChessMove::new(Square::E5, Square::D6, None)),
+        100
+    );
+
+    // Promoting without a capture only gains the promotion, and underpromotion gains less
+    let board = Board::from_str("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let a8 = |piece| ChessMove::new(Square::A7, Square::A8, Some(piece));
+    assert_eq!(board.capture_gain(a8(Piece::Queen)), 800);
+    assert_eq!(board.capture_gain(a8(Piece::Knight)), 200);
+    assert_eq!(
+        board.capture_gain(ChessMove::new(Square::A7, Square::B8, Some(Piece::Knight))),
+        700
+    );
+}
+
+#[test]
+fn test_piece_type_attacks() {
+    let board = Board::default();
+    assert_eq!(
+        board.piece_type_attacks(Piece::Knight, Color::White),
+        get_knight_moves(Square::B1) | get_knight_moves(Square::G1)
+    );
+    assert_eq!(
+        board
+            .piece_type_attacks(Piece::Queen, Color::White)
+            .popcnt(),
+        5
+    );
+
+    // Sliders stop at the first piece in the way
+    let board = Board::from_str("4k3/8/8/8/3p4/8/8/3RK3 w - - 0 1").unwrap();
+    let expected = [
+        Square::A1,
+        Square::B1,
+        Square::C1,
+        Square::D2,
+        Square::D3,
+        Square::D4,
+    ]
+    .iter()
+    .fold(BitBoard::from_square(Square::E1), |bb, sq| {
+        bb | BitBoard::from_square(*sq)
+    });
+    assert_eq!(
+        board.piece_type_attacks(Piece::Rook, Color::White),
+        expected
+    );
+}
+
+#[test]
+fn test_creates_en_passant() {
+    let c2c4 = ChessMove::new(Square::C2, Square::C4, None);
+
+    // The d4 pawn is ready to take
+    let board = Board::from_str("4k3/8/8/8/3p4/8/2P5/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.creates_en_passant(c2c4), Some(Square::C3));
+    assert_eq!(board.make_move_new(c2c4).en_passant(), Some(Square::C4));
+
+    // Nothing can take: a black pawn on d5 is too far back, and a white one next door doesn't count
+    let board = Board::from_str("4k3/8/8/3p4/1P6/8/2P5/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.creates_en_passant(c2c4), None);
+    assert_eq!(board.make_move_new(c2c4).en_passant(), None);
+
+    // Black pushes towards white's pawns
+    let board = Board::from_str("4k3/3p4/8/4P3/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(
+        board.creates_en_passant(ChessMove::new(Square::D7, Square::D5, None)),
+        Some(Square::D6)
+    );
+}
+
+#[test]
+fn test_checking_moves() {
+    // The classic bishop sacrifice on h7
+    let board =
+        Board::from_str("r1bq1rk1/pppn1ppp/4p3/3pP3/1b1P4/2NB1N2/PPP2PPP/R2QK2R w KQ - 0 1")
+            .unwrap();
+    let checks = board.checking_moves();
+    assert!(checks.contains(&ChessMove::new(Square::D3, Square::H7, None)));
+    assert!(checks.iter().all(|m| board.gives_check(*m)));
+
+    // Discovered checks count too
+    let board = Board::from_str("4k3/8/8/8/8/8/4B3/4R1K1 w - - 0 1").unwrap();
+    assert!(board
+        .checking_moves()
+        .contains(&ChessMove::new(Square::E2, Square::B5, None)));
+    assert!(Board::default().checking_moves().is_empty());
+}
+
+#[test]
+fn test_without_castle_rights() {
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let castles = |board: &Board| {
+        MoveGen::new_legal(board)
+            .filter(|m| {
+                m.get_source() == Square::E1
+                    && m.get_source().get_file().distance(m.get_dest().get_file()) == 2
+            })
+            .count()
+    };
+    assert_eq!(castles(&board), 2);
+
+    let stripped = board.without_castle_rights(Color::White);
+    assert_eq!(castles(&stripped), 0);
+    assert_eq!(
+        MoveGen::new_legal(&stripped).len(),
+        MoveGen::new_legal(&board).len() - 2
+    );
+    assert_eq!(
+        stripped,
+        Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w kq - 0 1").unwrap()
+    );
+    assert_eq!(
+        stripped.get_hash(),
+        Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w kq - 0 1")
+            .unwrap()
+            .get_hash()
+    );
+}
+
+#[test]
+fn test_filter_legal() {
+    let board = Board::from_str("r3k2r/8/8/8/8/8/4p3/R3K2R w KQkq - 0 1").unwrap();
+    let candidates = [
+        // Legal: taking the pawn, and taking either rook
+        ChessMove::new(Square::E1, Square::E2, None),
+        ChessMove::new(Square::A1, Square::A8, None),
+        ChessMove::new(Square::H1, Square::H8, None),
+        // Illegal: castling through the pawn's attacks on d1 and f1, a rook move through the
+        // king, and moving a piece that isn't there
+        ChessMove::new(Square::E1, Square::G1, None),
+        ChessMove::new(Square::E1, Square::C1, None),
+        ChessMove::new(Square::A1, Square::F1, None),
+        ChessMove::new(Square::D4, Square::D5, None),
+    ];
+    assert_eq!(board.filter_legal(&candidates), candidates[..3].to_vec());
+    for m in candidates.iter() {
+        assert_eq!(board.legal(*m), board.filter_legal(&[*m]).len() == 1);
+    }
+}
+
+#[test]
+fn test_tapered_material() {
+    let mg = PieceValues([80, 320, 330, 480, 950, 0]);
+    let eg = PieceValues([120, 280, 300, 520, 950, 0]);
 
-        let source_bb = BitBoard::from_square(source);
-        let dest_bb = BitBoard::from_square(dest);
-        let move_bb = source_bb ^ dest_bb;
-        let moved = self.piece_on(source).unwrap();
+    // Full phase: white has promoted its h pawn, and only the midgame values count
+    let board =
+        Board::from_str("rnbqkbnr/pppppppp/8/8/8/7Q/PPPPPPP1/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert_eq!(board.phase(), MAX_PHASE);
+    assert_eq!(board.tapered_material(&mg, &eg), 950 - 80);
 
-        result.xor(moved, source_bb, self.side_to_move);
-        result.xor(moved, dest_bb, self.side_to_move);
-        if let Some(captured) = self.piece_on(dest) {
-            result.xor(captured, dest_bb, !self.side_to_move);
-        }
+    // Zero phase: black has two extra pawns, and only the endgame values count
+    let board = Board::from_str("4k3/ppp5/8/8/8/8/P7/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.phase(), 0);
+    assert_eq!(board.tapered_material(&mg, &eg), -240);
 
-        #[allow(deprecated)]
-        result.remove_their_castle_rights(CastleRights::square_to_castle_rights(
-            !self.side_to_move,
-            dest,
-        ));
+    // Halfway: a rook and two queens, with white up the rook
+    let board = Board::from_str("3qk3/8/8/8/8/8/8/R2QK3 w - - 0 1").unwrap();
+    assert_eq!(board.phase(), 10);
+    assert_eq!(
+        board.tapered_material(&mg, &eg),
+        (480 * 10 + 520 * (MAX_PHASE - 10)) / MAX_PHASE
+    );
 
-        #[allow(deprecated)]
-        result.remove_my_castle_rights(CastleRights::square_to_castle_rights(
-            self.side_to_move,
-            source,
-        ));
+    // The default values give the plain material balance at any phase
+    let values = PieceValues::default();
+    assert_eq!(
+        board.tapered_material(&values, &values),
+        board.material_balance()
+    );
+}
 
-        let opp_king = result.pieces(Piece::King) & result.color_combined(!result.side_to_move);
+#[test]
+fn test_king_escape_squares() {
+    // The e1 rook checks the e8 king along the file.  d7 is covered by the b5 bishop, and e7 is
+    // still on the rook's file, so only d8 and f8 are left, with the f7 pawn in the way.
+    let board = Board::from_str("4k3/5p2/8/1B6/8/8/8/K3R3 b - - 0 1").unwrap();
+    let expected = BitBoard::from_square(Square::D8) | BitBoard::from_square(Square::F8);
+    assert_eq!(board.king_escape_squares(), expected);
 
-        let castles = moved == Piece::King && (move_bb & get_castle_moves()) == move_bb;
+    let king_moves = MoveGen::new_legal(&board)
+        .filter(|m| m.get_source() == Square::E8)
+        .fold(EMPTY, |bb, m| bb | BitBoard::from_square(m.get_dest()));
+    assert_eq!(king_moves, expected);
+}
 
-        let ksq = opp_king.to_square();
+#[test]
+fn test_blocked_pawns() {
+    // A French Advance structure: the d and e pawns are locked, and the f7 pawn is stuck behind
+    // black's own knight
+    let board =
+        Board::from_str("rnbqkb1r/pp3ppp/4pn2/2ppP3/3P4/2P5/PP3PPP/RNBQKBNR w KQkq - 0 1").unwrap();
 
-        const CASTLE_ROOK_START: [File; 8] = [
-            File::A,
-            File::A,
-            File::A,
-            File::A,
-            File::H,
-            File::H,
-            File::H,
-            File::H,
-        ];
-        const CASTLE_ROOK_END: [File; 8] = [
-            File::D,
-            File::D,
-            File::D,
-            File::D,
-            File::F,
-            File::F,
-            File::F,
-            File::F,
-        ];
+    let white = BitBoard::from_square(Square::D4) | BitBoard::from_square(Square::E5);
+    assert_eq!(board.blocked_pawns(Color::White), white);
 
-        if moved == Piece::Knight {
-            result.checkers ^= get_knight_moves(ksq) & dest_bb;
-        } else if moved == Piece::Pawn {
-            if let Some(Piece::Knight) = m.get_promotion() {
-                result.xor(Piece::Pawn, dest_bb, self.side_to_move);
-                result.xor(Piece::Knight, dest_bb, self.side_to_move);
-                result.checkers ^= get_knight_moves(ksq) & dest_bb;
-            } else if let Some(promotion) = m.get_promotion() {
-                result.xor(Piece::Pawn, dest_bb, self.side_to_move);
-                result.xor(promotion, dest_bb, self.side_to_move);
-            } else if (source_bb & get_pawn_source_double_moves()) != EMPTY
-                && (dest_bb & get_pawn_dest_double_moves()) != EMPTY
-            {
-                result.set_ep(dest);
-                result.checkers ^= get_pawn_attacks(ksq, !result.side_to_move, dest_bb);
-            } else if Some(dest.ubackward(self.side_to_move)) == self.en_passant {
-                result.xor(
-                    Piece::Pawn,
-                    BitBoard::from_square(dest.ubackward(self.side_to_move)),
-                    !self.side_to_move,
-                );
-                result.checkers ^= get_pawn_attacks(ksq, !result.side_to_move, dest_bb);
-            } else {
-                result.checkers ^= get_pawn_attacks(ksq, !result.side_to_move, dest_bb);
-            }
-        } else if castles {
-            let my_backrank = self.side_to_move.to_my_backrank();
-            let index = dest.get_file().to_index();
-            let start = BitBoard::set(my_backrank, unsafe {
-                *CASTLE_ROOK_START.get_unchecked(index)
-            });
-            let end = BitBoard::set(my_backrank, unsafe {
-                *CASTLE_ROOK_END.get_unchecked(index)
-            });
-            result.xor(Piece::Rook, start, self.side_to_move);
-            result.xor(Piece::Rook, end, self.side_to_move);
-        }
-        // now, lets see if we're in check or pinned
-        let attackers = result.color_combined(result.side_to_move)
-            & ((get_bishop_rays(ksq)
-                & (result.pieces(Piece::Bishop) | result.pieces(Piece::Queen)))
-                | (get_rook_rays(ksq)
-                    & (result.pieces(Piece::Rook) | result.pieces(Piece::Queen))));
+    let black = BitBoard::from_square(Square::D5)
+        | BitBoard::from_square(Square::E6)
+        | BitBoard::from_square(Square::F7);
+    assert_eq!(board.blocked_pawns(Color::Black), black);
 
-        for sq in attackers {
-            let between = between(sq, ksq) & result.combined();
-            if between == EMPTY {
-                result.checkers ^= BitBoard::from_square(sq);
-            } else if between.popcnt() == 1 {
-                result.pinned ^= between;
-            }
-        }
+    assert_eq!(Board::default().blocked_pawns(Color::White), EMPTY);
+}
 
-        result.side_to_move = !result.side_to_move;
+#[test]
+fn test_try_play() {
+    let line = |uci: &str| -> Vec<ChessMove> {
+        uci.split(' ')
+            .map(|m| ChessMove::from_str(m).unwrap())
+            .collect()
+    };
+
+    let board = Board::default()
+        .try_play(&line("e2e4 e7e5 g1f3 b8c6 f1b5"))
+        .unwrap();
+    assert_eq!(
+        board,
+        Board::from_str("r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3")
+            .unwrap()
+    );
+
+    // The second move tries to move a white pawn on black's turn
+    match Board::default().try_play(&line("e2e4 d2d4 g1f3")) {
+        Err((index, Error::IllegalMove)) => assert_eq!(index, 1),
+        other => panic!("expected the second move to be illegal, got {:?}", other),
     }
 
-    /// Update the pin information.
-    fn update_pin_info(&mut self) {
-        self.pinned = EMPTY;
-        self.checkers = EMPTY;
+    assert_eq!(Board::default().try_play(&[]).unwrap(), Board::default());
+}
 
-        let ksq = (self.pieces(Piece::King) & self.color_combined(self.side_to_move)).to_square();
+#[test]
+fn test_canonical_hash() {
+    // A pawnless ending, and its mirror image, its flip, and a quarter turn
+    let fens = [
+        "8/8/8/3k4/8/8/1Q6/K7 b - - 0 1",
+        "8/8/8/4k3/8/8/6Q1/7K b - - 0 1",
+        "K7/1Q6/8/8/3k4/8/8/8 b - - 0 1",
+        "8/8/8/8/4k3/8/1Q6/K7 b - - 0 1",
+    ];
+    let boards: Vec<Board> = fens
+        .iter()
+        .map(|fen| Board::from_str(fen).unwrap())
+        .collect();
+    for board in boards.iter() {
+        assert_eq!(board.canonical_hash(), boards[0].canonical_hash());
+        assert!(board.canonical_hash() <= board.get_hash());
+    }
+    assert_ne!(boards[0].get_hash(), boards[1].get_hash());
 
-        let pinners = self.color_combined(!self.side_to_move)
-            & ((get_bishop_rays(ksq) & (self.pieces(Piece::Bishop) | self.pieces(Piece::Queen)))
-                | (get_rook_rays(ksq) & (self.pieces(Piece::Rook) | self.pieces(Piece::Queen))));
+    // Whose move it is still matters
+    let white = Board::from_str("8/8/8/3k4/8/8/1Q6/K7 w - - 0 1").unwrap();
+    assert_ne!(white.canonical_hash(), boards[0].canonical_hash());
 
-        for sq in pinners {
-            let between = between(sq, ksq) & self.combined();
-            if between == EMPTY {
-                self.checkers ^= BitBoard::from_square(sq);
-            } else if between.popcnt() == 1 {
-                self.pinned ^= between;
-            }
-        }
+    // With pawns, only the left-right mirror counts
+    let pawns = Board::from_str("4k3/8/8/8/8/8/1P6/4K3 w - - 0 1").unwrap();
+    let mirrored = Board::from_str("3k4/8/8/8/8/8/6P1/3K4 w - - 0 1").unwrap();
+    let flipped = Board::from_str("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(pawns.canonical_hash(), mirrored.canonical_hash());
+    assert_ne!(pawns.canonical_hash(), flipped.canonical_hash());
 
-        self.checkers ^= get_knight_moves(ksq)
-            & self.color_combined(!self.side_to_move)
-            & self.pieces(Piece::Knight);
+    // Castle rights rule out every symmetry
+    let castling = Board::from_str("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    assert_eq!(castling.canonical_hash(), castling.get_hash());
+}
 
-        self.checkers ^= get_pawn_attacks(
-            ksq,
-            self.side_to_move,
-            self.color_combined(!self.side_to_move) & self.pieces(Piece::Pawn),
+#[test]
+fn test_hover_info() {
+    // The d4 rook is blocked by its own pawn on d6 and the enemy knight on f4
+    let board = Board::from_str("4k3/8/3P4/8/3R1n2/8/8/4K3 w - - 0 1").unwrap();
+    let (piece, color, attacks) = board.hover_info(Square::D4).unwrap();
+    assert_eq!((piece, color), (Piece::Rook, Color::White));
+
+    let expected = [
+        Square::D5,
+        Square::D6,
+        Square::E4,
+        Square::F4,
+        Square::C4,
+        Square::B4,
+        Square::A4,
+        Square::D3,
+        Square::D2,
+        Square::D1,
+    ]
+    .iter()
+    .fold(EMPTY, |acc, sq| acc | BitBoard::from_square(*sq));
+    assert_eq!(attacks, expected);
+
+    assert_eq!(board.hover_info(Square::H8), None);
+}
+
+#[test]
+fn test_legal_moves_by_see() {
+    // Rxb5 wins a knight for free, Rxd6 gives up the rook for a pawn
+    let board = Board::from_str("4k3/2p5/3p4/1n6/8/8/8/1R1RK3 w - - 0 1").unwrap();
+    let rxb5 = ChessMove::new(Square::B1, Square::B5, None);
+    let rxd6 = ChessMove::new(Square::D1, Square::D6, None);
+    let quiet = ChessMove::new(Square::B1, Square::A1, None);
+
+    assert_eq!(board.see(rxb5), 300);
+    assert_eq!(board.see(rxd6), 100 - 500);
+    assert_eq!(board.see(quiet), 0);
+
+    let moves = board.legal_moves_by_see();
+    assert_eq!(moves.len(), MoveGen::new_legal(&board).len());
+    assert_eq!(moves[0], rxb5);
+    assert_eq!(moves[moves.len() - 1], rxd6);
+
+    let position = |m| moves.iter().position(|x| *x == m).unwrap();
+    assert!(position(quiet) < position(rxd6));
+}
+
+#[test]
+fn test_board_bytes_round_trip() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+        "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1",
+        "4k3/8/8/8/8/8/8/4K3 b - - 0 1",
+    ];
+    for fen in fens.iter() {
+        let board = Board::from_str(fen).unwrap();
+        let bytes = board.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            11 + (board.combined().popcnt() as usize).div_ceil(2)
         );
+        assert_eq!(Board::from_bytes(&bytes).unwrap(), board, "{}", fen);
     }
 
-    /// Give me the `BitBoard` of my pinned pieces.
-    #[inline]
-    pub fn pinned(&self) -> &BitBoard {
-        &self.pinned
-    }
+    let mut bytes = Board::default().to_bytes();
+    bytes.pop();
+    assert!(Board::from_bytes(&bytes).is_err());
 
-    /// Give me the `Bitboard` of the pieces putting me in check.
-    #[inline]
-    pub fn checkers(&self) -> &BitBoard {
-        &self.checkers
-    }
+    // A piece code of 6 names no piece
+    let mut bytes = Board::default().to_bytes();
+    bytes[11] = 6;
+    assert!(Board::from_bytes(&bytes).is_err());
 }
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let fen: BoardBuilder = self.into();
-        write!(f, "{}", fen)
+#[test]
+fn test_zugzwang_risk() {
+    let board = Board::from_str("8/5k2/5p2/5P2/8/2K5/8/8 w - - 0 1").unwrap();
+    assert!(board.zugzwang_risk());
+
+    // Only the side to move matters
+    let board = Board::from_str("8/5k2/5p2/5P2/8/2K5/8/6n1 w - - 0 1").unwrap();
+    assert!(board.zugzwang_risk());
+    let board = Board::from_str("8/5k2/5p2/5P2/8/2K5/8/6N1 w - - 0 1").unwrap();
+    assert!(!board.zugzwang_risk());
+}
+
+#[test]
+fn test_king_moves() {
+    // The a7 rook covers d7-f7, so only d8 and f8 are left
+    let board = Board::from_str("4k3/R7/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(
+        board.king_moves(),
+        vec![
+            ChessMove::new(Square::E8, Square::D8, None),
+            ChessMove::new(Square::E8, Square::F8, None),
+        ]
+    );
+
+    // Castling through an attacked square, or out of check, isn't allowed
+    let board = Board::from_str("2r1k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(!board
+        .king_moves()
+        .contains(&ChessMove::new(Square::E1, Square::C1, None)));
+    assert!(board
+        .king_moves()
+        .contains(&ChessMove::new(Square::E1, Square::G1, None)));
+    let board = Board::from_str("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(board
+        .king_moves()
+        .iter()
+        .all(|m| m.get_dest() != Square::G1));
+
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ]
+    .iter()
+    {
+        let board = Board::from_str(fen).unwrap();
+        let ksq = board.king_square(board.side_to_move());
+        assert_eq!(board.king_moves(), board.legal_moves_from(ksq), "{}", fen);
     }
 }
 
-impl TryFrom<&BoardBuilder> for Board {
-    type Error = Error;
+#[test]
+fn test_opposite_colored_bishops() {
+    // f1 is light, c8 is light, d8 is dark
+    let same = Board::from_str("2b1k3/5p2/8/8/8/8/5P2/4KB2 w - - 0 1").unwrap();
+    assert!(!same.is_opposite_colored_bishops());
 
-    fn try_from(fen: &BoardBuilder) -> Result<Self, Self::Error> {
-        let mut board = Board::new();
+    let opposite = Board::from_str("3bk3/5p2/8/8/8/8/5P2/4KB2 w - - 0 1").unwrap();
+    assert!(opposite.is_opposite_colored_bishops());
 
-        for sq in ALL_SQUARES.iter() {
-            if let Some((piece, color)) = fen[*sq] {
-                board.xor(piece, BitBoard::from_square(*sq), color);
-            }
-        }
+    // A second bishop for either side rules it out
+    let two = Board::from_str("3bk3/5p2/8/8/8/8/5P2/3BKB2 w - - 0 1").unwrap();
+    assert!(!two.is_opposite_colored_bishops());
+}
 
-        board.side_to_move = fen.get_side_to_move();
+#[test]
+fn test_pins() {
+    // The d2 knight is pinned on the diagonal by the a5 bishop, and the e2 pawn on the file by
+    // the e8 rook.  The f2 pawn has two pieces in front of it on the h4-e1 diagonal, so it isn't
+    // pinned.
+    let board = Board::from_str("k3r3/8/8/b7/7b/6B1/3NPP2/4K3 w - - 0 1").unwrap();
+    let pins = board.pins();
+    assert_eq!(pins.len(), 2);
 
-        if let Some(ep) = fen.get_en_passant() {
-            board.side_to_move = !board.side_to_move;
-            board.set_ep(ep);
-            board.side_to_move = !board.side_to_move;
-        }
+    let knight = pins.iter().find(|(sq, _)| *sq == Square::D2).unwrap().1;
+    assert_eq!(
+        knight,
+        BitBoard::from_square(Square::D2)
+            | BitBoard::from_square(Square::C3)
+            | BitBoard::from_square(Square::B4)
+            | BitBoard::from_square(Square::A5)
+    );
+    let pawn = pins.iter().find(|(sq, _)| *sq == Square::E2).unwrap().1;
+    assert_eq!(
+        pawn,
+        between(Square::E1, Square::E8) | BitBoard::from_square(Square::E8)
+    );
 
-        #[allow(deprecated)]
-        board.add_castle_rights(Color::White, fen.get_castle_rights(Color::White));
-        #[allow(deprecated)]
-        board.add_castle_rights(Color::Black, fen.get_castle_rights(Color::Black));
+    let pinned = pins
+        .iter()
+        .fold(EMPTY, |acc, (sq, _)| acc | BitBoard::from_square(*sq));
+    assert_eq!(pinned, *board.pinned());
 
-        board.update_pin_info();
+    assert!(Board::default().pins().is_empty());
+}
 
-        if board.is_sane() {
-            Ok(board)
-        } else {
-            Err(Error::InvalidBoard)
-        }
+#[test]
+fn test_legal_capture_count() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/Pp2P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq a3 0 1",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+        "8/8/8/KPp4r/8/8/8/6k1 w - c6 0 1",
+    ];
+    for fen in fens.iter() {
+        let board = Board::from_str(fen).unwrap();
+        let expected = MoveGen::new_legal(&board)
+            .filter(|m| board.captured_piece(*m).is_some())
+            .count();
+        assert_eq!(board.legal_capture_count(), expected, "{}", fen);
     }
+
+    // Each capture-promotion counts four times
+    let board = Board::from_str("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.legal_capture_count(), 4);
 }
 
-impl TryFrom<&mut BoardBuilder> for Board {
-    type Error = Error;
+#[test]
+fn test_quiet_and_capture_destinations() {
+    // The e2 pawn can push one or two, or take on d3 or f3
+    let board = Board::from_str("4k3/8/8/8/8/3n1b2/4P3/K7 w - - 0 1").unwrap();
+    assert_eq!(
+        board.quiet_destinations_from(Square::E2),
+        BitBoard::from_square(Square::E3) | BitBoard::from_square(Square::E4)
+    );
+    assert_eq!(
+        board.capture_destinations_from(Square::E2),
+        BitBoard::from_square(Square::D3) | BitBoard::from_square(Square::F3)
+    );
+    assert_eq!(
+        board.quiet_destinations_from(Square::E2) | board.capture_destinations_from(Square::E2),
+        board.legal_destinations(Square::E2)
+    );
 
-    fn try_from(fen: &mut BoardBuilder) -> Result<Self, Self::Error> {
-        (&*fen).try_into()
-    }
+    // En passant lands on an empty square, but is still a capture
+    let board =
+        Board::from_str("rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3").unwrap();
+    assert_eq!(
+        board.capture_destinations_from(Square::D5),
+        BitBoard::from_square(Square::E6)
+    );
+    assert_eq!(
+        board.quiet_destinations_from(Square::D5),
+        BitBoard::from_square(Square::D6)
+    );
 }
 
-impl TryFrom<BoardBuilder> for Board {
-    type Error = Error;
+#[test]
+fn test_incremental_hash() {
+    // Quiet moves, captures, double pushes, en passant, castling both ways, promotions and
+    // underpromotions, and moves that take away castle rights
+    let lines: [(&str, &[&str]); 5] = [
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[
+                "a2a4", "b4a3", "e1g1", "e8c8", "f3h3", "d7d6", "e5f7", "h8h3", "g2h3",
+            ],
+        ),
+        (
+            "8/P1k5/8/8/8/8/1p2K3/R7 b - - 0 1",
+            &["b2a1q", "a7a8n", "c7b7", "e2d3", "a1a8"],
+        ),
+        (
+            "8/P1k5/8/8/8/8/1p2K3/R7 b - - 0 1",
+            &["b2b1r", "a7a8b", "b1a1", "a8d5"],
+        ),
+        (
+            "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+            &["d5e6", "d7e6", "d1d8", "e8d8", "c1f4", "f8b4"],
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            &["a1a8", "e8e7", "h1h8", "e7f7"],
+        ),
+    ];
 
-    fn try_from(fen: BoardBuilder) -> Result<Self, Self::Error> {
-        (&fen).try_into()
+    for (fen, moves) in lines.iter() {
+        let mut board = Board::from_str(fen).unwrap();
+        for uci in moves.iter() {
+            let m = ChessMove::from_str(uci).unwrap();
+            assert!(board.legal(m), "{} in {}", uci, board);
+            board = board.make_move_new(m);
+            assert_eq!(board.hash, board.compute_hash(), "{}", uci);
+            assert_eq!(
+                board.get_hash(),
+                BoardBuilder::from(&board).zobrist(),
+                "{}",
+                uci
+            );
+        }
     }
 }
 
-impl FromStr for Board {
-    type Err = Error;
+#[test]
+fn test_potential_pin_lines() {
+    // The e8 rook lines up with the e1 king through the e2 pawn and the e4 knight.  Neither
+    // is pinned, since there are two of them.
+    let board = Board::from_str("4r1k1/8/8/8/4N3/8/4P3/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.pinners(Color::White), EMPTY);
+    assert_eq!(
+        board.potential_pin_lines(Color::White),
+        vec![(Square::E8, between(Square::E1, Square::E8))]
+    );
 
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        Ok(BoardBuilder::from_str(value)?.try_into()?)
+    // A queen lines up on both kinds of line, a bishop only on diagonals
+    let board = Board::from_str("6k1/8/8/b7/8/8/8/q3K3 w - - 0 1").unwrap();
+    let lines = board.potential_pin_lines(Color::White);
+    assert_eq!(
+        lines,
+        vec![
+            (Square::A1, between(Square::A1, Square::E1)),
+            (Square::A5, between(Square::A5, Square::E1)),
+        ]
+    );
+    assert!(board.potential_pin_lines(Color::Black).is_empty());
+}
+
+#[test]
+fn test_first_evasion() {
+    let mated = [
+        "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+        "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4",
+        "6rk/5Npp/8/8/8/8/8/K7 b - - 0 1",
+    ];
+    for fen in mated.iter() {
+        let board = Board::from_str(fen).unwrap();
+        assert_eq!(board.status(), BoardStatus::Checkmate);
+        assert_eq!(board.first_evasion(), None, "{}", fen);
+    }
+
+    let checked = [
+        // The king steps off the file
+        "4k3/8/8/8/8/8/8/4RK2 b - - 0 1",
+        // Double check: only the king can move
+        "4k3/8/5N2/8/8/8/8/4RK2 b - - 0 1",
+        // Only taking the rook with a promotion gets out of check
+        "1r5K/P5PP/8/8/8/8/8/k7 w - - 0 1",
+    ];
+    for fen in checked.iter() {
+        let board = Board::from_str(fen).unwrap();
+        let evasion = board.first_evasion().expect(fen);
+        assert!(board.legal(evasion), "{} in {}", evasion, fen);
     }
+
+    assert_eq!(Board::default().first_evasion(), None);
 }
 
 #[test]
-fn test_null_move_en_passant() {
-    let start =
-        Board::from_str("rnbqkbnr/pppp2pp/8/4pP2/8/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 0").unwrap();
-    let expected =
-        Board::from_str("rnbqkbnr/pppp2pp/8/4pP2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 0").unwrap();
-    assert_eq!(start.null_move().unwrap(), expected);
+fn test_position_id() {
+    assert_eq!(Board::default().position_id(), "7d394ec8095ed75a");
+
+    let e4 = Board::default().make_move_new(ChessMove::new(Square::E2, Square::E4, None));
+    assert_ne!(e4.position_id(), Board::default().position_id());
 }
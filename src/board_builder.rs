@@ -6,6 +6,7 @@ use crate::file::{File, ALL_FILES};
 use crate::piece::Piece;
 use crate::rank::{Rank, ALL_RANKS};
 use crate::square::{Square, ALL_SQUARES};
+use crate::zobrist::Zobrist;
 
 use std::fmt;
 use std::ops::{Index, IndexMut};
@@ -53,6 +54,12 @@ pub struct BoardBuilder {
     side_to_move: Color,
     castle_rights: [CastleRights; 2],
     en_passant: Option<File>,
+    shredder_castling: bool,
+    // The castling rook files named in a Shredder-FEN, indexed by color, then kingside (0) or
+    // queenside (1).  `None` means the rook furthest from the king.
+    castling_rooks: [[Option<File>; 2]; 2],
+    halfmove_clock: u32,
+    fullmove_number: u32,
 }
 
 impl BoardBuilder {
@@ -62,6 +69,7 @@ impl BoardBuilder {
     /// * `CastleRights` are empty for both sides
     /// * `en_passant` is not set
     /// * `side_to_move` is Color::White
+    /// * The halfmove clock is 0, and the fullmove number is 1
     /// ```
     /// use chess::{BoardBuilder, Board, Square, Color, Piece};
     /// use std::convert::TryInto;
@@ -81,6 +89,10 @@ impl BoardBuilder {
             side_to_move: Color::White,
             castle_rights: [CastleRights::NoRights, CastleRights::NoRights],
             en_passant: None,
+            shredder_castling: false,
+            castling_rooks: [[None; 2]; 2],
+            halfmove_clock: 0,
+            fullmove_number: 1,
         }
     }
 
@@ -116,6 +128,10 @@ impl BoardBuilder {
             side_to_move: side_to_move,
             castle_rights: [white_castle_rights, black_castle_rights],
             en_passant: en_passant,
+            shredder_castling: false,
+            castling_rooks: [[None; 2]; 2],
+            halfmove_clock: 0,
+            fullmove_number: 1,
         };
 
         for piece in pieces.into_iter() {
@@ -167,6 +183,196 @@ impl BoardBuilder {
             .map(|f| Square::make_square((!self.get_side_to_move()).to_fourth_rank(), f))
     }
 
+    /// Get the halfmove clock: the number of half-moves since the last capture or pawn move, as
+    /// used by the fifty-move rule.
+    ///
+    /// ```
+    /// use chess::BoardBuilder;
+    /// use std::str::FromStr;
+    ///
+    /// let bb = BoardBuilder::from_str("4k3/8/8/8/8/8/8/4K3 w - - 12 40").unwrap();
+    /// assert_eq!(bb.get_halfmove_clock(), 12);
+    /// assert_eq!(bb.get_fullmove_number(), 40);
+    /// ```
+    pub fn get_halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Get the fullmove number.  This starts at 1, and goes up after each move by black.
+    pub fn get_fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// The Zobrist hash the `Board` built from this position would have, as in `Board::get_hash`,
+    /// without building it.  The position isn't checked, so an editor can use this to key a cache
+    /// before the position is valid.
+    ///
+    /// As with `Board`, the en passant file only counts when a pawn is there to capture with.
+    ///
+    /// ```
+    /// use chess::{Board, BoardBuilder};
+    ///
+    /// assert_eq!(BoardBuilder::default().zobrist(), Board::default().get_hash());
+    /// ```
+    pub fn zobrist(&self) -> u64 {
+        let mut hash = 0;
+        for sq in ALL_SQUARES.iter() {
+            if let Some((piece, color)) = self[*sq] {
+                hash ^= Zobrist::piece(piece, *sq, color);
+            }
+        }
+
+        for color in [Color::White, Color::Black].iter() {
+            hash ^= Zobrist::castles(self.castle_rights[color.to_index()], *color);
+        }
+
+        if let Some(victim) = self.get_en_passant() {
+            let capturer = Some((Piece::Pawn, self.side_to_move));
+            let can_capture = [victim.left(), victim.right()]
+                .iter()
+                .flatten()
+                .any(|sq| self[*sq] == capturer);
+            if can_capture {
+                hash ^= Zobrist::en_passant(victim.get_file(), !self.side_to_move);
+            }
+        }
+
+        if self.side_to_move == Color::Black {
+            hash ^= Zobrist::color();
+        }
+        hash
+    }
+
+    /// Does this position look like Chess960 (Fischer Random Chess)?
+    ///
+    /// This is true when the castling rights were written with rook files (Shredder-FEN, such as
+    /// "HAha"), when a side has castling rights but its king or rooks are not on their standard
+    /// squares, or when a side's back rank holds all of its original pieces in a non-standard
+    /// order.
+    ///
+    /// ```
+    /// use chess::BoardBuilder;
+    /// use std::str::FromStr;
+    ///
+    /// let standard = BoardBuilder::from_str(
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// ).unwrap();
+    /// assert!(!standard.is_chess960());
+    ///
+    /// let shredder = BoardBuilder::from_str(
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+    /// ).unwrap();
+    /// assert!(shredder.is_chess960());
+    /// ```
+    pub fn is_chess960(&self) -> bool {
+        self.shredder_castling
+            || self.nonstandard_placement(Color::White)
+            || self.nonstandard_placement(Color::Black)
+    }
+
+    /// Write the FEN with standard castle rights ("KQkq"), even if the position was read from
+    /// Shredder-FEN.  This fails with `Error::NonStandardCastling` when a side that can castle
+    /// doesn't have its king on the e file, or its castling rook on the a or h file.
+    ///
+    /// ```
+    /// use chess::BoardBuilder;
+    /// use std::str::FromStr;
+    ///
+    /// let shredder = BoardBuilder::from_str("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1").unwrap();
+    /// assert_eq!(
+    ///     shredder.to_standard_fen().unwrap(),
+    ///     "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+    /// );
+    ///
+    /// let chess960 = BoardBuilder::from_str("rk5r/8/8/8/8/8/8/RK5R w HAha - 0 1").unwrap();
+    /// assert!(chess960.to_standard_fen().is_err());
+    /// ```
+    pub fn to_standard_fen(&self) -> Result<String, Error> {
+        for color in [Color::White, Color::Black].iter() {
+            let rights = self.castle_rights[color.to_index()];
+            if rights == CastleRights::NoRights {
+                continue;
+            }
+
+            let back_rank = color.to_my_backrank();
+            if self[Square::make_square(back_rank, File::E)] != Some((Piece::King, *color)) {
+                return Err(Error::NonStandardCastling);
+            }
+            if rights.has_kingside()
+                && self.castling_rook_file(*color, CastleRights::KingSide) != Some(File::H)
+            {
+                return Err(Error::NonStandardCastling);
+            }
+            if rights.has_queenside()
+                && self.castling_rook_file(*color, CastleRights::QueenSide) != Some(File::A)
+            {
+                return Err(Error::NonStandardCastling);
+            }
+        }
+
+        let mut standard = *self;
+        standard.shredder_castling = false;
+        Ok(standard.to_string())
+    }
+
+    /// Write the FEN with castle rights as the files of the castling rooks (Shredder-FEN, such
+    /// as "HAha"), which works for both standard chess and Chess960.
+    ///
+    /// ```
+    /// use chess::BoardBuilder;
+    ///
+    /// assert_eq!(
+    ///     BoardBuilder::default().to_shredder_fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+    /// );
+    /// ```
+    pub fn to_shredder_fen(&self) -> String {
+        let mut shredder = *self;
+        shredder.shredder_castling = true;
+        shredder.to_string()
+    }
+
+    /// Is `color`s back rank set up in a way that standard chess can't explain?
+    fn nonstandard_placement(&self, color: Color) -> bool {
+        let back_rank = color.to_my_backrank();
+        let on_back_rank = |file: File| match self[Square::make_square(back_rank, file)] {
+            Some((piece, c)) if c == color => Some(piece),
+            _ => None,
+        };
+
+        let rights = self.castle_rights[color.to_index()];
+        if rights != CastleRights::NoRights && on_back_rank(File::E) != Some(Piece::King) {
+            return true;
+        }
+        if rights.has_kingside() && on_back_rank(File::H) != Some(Piece::Rook) {
+            return true;
+        }
+        if rights.has_queenside() && on_back_rank(File::A) != Some(Piece::Rook) {
+            return true;
+        }
+
+        // All eight pieces are home, but shuffled
+        let standard = [
+            Piece::Rook,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Queen,
+            Piece::King,
+            Piece::Bishop,
+            Piece::Knight,
+            Piece::Rook,
+        ];
+        let mut back_rank_pieces: Vec<Piece> =
+            ALL_FILES.iter().filter_map(|f| on_back_rank(*f)).collect();
+        if back_rank_pieces.len() != 8 || back_rank_pieces == standard {
+            return false;
+        }
+        let mut sorted_standard = standard;
+        sorted_standard.sort();
+        back_rank_pieces.sort();
+        back_rank_pieces == sorted_standard
+    }
+
     /// Set the side to move on the position
     ///
     /// This function can be used on self directly or in a builder pattern.
@@ -259,6 +465,67 @@ impl BoardBuilder {
         self.en_passant = file;
         self
     }
+
+    /// Set the halfmove clock.
+    ///
+    /// This function can be used directly or in a builder pattern.
+    ///
+    /// ```
+    /// use chess::BoardBuilder;
+    ///
+    /// let mut bb = BoardBuilder::default();
+    /// bb.halfmove_clock(4).fullmove_number(20);
+    /// assert_eq!(
+    ///     bb.to_string(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 20"
+    /// );
+    /// ```
+    pub fn halfmove_clock(&mut self, halfmove_clock: u32) -> &mut Self {
+        self.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    /// Set the fullmove number.
+    ///
+    /// This function can be used directly or in a builder pattern.
+    pub fn fullmove_number(&mut self, fullmove_number: u32) -> &mut Self {
+        self.fullmove_number = fullmove_number;
+        self
+    }
+
+    /// Find the file of the rook `color` castles with on `side`, for writing Shredder-FEN.  This
+    /// is the file the Shredder-FEN this was read from named, if a rook is still there, or else
+    /// the rook furthest from the king on that side of the back rank.
+    fn castling_rook_file(&self, color: Color, side: CastleRights) -> Option<File> {
+        let back_rank = color.to_my_backrank();
+        let index = if side == CastleRights::KingSide { 0 } else { 1 };
+        if let Some(file) = self.castling_rooks[color.to_index()][index] {
+            if self[Square::make_square(back_rank, file)] == Some((Piece::Rook, color)) {
+                return Some(file);
+            }
+        }
+
+        let king_file = ALL_FILES
+            .iter()
+            .find(|f| self[Square::make_square(back_rank, **f)] == Some((Piece::King, color)))?;
+        let is_rook =
+            |f: &&File| self[Square::make_square(back_rank, **f)] == Some((Piece::Rook, color));
+
+        if side == CastleRights::KingSide {
+            ALL_FILES
+                .iter()
+                .rev()
+                .take_while(|f| *f > king_file)
+                .find(is_rook)
+                .copied()
+        } else {
+            ALL_FILES
+                .iter()
+                .take_while(|f| *f < king_file)
+                .find(is_rook)
+                .copied()
+        }
+    }
 }
 
 impl Index<Square> for BoardBuilder {
@@ -312,16 +579,35 @@ impl fmt::Display for BoardBuilder {
             write!(f, "b ")?;
         }
 
-        write!(
-            f,
-            "{}",
-            self.castle_rights[Color::White.to_index()].to_string(Color::White)
-        )?;
-        write!(
-            f,
-            "{}",
-            self.castle_rights[Color::Black.to_index()].to_string(Color::Black)
-        )?;
+        for color in [Color::White, Color::Black].iter() {
+            let rights = self.castle_rights[color.to_index()];
+            if !self.shredder_castling {
+                write!(f, "{}", rights.to_string(*color))?;
+                continue;
+            }
+
+            // Shredder-FEN: the file of each castling rook, kingside first
+            let sides = [
+                (rights.has_kingside(), CastleRights::KingSide),
+                (rights.has_queenside(), CastleRights::QueenSide),
+            ];
+            for (has_side, side) in sides.iter() {
+                if !has_side {
+                    continue;
+                }
+                match self.castling_rook_file(*color, *side) {
+                    Some(file) => {
+                        let letter = (b'a' + file.to_index() as u8) as char;
+                        if *color == Color::White {
+                            write!(f, "{}", letter.to_ascii_uppercase())?;
+                        } else {
+                            write!(f, "{}", letter)?;
+                        }
+                    }
+                    None => write!(f, "{}", side.to_string(*color))?,
+                }
+            }
+        }
         if self.castle_rights[0] == CastleRights::NoRights
             && self.castle_rights[1] == CastleRights::NoRights
         {
@@ -330,12 +616,13 @@ impl fmt::Display for BoardBuilder {
 
         write!(f, " ")?;
         if let Some(sq) = self.get_en_passant() {
-            write!(f, "{}", sq)?;
+            // FEN names the square the pawn skipped over, not the pawn itself
+            write!(f, "{}", sq.ubackward(!self.side_to_move))?;
         } else {
             write!(f, "-")?;
         }
 
-        write!(f, " 0 1")
+        write!(f, " {} {}", self.halfmove_clock, self.fullmove_number)
     }
 }
 
@@ -353,7 +640,12 @@ impl FromStr for BoardBuilder {
         let mut cur_file = File::A;
         let mut fen = &mut BoardBuilder::new();
 
-        let tokens: Vec<&str> = value.split(' ').collect();
+        // FENs copied from files may start with a byte order mark, and may have extra spaces or
+        // tabs between the fields
+        let tokens: Vec<&str> = value
+            .trim_start_matches('\u{feff}')
+            .split_whitespace()
+            .collect();
         if tokens.len() < 4 {
             return Err(Error::InvalidFen {
                 fen: value.to_string(),
@@ -472,10 +764,42 @@ impl FromStr for BoardBuilder {
             fen.castle_rights[Color::Black.to_index()] = CastleRights::NoRights;
         }
 
+        // Shredder-FEN names the file of each castling rook instead.  A rook on the king's right
+        // castles kingside, and one on its left castles queenside.
+        for x in castles.chars() {
+            let (color, file) = match x {
+                'A'..='H' => (Color::White, x as usize - 'A' as usize),
+                'a'..='h' => (Color::Black, x as usize - 'a' as usize),
+                _ => continue,
+            };
+            let back_rank = color.to_my_backrank();
+            let king_file = ALL_FILES
+                .iter()
+                .find(|f| fen[Square::make_square(back_rank, **f)] == Some((Piece::King, color)));
+            if let Some(king_file) = king_file {
+                let (side, index) = if file > king_file.to_index() {
+                    (CastleRights::KingSide, 0)
+                } else {
+                    (CastleRights::QueenSide, 1)
+                };
+                fen.castle_rights[color.to_index()] = fen.castle_rights[color.to_index()].add(side);
+                fen.castling_rooks[color.to_index()][index] = Some(File::from_index(file));
+            }
+            fen.shredder_castling = true;
+        }
+
         if let Ok(sq) = Square::from_str(&ep) {
             fen = fen.en_passant(Some(sq.get_file()));
         }
 
+        // The counters are optional.  Missing or malformed ones get their starting values.
+        fen.halfmove_clock = tokens.get(4).and_then(|n| n.parse().ok()).unwrap_or(0);
+        fen.fullmove_number = tokens
+            .get(5)
+            .and_then(|n| n.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(1);
+
         Ok(*fen)
     }
 }
@@ -555,3 +879,158 @@ fn test_in_check() {
     let res: Result<Board, _> = bb.try_into();
     assert!(res.is_err()); // My opponent cannot be in check when it's my move.
 }
+
+#[test]
+fn untrusted_fen_does_not_panic() {
+    // A multibyte character where the en-passant square should be
+    assert!(BoardBuilder::from_str("4k3/8/8/8/8/8/8/4K3 w - é 0 1").is_ok());
+    assert!(Square::from_str("é").is_err());
+
+    // Kingless and double-king boards are rejected rather than crashing
+    let result: Result<Board, _> = BoardBuilder::from_str("8/8/8/8/8/8/8/8 w - - 0 1")
+        .unwrap()
+        .try_into();
+    assert!(result.is_err());
+    assert!(Board::from_str("8/8/8/8/8/8/8/4K3 b - - 0 1").is_err());
+    assert!(Board::from_str("k7/8/8/8/8/8/8/KK6 w - - 0 1").is_err());
+}
+
+#[test]
+fn detect_chess960() {
+    let standard = BoardBuilder::default();
+    assert!(!standard.is_chess960());
+
+    // After castling, the back rank is no longer complete
+    let castled = BoardBuilder::from_str(
+        "r1bq1rk1/pppp1ppp/2n2n2/2b1p3/2B1P3/2N2N2/PPPP1PPP/R1BQ1RK1 w - - 6 5",
+    )
+    .unwrap();
+    assert!(!castled.is_chess960());
+
+    // A scrambled back rank, with castling rights written as rook files
+    let scrambled =
+        BoardBuilder::from_str("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1").unwrap();
+    assert!(scrambled.is_chess960());
+    assert_eq!(
+        scrambled.get_castle_rights(Color::White),
+        CastleRights::Both
+    );
+    assert_eq!(
+        scrambled.get_castle_rights(Color::Black),
+        CastleRights::Both
+    );
+
+    // The same position without castling rights still looks like Chess960
+    let board = Board::from_str("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w - - 0 1").unwrap();
+    assert!(board.is_chess960());
+    assert!(!Board::default().is_chess960());
+
+    // Standard rights, but the king isn't on e1
+    let moved_king = BoardBuilder::from_str("4k3/8/8/8/8/8/8/R4K1R w KQ - 0 1").unwrap();
+    assert!(moved_king.is_chess960());
+}
+
+#[test]
+fn fen_round_trips() {
+    let fens = [
+        // Standard castling, counters, and en passant
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 17 52",
+        // Chess960 with Shredder-FEN castling and non-default counters
+        "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 3 17",
+        "bqnbrkrn/ppp1pppp/8/8/3pP3/8/PPPP1PPP/BQNBRKR1 b Eg e3 0 9",
+        // Castling with an inner rook, when another rook stands further out
+        "rk2r2r/8/8/8/8/8/8/RK2R2R w Ee - 0 1",
+        "rk2r2r/8/8/8/8/8/8/RK2R2R w EAha - 0 1",
+    ];
+    for fen in fens.iter() {
+        assert_eq!(BoardBuilder::from_str(fen).unwrap().to_string(), *fen);
+    }
+
+    // Without counters, the starting values are written
+    assert_eq!(
+        BoardBuilder::from_str("4k3/8/8/8/8/8/8/4K3 w - -")
+            .unwrap()
+            .to_string(),
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1"
+    );
+
+    // Standard-style castling letters are auto-detected as well
+    let standard = BoardBuilder::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let shredder = BoardBuilder::from_str("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1").unwrap();
+    for color in [Color::White, Color::Black].iter() {
+        assert_eq!(
+            standard.get_castle_rights(*color),
+            shredder.get_castle_rights(*color)
+        );
+    }
+}
+
+#[test]
+fn standard_and_shredder_fens() {
+    let standard = "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1";
+    let shredder = "r3k2r/8/8/8/8/8/8/R3K2R w Ha - 0 1";
+    for fen in [standard, shredder].iter() {
+        let builder = BoardBuilder::from_str(fen).unwrap();
+        assert_eq!(builder.to_standard_fen().unwrap(), standard);
+        assert_eq!(builder.to_shredder_fen(), shredder);
+    }
+
+    let board = Board::from_str(standard).unwrap();
+    assert_eq!(board.to_standard_fen().unwrap(), standard);
+    assert_eq!(board.to_shredder_fen(), shredder);
+
+    // The kings start on f1 and f8, so "KQkq" would mean something else
+    let chess960 =
+        BoardBuilder::from_str("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1").unwrap();
+    assert!(matches!(
+        chess960.to_standard_fen(),
+        Err(Error::NonStandardCastling)
+    ));
+    assert_eq!(
+        chess960.to_shredder_fen(),
+        "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1"
+    );
+
+    // Without castle rights, any position can be written in standard FEN
+    let no_rights =
+        BoardBuilder::from_str("bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w - - 0 1").unwrap();
+    assert_eq!(
+        no_rights.to_standard_fen().unwrap(),
+        "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w - - 0 1"
+    );
+}
+
+#[test]
+fn parse_fen_with_extra_whitespace() {
+    let expected = Board::default();
+    let fens = [
+        "\u{feff}rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR  w   KQkq -\t0 1",
+        "  rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\r\n",
+    ];
+    for fen in fens.iter() {
+        assert_eq!(Board::from_str(fen).unwrap(), expected);
+    }
+
+    assert!(Board::from_str("\u{feff}").is_err());
+    assert!(Board::from_str("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR   w").is_err());
+    assert!(Board::from_str("rnbqkbnr/pppp pppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+}
+
+#[test]
+fn zobrist_matches_board() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "rnbqkbnr/ppp1pppp/8/8/2Pp4/4P3/PP1P1PPP/RNBQKBNR b KQkq c3 0 3",
+        "rnbqkbnr/pppp1ppp/8/3Pp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 0 1",
+    ];
+    for fen in fens.iter() {
+        let builder = BoardBuilder::from_str(fen).unwrap();
+        let board = Board::from_str(fen).unwrap();
+        assert_eq!(builder.zobrist(), board.get_hash(), "{}", fen);
+    }
+}
@@ -8,6 +8,7 @@
 extern crate rand;
 mod bitboard;
 mod color;
+mod direction;
 mod error;
 mod file;
 mod gen_tables;
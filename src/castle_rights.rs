@@ -27,6 +27,13 @@ pub const ALL_CASTLE_RIGHTS: [CastleRights; NUM_CASTLE_RIGHTS] = [
     CastleRights::Both,
 ];
 
+/// Which way can a player castle?
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Debug, Hash)]
+pub enum CastleSide {
+    KingSide,
+    QueenSide,
+}
+
 const CASTLES_PER_SQUARE: [[u8; 64]; 2] = [
     [
         2, 0, 0, 0, 3, 0, 0, 1, // 1
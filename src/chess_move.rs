@@ -1,4 +1,5 @@
 use crate::board::Board;
+use crate::color::Color;
 use crate::error::Error;
 use crate::file::File;
 use crate::movegen::MoveGen;
@@ -30,6 +31,48 @@ impl ChessMove {
         }
     }
 
+    /// The move that castles kingside for `color`, written the way this library writes castling
+    /// moves: as the king moving two squares, from the e file to the g file.
+    ///
+    /// ```
+    /// use chess::{ChessMove, Color, Square};
+    ///
+    /// assert_eq!(
+    ///     ChessMove::castle_kingside(Color::White),
+    ///     ChessMove::new(Square::E1, Square::G1, None)
+    /// );
+    /// ```
+    #[inline]
+    pub fn castle_kingside(color: Color) -> ChessMove {
+        let rank = color.to_my_backrank();
+        ChessMove::new(
+            Square::make_square(rank, File::E),
+            Square::make_square(rank, File::G),
+            None,
+        )
+    }
+
+    /// The move that castles queenside for `color`: the king moving from the e file to the c
+    /// file.
+    ///
+    /// ```
+    /// use chess::{ChessMove, Color, Square};
+    ///
+    /// assert_eq!(
+    ///     ChessMove::castle_queenside(Color::Black),
+    ///     ChessMove::new(Square::E8, Square::C8, None)
+    /// );
+    /// ```
+    #[inline]
+    pub fn castle_queenside(color: Color) -> ChessMove {
+        let rank = color.to_my_backrank();
+        ChessMove::new(
+            Square::make_square(rank, File::E),
+            Square::make_square(rank, File::C),
+            None,
+        )
+    }
+
     /// Get the source square (square the piece is currently on).
     #[inline]
     pub fn get_source(&self) -> Square {
@@ -49,6 +92,9 @@ impl ChessMove {
     }
     /// Convert a SAN (Standard Algebraic Notation) move into a `ChessMove`
     ///
+    /// Castling may also be written with zeros (`0-0`, `0-0-0`), and pieces may be written with
+    /// figurines (`♘f3` or `♞f6`) instead of letters.
+    ///
     /// ```
     /// use chess::{Board, ChessMove, Square};
     ///
@@ -57,8 +103,20 @@ impl ChessMove {
     ///     ChessMove::from_san(&board, "e4").expect("e4 is valid in the initial position"),
     ///     ChessMove::new(Square::E2, Square::E4, None)
     /// );
+    /// assert_eq!(
+    ///     ChessMove::from_san(&board, "♘f3").expect("Nf3 is valid in the initial position"),
+    ///     ChessMove::new(Square::G1, Square::F3, None)
+    /// );
     /// ```
     pub fn from_san(board: &Board, move_text: &str) -> Result<ChessMove, Error> {
+        let normalized;
+        let move_text = if move_text.starts_with("0-0") || !move_text.is_ascii() {
+            normalized = normalize_san(move_text);
+            normalized.as_str()
+        } else {
+            move_text
+        };
+
         // Castles first...
         if move_text == "O-O" || move_text == "O-O-O" {
             let rank = board.side_to_move().to_my_backrank();
@@ -123,7 +181,7 @@ impl ChessMove {
         // [Optional Source Specifier] ( "" | "a-h" | "1-8" | ("a-h" + "1-8"))
         // [Optional Takes Specifier] ("" | "x")
         // [Full Destination Square] ("a-h" + "0-8")
-        // [Optional Promotion Specifier] ("" | "N" | "B" | "R" | "Q")
+        // [Optional Promotion Specifier] ("" | "N" | "B" | "R" | "Q" | "=N" | "=B" | "=R" | "=Q")
         // [Optional Check(mate) Specifier] ("" | "+" | "#")
         // [Optional En Passant Specifier] ("" | " e.p.")
 
@@ -269,6 +327,11 @@ impl ChessMove {
             sq
         };
 
+        // Allow the promotion to be written as either "e8Q" or "e8=Q"
+        if move_text.get(cur_index..(cur_index + 1)) == Some("=") {
+            cur_index += 1;
+        }
+
         let promotion = if let Some(s) = move_text.get(cur_index..(cur_index + 1)) {
             match s {
                 "N" => {
@@ -359,7 +422,12 @@ impl ChessMove {
                 }
             }
 
-            if !ep && takes {
+            // a legal pawn move that changes files is a capture, even when it lands on an empty
+            // square, so "exd6" is fine for an en passant capture without the " e.p." suffix
+            let pawn_capture =
+                moving_piece == Piece::Pawn && m.get_source().get_file() != m.get_dest().get_file();
+
+            if !ep && !pawn_capture && takes {
                 if board.piece_on(m.get_dest()).is_none() {
                     continue;
                 }
@@ -381,6 +449,40 @@ impl fmt::Display for ChessMove {
     }
 }
 
+/// Display a legal move in SAN (Standard Algebraic Notation).  SAN depends on the position, so
+/// this pairs the move with the `Board` it is played on, and formats it with `Board::san`.
+///
+/// ```
+/// use chess::{Board, ChessMove, SanMove, Square};
+///
+/// let board = Board::default();
+/// let m = ChessMove::new(Square::G1, Square::F3, None);
+///
+/// assert_eq!(format!("1. {}", SanMove(&board, m)), "1. Nf3");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SanMove<'a>(pub &'a Board, pub ChessMove);
+
+impl<'a> fmt::Display for SanMove<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.san(self.1))
+    }
+}
+
+/// `ChessMove`s are ordered by source square, then destination square, then promotion piece (with
+/// no promotion first).  This is a canonical order, useful for sorting, deduplicating and binary
+/// searching move lists; it says nothing about how good a move is.
+///
+/// ```
+/// use chess::{ChessMove, Piece, Square};
+///
+/// let a = ChessMove::new(Square::A7, Square::A8, Some(Piece::Queen));
+/// let b = ChessMove::new(Square::A7, Square::B8, None);
+/// let c = ChessMove::new(Square::B7, Square::A8, None);
+///
+/// assert!(a < b);
+/// assert!(b < c);
+/// ```
 impl Ord for ChessMove {
     fn cmp(&self, other: &ChessMove) -> Ordering {
         if self.source != other.source {
@@ -401,14 +503,40 @@ impl Ord for ChessMove {
     }
 }
 
+/// Rewrite the variations `from_san` tolerates into plain SAN: zeros for castling become letters,
+/// and figurines become piece letters (or nothing, for pawns).
+fn normalize_san(move_text: &str) -> String {
+    let san: String = move_text
+        .chars()
+        .filter_map(|c| match c {
+            '♔' | '♚' => Some('K'),
+            '♕' | '♛' => Some('Q'),
+            '♖' | '♜' => Some('R'),
+            '♗' | '♝' => Some('B'),
+            '♘' | '♞' => Some('N'),
+            '♙' | '♟' => None,
+            c => Some(c),
+        })
+        .collect();
+
+    if san.starts_with("0-0-0") {
+        san.replacen("0-0-0", "O-O-O", 1)
+    } else if san.starts_with("0-0") {
+        san.replacen("0-0", "O-O", 1)
+    } else {
+        san
+    }
+}
+
 /// Convert a UCI `String` to a move. If invalid, return `None`
 /// ```
-/// use chess::{ChessMove, Square, Piece};
+/// use chess::{ChessMove, Error, Square, Piece};
 /// use std::str::FromStr;
 ///
 /// let mv = ChessMove::new(Square::E7, Square::E8, Some(Piece::Queen));
 ///
 /// assert_eq!(ChessMove::from_str("e7e8q").expect("Valid Move"), mv);
+/// assert!(matches!(ChessMove::from_str("e7e8k"), Err(Error::InvalidUciMove)));
 /// ```
 impl FromStr for ChessMove {
     type Err = Error;
@@ -432,6 +560,48 @@ impl FromStr for ChessMove {
     }
 }
 
+/// Write a line of moves, such as a principal variation, in UCI notation separated by spaces.
+///
+/// ```
+/// use chess::{uci_line, ChessMove, Square};
+///
+/// let pv = [
+///     ChessMove::new(Square::E2, Square::E4, None),
+///     ChessMove::new(Square::E7, Square::E5, None),
+/// ];
+/// assert_eq!(uci_line(&pv), "e2e4 e7e5");
+/// ```
+pub fn uci_line(moves: &[ChessMove]) -> String {
+    moves
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Read a line of UCI moves separated by whitespace, the inverse of `uci_line`.  This only checks
+/// that each move is well formed, not that it is legal; see `Board::uci_line_to_san` for that.
+///
+/// ```
+/// use chess::{parse_uci_line, ChessMove, Piece, Square};
+///
+/// let pv = parse_uci_line("e2e4 e7e5 a7a8q").unwrap();
+/// assert_eq!(pv[0], ChessMove::new(Square::E2, Square::E4, None));
+/// assert_eq!(pv[2], ChessMove::new(Square::A7, Square::A8, Some(Piece::Queen)));
+///
+/// assert!(parse_uci_line("e2e4 Nf3").is_err());
+/// ```
+pub fn parse_uci_line(line: &str) -> Result<Vec<ChessMove>, Error> {
+    line.split_whitespace()
+        .map(|uci| {
+            if uci.len() != 4 && uci.len() != 5 {
+                return Err(Error::InvalidUciMove);
+            }
+            ChessMove::from_str(uci)
+        })
+        .collect()
+}
+
 #[test]
 fn test_basic_moves() {
     let board = Board::default();
@@ -440,3 +610,98 @@ fn test_basic_moves() {
         ChessMove::new(Square::E2, Square::E4, None)
     );
 }
+
+#[test]
+fn test_sort_and_binary_search() {
+    let board = Board::default();
+    let mut moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    moves.sort();
+
+    assert!(moves.windows(2).all(|w| w[0] < w[1]));
+    // b1 comes before any square on the second rank
+    assert_eq!(moves[0], ChessMove::new(Square::B1, Square::A3, None));
+
+    let e4 = ChessMove::new(Square::E2, Square::E4, None);
+    let index = moves.binary_search(&e4).expect("e4 is legal");
+    assert_eq!(moves[index], e4);
+
+    let e5 = ChessMove::new(Square::E2, Square::E5, None);
+    assert!(moves.binary_search(&e5).is_err());
+}
+
+#[test]
+fn test_san_variations() {
+    let board = Board::from_str("r3k2r/pppq1ppp/2np1n2/4p3/4P3/2NP1N2/PPPQ1PPP/R3K2R w KQkq - 0 1")
+        .unwrap();
+    assert_eq!(
+        ChessMove::from_san(&board, "0-0").unwrap(),
+        ChessMove::new(Square::E1, Square::G1, None)
+    );
+    assert_eq!(
+        ChessMove::from_san(&board, "0-0-0").unwrap(),
+        ChessMove::new(Square::E1, Square::C1, None)
+    );
+    assert_eq!(
+        ChessMove::from_san(&board, "O-O").unwrap(),
+        ChessMove::new(Square::E1, Square::G1, None)
+    );
+
+    let board = Board::default().make_move_new(ChessMove::new(Square::E2, Square::E4, None));
+    assert_eq!(
+        ChessMove::from_san(&board, "♞f6").unwrap(),
+        ChessMove::new(Square::G8, Square::F6, None)
+    );
+    assert_eq!(
+        ChessMove::from_san(&board, "♟e5").unwrap(),
+        ChessMove::new(Square::E7, Square::E5, None)
+    );
+    assert!(ChessMove::from_san(&board, "♛f6").is_err());
+
+    let board = Board::from_str("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        ChessMove::from_san(&board, "b8=♕").unwrap(),
+        ChessMove::new(Square::B7, Square::B8, Some(Piece::Queen))
+    );
+}
+
+#[test]
+fn test_castle_constructors() {
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    assert!(moves.contains(&ChessMove::castle_kingside(Color::White)));
+    assert!(moves.contains(&ChessMove::castle_queenside(Color::White)));
+
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+    let moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    assert!(moves.contains(&ChessMove::castle_kingside(Color::Black)));
+    assert!(moves.contains(&ChessMove::castle_queenside(Color::Black)));
+    assert_eq!(
+        board.san(ChessMove::castle_queenside(Color::Black)),
+        "O-O-O"
+    );
+}
+
+#[test]
+fn test_uci_line_round_trip() {
+    let board = Board::default();
+    let pv: Vec<ChessMove> = ["e4", "c5", "Nf3", "d6", "d4", "cxd4", "Nxd4", "Nf6"]
+        .iter()
+        .scan(board, |board, san| {
+            let m = ChessMove::from_san(board, san).unwrap();
+            *board = board.make_move_new(m);
+            Some(m)
+        })
+        .collect();
+
+    let line = uci_line(&pv);
+    assert_eq!(line, "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6");
+    assert_eq!(parse_uci_line(&line).unwrap(), pv);
+
+    assert_eq!(uci_line(&[]), "");
+    assert_eq!(parse_uci_line("  ").unwrap(), vec![]);
+    assert!(parse_uci_line("e2e4 e7e5e6").is_err());
+    assert!(matches!(
+        parse_uci_line("e7e8k"),
+        Err(Error::InvalidUciMove)
+    ));
+}
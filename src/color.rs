@@ -2,6 +2,8 @@ use crate::rank::Rank;
 use std::ops::Not;
 
 /// Represent a color.
+///
+/// The numeric values are stable, and safe to rely on across FFI: `White` is 0 and `Black` is 1.
 #[derive(PartialOrd, PartialEq, Eq, Copy, Clone, Debug, Hash)]
 pub enum Color {
     White,
@@ -14,7 +16,7 @@ pub const NUM_COLORS: usize = 2;
 pub const ALL_COLORS: [Color; NUM_COLORS] = [Color::White, Color::Black];
 
 impl Color {
-    /// Convert the `Color` to a `usize` for table lookups.
+    /// Convert the `Color` to a `usize` for table lookups.  `White` is 0 and `Black` is 1.
     #[inline]
     pub fn to_index(&self) -> usize {
         *self as usize
@@ -59,6 +61,19 @@ impl Color {
         }
     }
 
+    /// Get the other color.  This is the same as `!color`.
+    ///
+    /// ```
+    /// use chess::Color;
+    ///
+    /// assert_eq!(Color::White.opponent(), Color::Black);
+    /// assert_eq!(Color::Black.opponent(), Color::White);
+    /// ```
+    #[inline]
+    pub fn opponent(self) -> Color {
+        !self
+    }
+
     /// Convert a `Color` to my seventh rank, which represents the rank before pawn promotion.
     #[inline]
     pub fn to_seventh_rank(&self) -> Rank {
@@ -73,6 +88,13 @@ impl Not for Color {
     type Output = Color;
 
     /// Get the other color.
+    ///
+    /// ```
+    /// use chess::Color;
+    ///
+    /// assert_eq!(!Color::White, Color::Black);
+    /// assert_eq!(!Color::Black, Color::White);
+    /// ```
     #[inline]
     fn not(self) -> Color {
         if self == Color::White {
@@ -82,3 +104,9 @@ impl Not for Color {
         }
     }
 }
+
+#[test]
+fn color_layout_is_stable() {
+    assert_eq!(Color::White.to_index(), 0);
+    assert_eq!(Color::Black.to_index(), 1);
+}
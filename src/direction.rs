@@ -0,0 +1,79 @@
+/// Describe a compass direction on a chess board.  North is towards the eighth rank, and east is
+/// towards the h file.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Debug, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+/// How many directions are there?
+pub const NUM_DIRECTIONS: usize = 8;
+
+/// Enumerate all directions
+pub const ALL_DIRECTIONS: [Direction; NUM_DIRECTIONS] = [
+    Direction::North,
+    Direction::South,
+    Direction::East,
+    Direction::West,
+    Direction::NorthEast,
+    Direction::NorthWest,
+    Direction::SouthEast,
+    Direction::SouthWest,
+];
+
+impl Direction {
+    /// How far one step in this direction moves, as a `(file, rank)` delta.  These can be passed
+    /// straight to `Square::offset`.
+    ///
+    /// ```
+    /// use chess::{Direction, Square};
+    ///
+    /// assert_eq!(Direction::NorthEast.offset(), (1, 1));
+    ///
+    /// let (file, rank) = Direction::West.offset();
+    /// assert_eq!(Square::E4.offset(file, rank), Some(Square::D4));
+    /// ```
+    #[inline]
+    pub fn offset(&self) -> (i8, i8) {
+        match *self {
+            Direction::North => (0, 1),
+            Direction::South => (0, -1),
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::NorthWest => (-1, 1),
+            Direction::SouthEast => (1, -1),
+            Direction::SouthWest => (-1, -1),
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::square::Square;
+
+#[test]
+fn walk_north_east() {
+    let mut path = vec![Square::A1];
+    while let Some(sq) = path[path.len() - 1].step(Direction::NorthEast) {
+        path.push(sq);
+    }
+
+    assert_eq!(path.len(), 8);
+    assert_eq!(path[7], Square::H8);
+    assert_eq!(path[3], Square::D4);
+}
+
+#[test]
+fn opposite_directions_cancel() {
+    for dir in ALL_DIRECTIONS.iter() {
+        let (file, rank) = dir.offset();
+        let back = Square::D4.offset(file, rank).unwrap().offset(-file, -rank);
+        assert_eq!(back, Some(Square::D4));
+    }
+}
@@ -1,35 +1,125 @@
-use failure::Fail;
+use std::error;
+use std::fmt;
 
 /// Sometimes, bad stuff happens.
-#[derive(Clone, Debug, Fail)]
+#[derive(Clone, Debug)]
 pub enum Error {
     /// The FEN string is invalid
-    #[fail(display = "Invalid FEN string: {}", fen)]
     InvalidFen { fen: String },
 
     /// The board created from BoardBuilder was found to be invalid
-    #[fail(
-        display = "The board specified did not pass sanity checks.  Are you sure the kings exist and the side to move cannot capture the opposing king?"
-    )]
     InvalidBoard,
 
     /// An attempt was made to create a square from an invalid string
-    #[fail(display = "The string specified does not contain a valid algebraic notation square")]
     InvalidSquare,
 
     /// An attempt was made to create a move from an invalid SAN string
-    #[fail(display = "The string specified does not contain a valid SAN notation move")]
     InvalidSanMove,
 
     /// An atempt was made to create a move from an invalid UCI string
-    #[fail(display = "The string specified does not contain a valid UCI notation move")]
     InvalidUciMove,
 
     /// An attempt was made to convert a string not equal to "1"-"8" to a rank
-    #[fail(display = "The string specified does not contain a valid rank")]
     InvalidRank,
 
     /// An attempt was made to convert a string not equal to "a"-"h" to a file
-    #[fail(display = "The string specified does not contain a valid file")]
     InvalidFile,
+
+    /// The PGN string is malformed (for example, an unterminated tag or comment)
+    InvalidPgn,
+
+    /// A move in a sequence of SAN moves was invalid or illegal
+    InvalidSanSequence { index: usize, san: String },
+
+    /// An attempt was made to make a move that is not legal in the position
+    IllegalMove,
+
+    /// The castle rights can't be written in standard FEN, because a king or castling rook is
+    /// not on its standard square
+    NonStandardCastling,
+
+    /// The bytes specified are not a board written by `Board::to_bytes`
+    InvalidBoardBytes,
+
+    /// An attempt was made to make a move with the wrong promotion: to something other than a
+    /// knight, bishop, rook or queen, to nothing on the last rank, or on a move that can't promote
+    InvalidPromotion,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidFen { ref fen } => write!(f, "Invalid FEN string: {}", fen),
+            Error::InvalidBoard => write!(
+                f,
+                "The board specified did not pass sanity checks.  Are you sure the kings exist and the side to move cannot capture the opposing king?"
+            ),
+            Error::InvalidSquare => write!(
+                f,
+                "The string specified does not contain a valid algebraic notation square"
+            ),
+            Error::InvalidSanMove => write!(
+                f,
+                "The string specified does not contain a valid SAN notation move"
+            ),
+            Error::InvalidUciMove => write!(
+                f,
+                "The string specified does not contain a valid UCI notation move"
+            ),
+            Error::InvalidRank => write!(f, "The string specified does not contain a valid rank"),
+            Error::InvalidFile => write!(f, "The string specified does not contain a valid file"),
+            Error::InvalidPgn => write!(f, "The string specified is not a valid PGN game"),
+            Error::InvalidSanSequence { index, ref san } => {
+                write!(f, "Move {} ({}) is not a valid SAN move", index, san)
+            }
+            Error::IllegalMove => write!(f, "The move specified is not legal in this position"),
+            Error::NonStandardCastling => write!(
+                f,
+                "The castle rights cannot be written without naming the rook files"
+            ),
+            Error::InvalidBoardBytes => {
+                write!(f, "The bytes specified do not contain a valid board")
+            }
+            Error::InvalidPromotion => write!(
+                f,
+                "The promotion specified is not valid for this move"
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[test]
+fn format_new_errors() {
+    assert_eq!(
+        Error::IllegalMove.to_string(),
+        "The move specified is not legal in this position"
+    );
+    assert_eq!(
+        Error::InvalidPromotion.to_string(),
+        "The promotion specified is not valid for this move"
+    );
+    assert_eq!(
+        Error::InvalidRank.to_string(),
+        "The string specified does not contain a valid rank"
+    );
+    assert_eq!(
+        Error::InvalidFile.to_string(),
+        "The string specified does not contain a valid file"
+    );
+    assert_eq!(
+        Error::InvalidSanSequence {
+            index: 2,
+            san: "Ke3".to_string()
+        }
+        .to_string(),
+        "Move 2 (Ke3) is not a valid SAN move"
+    );
+
+    let boxed: Box<dyn error::Error> = Box::new(Error::IllegalMove);
+    assert_eq!(
+        boxed.to_string(),
+        "The move specified is not legal in this position"
+    );
 }
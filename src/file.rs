@@ -1,8 +1,11 @@
+use crate::bitboard::BitBoard;
 use crate::error::Error;
 use std::mem::transmute;
 use std::str::FromStr;
 
 /// Describe a file (column) on a chess board
+///
+/// The numeric values are stable, and safe to rely on across FFI: `A` is 0, up to `H`, which is 7.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Debug, Hash)]
 pub enum File {
     A,
@@ -31,12 +34,44 @@ pub const ALL_FILES: [File; NUM_FILES] = [
 ];
 
 impl File {
+    /// Iterate over every file, from `A` to `H`.  Use `.rev()` to go from `H` to `A`.
+    ///
+    /// ```
+    /// use chess::File;
+    ///
+    /// assert_eq!(File::iter().next(), Some(File::A));
+    /// assert_eq!(File::iter().rev().next(), Some(File::H));
+    /// ```
+    #[inline]
+    pub fn iter() -> impl DoubleEndedIterator<Item = File> + ExactSizeIterator {
+        ALL_FILES.iter().copied()
+    }
+
     /// Convert a `usize` into a `File` (the inverse of to_index).  If i > 7, wrap around.
     #[inline]
     pub fn from_index(i: usize) -> File {
         unsafe { transmute((i as u8) & 7) }
     }
 
+    /// Convert a `usize` into a `File`, returning an error instead of wrapping if i > 7.
+    ///
+    /// ```
+    /// use chess::File;
+    ///
+    /// assert_eq!(File::checked_from_index(0).unwrap(), File::A);
+    /// assert_eq!(File::checked_from_index(7).unwrap(), File::H);
+    /// assert!(File::checked_from_index(8).is_err());
+    /// assert!(File::checked_from_index(usize::max_value()).is_err());
+    /// ```
+    #[inline]
+    pub fn checked_from_index(i: usize) -> Result<File, Error> {
+        if i < NUM_FILES {
+            Ok(File::from_index(i))
+        } else {
+            Err(Error::InvalidFile)
+        }
+    }
+
     /// Go one file to the left.  If impossible, wrap around.
     #[inline]
     pub fn left(&self) -> File {
@@ -54,6 +89,47 @@ impl File {
     pub fn to_index(&self) -> usize {
         *self as usize
     }
+
+    /// Convert this `File` into a `u8` from 0 (`A`) to 7 (`H`) inclusive.
+    ///
+    /// ```
+    /// use chess::File;
+    ///
+    /// assert_eq!(File::C.to_u8(), 2);
+    /// ```
+    #[inline]
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Every square on this `File`, the same as `get_file(self)`.
+    ///
+    /// ```
+    /// use chess::{get_file, File};
+    ///
+    /// for file in File::iter() {
+    ///     assert_eq!(file.bitboard().popcnt(), 8);
+    ///     assert_eq!(file.bitboard(), get_file(file));
+    /// }
+    /// ```
+    #[inline]
+    pub fn bitboard(self) -> BitBoard {
+        BitBoard::from_file(self)
+    }
+
+    /// How many files apart are these two files?
+    ///
+    /// ```
+    /// use chess::File;
+    ///
+    /// assert_eq!(File::A.distance(File::H), 7);
+    /// assert_eq!(File::E.distance(File::C), 2);
+    /// assert_eq!(File::D.distance(File::D), 0);
+    /// ```
+    #[inline]
+    pub fn distance(self, other: File) -> u8 {
+        (self.to_u8() as i8 - other.to_u8() as i8).unsigned_abs()
+    }
 }
 
 impl FromStr for File {
@@ -76,3 +152,15 @@ impl FromStr for File {
         }
     }
 }
+
+#[test]
+fn file_layout_is_stable() {
+    assert_eq!(File::A.to_u8(), 0);
+    assert_eq!(File::B.to_u8(), 1);
+    assert_eq!(File::C.to_u8(), 2);
+    assert_eq!(File::D.to_u8(), 3);
+    assert_eq!(File::E.to_u8(), 4);
+    assert_eq!(File::F.to_u8(), 5);
+    assert_eq!(File::G.to_u8(), 6);
+    assert_eq!(File::H.to_u8(), 7);
+}
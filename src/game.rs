@@ -1,10 +1,13 @@
 use crate::board::{Board, BoardStatus};
+use crate::board_builder::BoardBuilder;
 use crate::chess_move::ChessMove;
 use crate::color::Color;
 use crate::error::Error;
 use crate::movegen::MoveGen;
-use crate::piece::Piece;
+use crate::pgn::{format_clock, parse_clock, tokenize, write_tag, Headers, PgnToken};
+use std::convert::TryFrom;
 use std::str::FromStr;
+use std::time::Duration;
 
 /// Contains all actions supported within the game
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Eq)]
@@ -28,6 +31,66 @@ pub enum GameResult {
     DrawDeclared,
 }
 
+/// Who won a game, without saying how.  This is what a PGN result ("1-0", "0-1" or "1/2-1/2")
+/// records.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum Outcome {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl Outcome {
+    /// Convert a PGN result token into an `Outcome`.  Returns `None` for "*" (game in progress)
+    /// or anything unrecognized.
+    fn from_pgn(token: &str) -> Option<Outcome> {
+        match token {
+            "1-0" => Some(Outcome::WhiteWins),
+            "0-1" => Some(Outcome::BlackWins),
+            "1/2-1/2" => Some(Outcome::Draw),
+            _ => None,
+        }
+    }
+
+    /// The PGN result token for this `Outcome`.
+    fn to_pgn(self) -> &'static str {
+        match self {
+            Outcome::WhiteWins => "1-0",
+            Outcome::BlackWins => "0-1",
+            Outcome::Draw => "1/2-1/2",
+        }
+    }
+}
+
+impl GameResult {
+    /// Who won?
+    fn outcome(&self) -> Outcome {
+        match *self {
+            GameResult::WhiteCheckmates | GameResult::BlackResigns => Outcome::WhiteWins,
+            GameResult::BlackCheckmates | GameResult::WhiteResigns => Outcome::BlackWins,
+            GameResult::Stalemate | GameResult::DrawAccepted | GameResult::DrawDeclared => {
+                Outcome::Draw
+            }
+        }
+    }
+}
+
+/// Which draw claims are available to the player to move?
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Hash)]
+pub struct DrawClaims {
+    /// The current position has appeared at least three times.
+    pub threefold_repetition: bool,
+    /// There have been at least 50 moves by each side without a pawn move or capture.
+    pub fifty_moves: bool,
+}
+
+impl DrawClaims {
+    /// Can any draw be claimed?
+    pub fn any(&self) -> bool {
+        self.threefold_repetition || self.fifty_moves
+    }
+}
+
 /// For UI/UCI Servers, store a game object which allows you to determine
 /// draw by 3 fold repitition, draw offers, resignations, and moves.
 ///
@@ -37,6 +100,21 @@ pub enum GameResult {
 pub struct Game {
     start_pos: Board,
     moves: Vec<Action>,
+    start_fullmove_number: u32,
+    headers: Headers,
+    // The clock time recorded after each action, so always as long as `moves`.  Actions are only
+    // added through `push_action`, which keeps the two in step.
+    clocks: Vec<Option<Duration>>,
+    // Where the last irreversible move left the game: the position, the index into `moves` just
+    // after it, and the number of moves made up to it.  Nothing before it can repeat.
+    window_start: Board,
+    window_index: usize,
+    window_ply: usize,
+    // For a game made by `fork_at_current`: the positions from before `start_pos` that can still
+    // come up again, oldest first, and the number of moves since the last pawn move or capture at
+    // `start_pos`.  Empty and 0 for any other game.
+    prior_positions: Vec<Board>,
+    prior_halfmove_clock: usize,
 }
 
 impl Game {
@@ -52,6 +130,14 @@ impl Game {
         Game {
             start_pos: Board::default(),
             moves: vec![],
+            start_fullmove_number: 1,
+            headers: Headers::default(),
+            clocks: vec![],
+            window_start: Board::default(),
+            window_index: 0,
+            window_ply: 0,
+            prior_positions: vec![],
+            prior_halfmove_clock: 0,
         }
     }
 
@@ -67,6 +153,14 @@ impl Game {
         Game {
             start_pos: board,
             moves: vec![],
+            start_fullmove_number: 1,
+            headers: Headers::default(),
+            clocks: vec![],
+            window_start: board,
+            window_index: 0,
+            window_ply: 0,
+            prior_positions: vec![],
+            prior_halfmove_clock: 0,
         }
     }
 
@@ -122,6 +216,351 @@ impl Game {
         }
     }
 
+    /// Load a `Game` from a PGN string.
+    ///
+    /// Only the main line is kept: comments, numeric annotation glyphs, and variations are
+    /// skipped.  A `FEN` tag sets the starting position.  Parsing stops at the first game
+    /// termination marker, so only the first game in the string is read.
+    ///
+    /// The result recorded in the PGN is kept as-is, even if it disagrees with the moves (for
+    /// example, when a game was adjudicated or the movetext is truncated).  See `declared_result`
+    /// and `computed_result`.
+    ///
+    /// ```
+    /// use chess::{Game, Outcome};
+    ///
+    /// let pgn = "[Event \"Scholar's Mate\"]
+    /// [Result \"1-0\"]
+    ///
+    /// 1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6?? 4. Qxf7# 1-0";
+    ///
+    /// let game = Game::from_pgn(pgn).expect("Valid PGN");
+    /// assert_eq!(game.declared_result(), Some(Outcome::WhiteWins));
+    /// assert_eq!(game.computed_result(), Some(Outcome::WhiteWins));
+    /// ```
+    pub fn from_pgn(pgn: &str) -> Result<Game, Error> {
+        let mut start_pos = Board::default();
+        let mut start_fullmove_number = 1;
+        let mut headers = Headers::default();
+        let mut has_result_tag = false;
+        let mut moves = vec![];
+        let mut clocks = vec![];
+        let mut depth = 0;
+
+        let mut board = start_pos;
+        let mut window_start = start_pos;
+        let mut window_len = 0;
+        for token in tokenize(pgn)? {
+            match token {
+                PgnToken::Tag(name, value) => {
+                    if !moves.is_empty() {
+                        // The tags of the next game
+                        break;
+                    }
+                    match name.as_str() {
+                        "FEN" => {
+                            let builder = BoardBuilder::from_str(&value)?;
+                            start_pos = Board::try_from(&builder)?;
+                            start_fullmove_number = builder.get_fullmove_number();
+                            board = start_pos;
+                            window_start = start_pos;
+                        }
+                        // Written back out by `to_pgn` whenever it is needed
+                        "SetUp" => {}
+                        _ => {
+                            has_result_tag |= name == "Result";
+                            headers.set(name, value);
+                        }
+                    }
+                }
+                PgnToken::VariationStart => depth += 1,
+                PgnToken::VariationEnd => depth -= 1,
+                PgnToken::Symbol(ref symbol) if depth == 0 => {
+                    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&symbol.as_str()) {
+                        if !has_result_tag {
+                            headers.result = symbol.clone();
+                        }
+                        break;
+                    }
+
+                    // Strip move numbers ("1.", "1...", or "1.e4"), then annotations and check
+                    // markers, which `from_san` doesn't need.  Digits only make a move number
+                    // when a '.' follows them, so "0-0" is left alone.
+                    let unnumbered = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+                    let san = if unnumbered.starts_with('.') {
+                        unnumbered.trim_start_matches('.')
+                    } else {
+                        symbol.as_str()
+                    };
+                    let san = san.trim_end_matches(&['!', '?', '+', '#'][..]);
+                    if san.is_empty() {
+                        continue;
+                    }
+
+                    let m = ChessMove::from_san(&board, san)?;
+                    let after = board.make_move_new(m);
+                    moves.push(Action::MakeMove(m));
+                    clocks.push(None);
+                    if is_irreversible(&board, m, &after) {
+                        window_start = after;
+                        window_len = moves.len();
+                    }
+                    board = after;
+                }
+                PgnToken::Comment(ref comment) if depth == 0 && !clocks.is_empty() => {
+                    let last = clocks.len() - 1;
+                    clocks[last] = parse_clock(comment).or(clocks[last]);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Game {
+            start_pos,
+            moves,
+            start_fullmove_number,
+            headers,
+            clocks,
+            window_start,
+            window_index: window_len,
+            window_ply: window_len,
+            prior_positions: vec![],
+            prior_halfmove_clock: 0,
+        })
+    }
+
+    /// The PGN tags of this game.  For a game loaded with `from_pgn`, these are the tags it was
+    /// loaded with, except `FEN` and `SetUp`, which are kept as the starting position.
+    ///
+    /// ```
+    /// use chess::Game;
+    ///
+    /// let game = Game::from_pgn("[White \"Anderssen, Adolf\"]\n\n1. e4 *").expect("Valid PGN");
+    /// assert_eq!(game.headers().white, "Anderssen, Adolf");
+    /// assert_eq!(game.headers().black, "?");
+    /// ```
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    /// The time left on the clock of the player who made the action at `index` in `actions()`,
+    /// right after they made it.  For games loaded with `from_pgn`, this comes from `[%clk ...]`
+    /// comments.  This is `None` when no time was recorded.
+    ///
+    /// ```
+    /// use chess::Game;
+    /// use std::time::Duration;
+    ///
+    /// let game = Game::from_pgn("1. e4 {[%clk 0:03:00]} e5 *").expect("Valid PGN");
+    /// assert_eq!(game.clock(0), Some(Duration::from_secs(180)));
+    /// assert_eq!(game.clock(1), None);
+    /// ```
+    pub fn clock(&self, index: usize) -> Option<Duration> {
+        self.clocks.get(index).cloned().flatten()
+    }
+
+    /// Record the time left on the clock after the action at `index` in `actions()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no action at `index`.
+    ///
+    /// ```
+    /// use chess::{ChessMove, Game, Square};
+    /// use std::time::Duration;
+    ///
+    /// let mut game = Game::new();
+    /// game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    /// game.set_clock(0, Some(Duration::from_secs(59)));
+    ///
+    /// assert!(game.to_pgn().ends_with("1. e4 {[%clk 0:00:59]} *\n"));
+    /// ```
+    pub fn set_clock(&mut self, index: usize, clock: Option<Duration>) {
+        assert!(index < self.moves.len(), "no action at index {}", index);
+        self.clocks[index] = clock;
+    }
+
+    /// Add an action to the game, with no clock time recorded for it yet.
+    fn push_action(&mut self, action: Action) {
+        self.moves.push(action);
+        self.clocks.push(None);
+    }
+
+    /// Replace the PGN tags of this game.
+    ///
+    /// ```
+    /// use chess::{Game, Headers, Outcome};
+    ///
+    /// let mut game = Game::new();
+    /// game.set_headers(Headers {
+    ///     event: "Casual Game".to_string(),
+    ///     result: "1/2-1/2".to_string(),
+    ///     ..Headers::default()
+    /// });
+    /// assert_eq!(game.headers().event, "Casual Game");
+    /// assert_eq!(game.declared_result(), Some(Outcome::Draw));
+    /// ```
+    pub fn set_headers(&mut self, headers: Headers) {
+        self.headers = headers;
+    }
+
+    /// Write this game as PGN.
+    ///
+    /// The seven tag roster comes first, in the standard order, followed by any other tags and,
+    /// if the game did not start from the initial position, `SetUp` and `FEN`.  Only moves are
+    /// written; draw offers and resignations only show up in the result.  If the `Result` tag
+    /// is "*" but the game is over, the movetext ends with the actual result.  Clock times are
+    /// written as `[%clk ...]` comments after their moves.
+    ///
+    /// ```
+    /// use chess::Game;
+    ///
+    /// let game = Game::from_pgn("1. f3 e5 2. g4 Qh4# 0-1").expect("Valid PGN");
+    /// assert!(game.to_pgn().starts_with("[Event \"?\"]\n"));
+    /// assert!(game.to_pgn().ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+    /// ```
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+
+        // The Result tag has to match the termination marker at the end of the movetext
+        let result = self.pgn_result();
+        for (name, value) in self.headers.roster().iter() {
+            let value = if *name == "Result" { result } else { value };
+            write_tag(&mut pgn, name, value);
+        }
+        for (name, value) in self.headers.extra.iter() {
+            write_tag(&mut pgn, name, value);
+        }
+        if self.start_pos != Board::default() || self.start_fullmove_number != 1 {
+            // `Board` doesn't keep the move counters, so put the fullmove number back
+            let fen = self.start_pos.to_string();
+            let fields: Vec<&str> = fen.split(' ').take(4).collect();
+            let fen = format!("{} 0 {}", fields.join(" "), self.start_fullmove_number);
+            write_tag(&mut pgn, "SetUp", "1");
+            write_tag(&mut pgn, "FEN", &fen);
+        }
+
+        pgn.push('\n');
+        pgn.push_str(&self.to_movetext());
+        pgn.push('\n');
+        pgn
+    }
+
+    /// The numbered SAN moves of this game, followed by the result, wrapped to fit in 80 columns.
+    /// This is the part of `to_pgn` after the tags, for embedding a game somewhere the tags
+    /// aren't wanted.
+    ///
+    /// ```
+    /// use chess::Game;
+    ///
+    /// let game = Game::from_pgn("[White \"Me\"]\n\n1. f3 e5 2. g4 Qh4# 0-1").expect("Valid PGN");
+    /// assert_eq!(game.to_movetext(), "1. f3 e5 2. g4 Qh4# 0-1");
+    /// ```
+    pub fn to_movetext(&self) -> String {
+        let result = self.pgn_result();
+
+        let mut words = vec![];
+        let mut board = self.start_pos;
+        let mut fullmove_number = self.start_fullmove_number;
+        for (index, action) in self.moves.iter().enumerate() {
+            if let Action::MakeMove(m) = *action {
+                if board.side_to_move() == Color::White {
+                    words.push(format!("{}.", fullmove_number));
+                } else if words.is_empty() {
+                    words.push(format!("{}...", fullmove_number));
+                }
+                words.push(board.san_with_suffix(m, true));
+                if let Some(clock) = self.clock(index) {
+                    words.push(format_clock(clock));
+                }
+                if board.side_to_move() == Color::Black {
+                    fullmove_number += 1;
+                }
+                board = board.make_move_new(m);
+            }
+        }
+        words.push(result.to_string());
+
+        let mut movetext = String::new();
+        let mut line_length = 0;
+        for word in words {
+            if line_length > 0 && line_length + 1 + word.len() > 80 {
+                movetext.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                movetext.push(' ');
+                line_length += 1;
+            }
+            line_length += word.len();
+            movetext.push_str(&word);
+        }
+        movetext
+    }
+
+    /// The result to write in PGN: the `Result` header, unless that is "*" and the moves and
+    /// actions have ended the game.
+    fn pgn_result(&self) -> &str {
+        match self.computed_result() {
+            Some(outcome) if self.headers.result == "*" => outcome.to_pgn(),
+            _ => self.headers.result.as_str(),
+        }
+    }
+
+    /// The result this game was loaded with, from the PGN `Result` tag (or the termination
+    /// marker at the end of the movetext, if there is no tag).  This is `None` for games that
+    /// were not loaded from PGN, or whose result is "*".
+    ///
+    /// ```
+    /// use chess::Game;
+    ///
+    /// assert_eq!(Game::new().declared_result(), None);
+    /// ```
+    pub fn declared_result(&self) -> Option<Outcome> {
+        Outcome::from_pgn(&self.headers.result)
+    }
+
+    /// The result of this game, according to its moves and actions.  This is `result()`, without
+    /// the detail of how the game ended.
+    ///
+    /// ```
+    /// use chess::{Color, Game, Outcome};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.computed_result(), None);
+    ///
+    /// game.resign(Color::Black);
+    /// assert_eq!(game.computed_result(), Some(Outcome::WhiteWins));
+    /// ```
+    pub fn computed_result(&self) -> Option<Outcome> {
+        self.result().map(|r| r.outcome())
+    }
+
+    /// Is the game over?  It is when `result()` is set (checkmate, stalemate, resignation, or a
+    /// draw that was agreed or declared), and also when the game is drawn without anyone having
+    /// to claim it: in a dead position (see `Board::is_dead_position`), when the same position
+    /// has come up five times, or after 75 moves by each side without a pawn move or capture.
+    ///
+    /// A draw by threefold repetition or the 50-move rule doesn't end the game until it is
+    /// declared.
+    ///
+    /// ```
+    /// use chess::{Color, Game};
+    /// use std::str::FromStr;
+    ///
+    /// let mut game = Game::new();
+    /// assert!(!game.is_over());
+    ///
+    /// game.resign(Color::White);
+    /// assert!(game.is_over());
+    ///
+    /// // Nobody can mate with only the kings left
+    /// let game = Game::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").expect("Valid FEN");
+    /// assert!(game.is_over());
+    /// ```
+    pub fn is_over(&self) -> bool {
+        self.result().is_some() || self.automatic_draw()
+    }
+
     /// Create a new `Game` object from an FEN string.
     ///
     /// ```
@@ -170,6 +609,139 @@ impl Game {
         copy
     }
 
+    /// Get the position after the first `ply` half-moves of this game, where 0 is the starting
+    /// position.  Unlike `ply()`, this doesn't count moves from before a starting FEN.  Returns
+    /// `None` if fewer than `ply` moves have been made.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, Game, Square};
+    ///
+    /// let mut game = Game::new();
+    /// game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    /// game.make_move(ChessMove::new(Square::E7, Square::E5, None));
+    ///
+    /// assert_eq!(game.position_at_ply(0), Some(Board::default()));
+    /// assert_eq!(game.position_at_ply(2), Some(game.current_position()));
+    /// assert_eq!(game.position_at_ply(3), None);
+    /// ```
+    pub fn position_at_ply(&self, ply: usize) -> Option<Board> {
+        // Start from the last irreversible move when we can, rather than replaying everything
+        let (mut board, start, skip) = if ply >= self.window_ply {
+            (self.window_start, self.window_ply, self.window_index)
+        } else {
+            (self.start_pos, 0, 0)
+        };
+
+        let mut made = start;
+        for x in self.moves[skip..].iter() {
+            if made == ply {
+                break;
+            }
+            if let Action::MakeMove(m) = *x {
+                board = board.make_move_new(m);
+                made += 1;
+            }
+        }
+
+        if made == ply {
+            Some(board)
+        } else {
+            None
+        }
+    }
+
+    /// Get a `GameCursor` for stepping through the moves of this game, starting from its
+    /// starting position.
+    ///
+    /// ```
+    /// use chess::{Board, Game};
+    ///
+    /// let game = Game::from_pgn("1. e4 e5 2. Nf3 *").expect("Valid PGN");
+    /// let mut cursor = game.cursor();
+    /// assert_eq!(cursor.current_board(), Board::default());
+    ///
+    /// cursor.goto_end();
+    /// assert_eq!(cursor.current_board(), game.current_position());
+    /// ```
+    pub fn cursor(&self) -> GameCursor {
+        GameCursor::new(self)
+    }
+
+    /// Start a new `Game` from the current position, for analysing a line without touching this
+    /// one.  The new game has no moves, default headers, and carries on the fullmove number.
+    /// Positions since the last irreversible move still count towards repetitions in the new
+    /// game, and moves from before the fork still count towards the 50 and 75-move rules.
+    ///
+    /// ```
+    /// use chess::Game;
+    ///
+    /// let game = Game::from_pgn("1. e4 e5 2. Nf3 *").expect("Valid PGN");
+    /// let fork = game.fork_at_current();
+    ///
+    /// assert_eq!(fork.current_position(), game.current_position());
+    /// assert!(fork.actions().is_empty());
+    /// assert_eq!(fork.ply(), game.ply());
+    /// ```
+    pub fn fork_at_current(&self) -> Game {
+        // Only a fork with no irreversible move since its start has earlier positions to pass on
+        let mut prior_positions = if self.window_index == 0 {
+            self.prior_positions.clone()
+        } else {
+            vec![]
+        };
+        let mut board = self.window_start;
+        for x in self.moves[self.window_index..].iter() {
+            if let Action::MakeMove(m) = *x {
+                prior_positions.push(board);
+                board = board.make_move_new(m);
+            }
+        }
+
+        Game {
+            start_fullmove_number: self.fullmove_number(),
+            prior_positions,
+            prior_halfmove_clock: self.halfmove_clock(),
+            ..Game::new_with_board(board)
+        }
+    }
+
+    /// A hash of every position in this game, in order, for finding duplicate games quickly.
+    /// Like `==` on games, this ignores the headers, clock times, and anything that isn't a move.
+    /// Games that reach the same position by different move orders hash differently.
+    ///
+    /// The hash is built from `Board::get_hash`, so it is the same from one run to the next.
+    ///
+    /// ```
+    /// use chess::Game;
+    ///
+    /// let a = Game::from_pgn("[Event \"A\"]\n\n1. e4 e5 2. Nf3 *").expect("Valid PGN");
+    /// let b = Game::from_pgn("[Event \"B\"]\n\n1. e4 e5 2. Nf3 1-0").expect("Valid PGN");
+    /// let c = Game::from_pgn("1. Nf3 e5 2. e4 *").expect("Valid PGN");
+    ///
+    /// assert_eq!(a.move_hash(), b.move_hash());
+    /// assert_ne!(a.move_hash(), c.move_hash());
+    /// ```
+    pub fn move_hash(&self) -> u64 {
+        // FNV-1a style mixing, so the order of the positions matters
+        let mix = |hash: u64, key: u64| (hash ^ key).wrapping_mul(0x0000_0100_0000_01b3);
+
+        let mut board = self.start_pos;
+        let mut hash = mix(0xcbf2_9ce4_8422_2325, board.get_hash());
+        for m in self.chess_moves() {
+            board = board.make_move_new(m);
+            hash = mix(hash, board.get_hash());
+        }
+        hash
+    }
+
+    /// Every move made in this game, leaving out the other actions.
+    fn chess_moves(&self) -> impl Iterator<Item = ChessMove> + '_ {
+        self.moves.iter().filter_map(|x| match *x {
+            Action::MakeMove(m) => Some(m),
+            _ => None,
+        })
+    }
+
     /// Determine if a player can legally declare a draw by 3-fold repetition or 50-move rule.
     ///
     /// ```
@@ -202,59 +774,74 @@ impl Game {
             return false;
         }
 
-        let mut legal_moves_per_turn: Vec<(u64, Vec<ChessMove>)> = vec![];
-
-        let mut board = self.start_pos;
-        let mut reversible_moves = 0;
-
-        // Loop over each move, counting the reversible_moves for draw by 50 move rule,
-        // and filling a list of legal_moves_per_turn list for 3-fold repitition
-        legal_moves_per_turn.push((board.get_hash(), MoveGen::new_legal(&board).collect()));
-        for x in self.moves.iter() {
-            match *x {
-                Action::MakeMove(m) => {
-                    let white_castle_rights = board.castle_rights(Color::White);
-                    let black_castle_rights = board.castle_rights(Color::Black);
-                    if board.piece_on(m.get_source()) == Some(Piece::Pawn) {
-                        reversible_moves = 0;
-                        legal_moves_per_turn.clear();
-                    } else if board.piece_on(m.get_dest()).is_some() {
-                        reversible_moves = 0;
-                        legal_moves_per_turn.clear();
-                    } else {
-                        reversible_moves += 1;
-                    }
-                    board = board.make_move_new(m);
+        self.draw_claims().any()
+    }
 
-                    if board.castle_rights(Color::White) != white_castle_rights
-                        || board.castle_rights(Color::Black) != black_castle_rights
-                    {
-                        reversible_moves = 0;
-                        legal_moves_per_turn.clear();
-                    }
-                    legal_moves_per_turn
-                        .push((board.get_hash(), MoveGen::new_legal(&board).collect()));
-                }
-                _ => {}
-            }
+    /// Determine which draws (3-fold repetition or the 50-move rule) could be claimed in the
+    /// current position.
+    fn draw_claims(&self) -> DrawClaims {
+        let (repetitions, reversible_moves) = self.repetitions_and_reversible_moves();
+        DrawClaims {
+            threefold_repetition: repetitions >= 3,
+            fifty_moves: reversible_moves >= 100,
         }
+    }
 
-        if reversible_moves >= 100 {
+    /// Has the game been drawn without anyone claiming it: by a dead position, the fivefold
+    /// repetition rule, or the 75-move rule?
+    fn automatic_draw(&self) -> bool {
+        if self.current_position().is_dead_position() {
             return true;
         }
+        let (repetitions, _) = self.repetitions_and_reversible_moves();
+        repetitions >= 5 || self.halfmove_clock() >= 150
+    }
 
-        // Detect possible draw by 3 fold repitition
-        let last_moves = legal_moves_per_turn[legal_moves_per_turn.len() - 1].clone();
+    /// How many moves have been made since the last pawn move or capture?  Unlike the moves
+    /// counted by `repetitions_and_reversible_moves`, giving up castle rights doesn't restart
+    /// this count, as the 75-move rule only restarts on a pawn move or a capture.
+    fn halfmove_clock(&self) -> usize {
+        let mut board = self.start_pos;
+        let mut clock = self.prior_halfmove_clock;
+        for m in self.chess_moves() {
+            clock = if board.is_reversible(m) { clock + 1 } else { 0 };
+            board = board.make_move_new(m);
+        }
+        clock
+    }
 
-        for i in 1..(legal_moves_per_turn.len() - 1) {
-            for j in 0..i {
-                if legal_moves_per_turn[i] == last_moves && legal_moves_per_turn[j] == last_moves {
-                    return true;
-                }
+    /// How many times has the current position come up, counting this time, and how many moves
+    /// have been made since the last irreversible one?
+    fn repetitions_and_reversible_moves(&self) -> (usize, usize) {
+        let mut legal_moves_per_turn: Vec<(u64, Vec<ChessMove>)> = vec![];
+
+        // Only the positions since the last irreversible move can repeat, and every move since
+        // then counts towards the 50-move rule
+        let mut board = self.window_start;
+        let mut reversible_moves = 0;
+
+        // Positions from before a fork count too, if nothing irreversible has happened since
+        if self.window_index == 0 {
+            for prior in self.prior_positions.iter() {
+                reversible_moves += 1;
+                legal_moves_per_turn.push((prior.get_hash(), MoveGen::new_legal(prior).collect()));
+            }
+        }
+        legal_moves_per_turn.push((board.get_hash(), MoveGen::new_legal(&board).collect()));
+        for x in self.moves[self.window_index..].iter() {
+            if let Action::MakeMove(m) = *x {
+                reversible_moves += 1;
+                board = board.make_move_new(m);
+                legal_moves_per_turn.push((board.get_hash(), MoveGen::new_legal(&board).collect()));
             }
         }
 
-        return false;
+        // Positions only repeat if the same moves are possible, so castle rights and en passant
+        // count too
+        let last = &legal_moves_per_turn[legal_moves_per_turn.len() - 1];
+        let repetitions = legal_moves_per_turn.iter().filter(|x| *x == last).count();
+
+        (repetitions, reversible_moves)
     }
 
     /// Declare a draw by 3-fold repitition or 50-move rule.
@@ -287,7 +874,7 @@ impl Game {
     /// ```
     pub fn declare_draw(&mut self) -> bool {
         if self.can_declare_draw() {
-            self.moves.push(Action::DeclareDraw);
+            self.push_action(Action::DeclareDraw);
             true
         } else {
             false
@@ -309,41 +896,128 @@ impl Game {
         if self.result().is_some() {
             return false;
         }
-        if self.current_position().legal(chess_move) {
-            self.moves.push(Action::MakeMove(chess_move));
+        let board = self.current_position();
+        if board.legal(chess_move) {
+            let after = board.make_move_new(chess_move);
+            self.push_action(Action::MakeMove(chess_move));
+            if is_irreversible(&board, chess_move, &after) {
+                self.window_start = after;
+                self.window_index = self.moves.len();
+                self.window_ply = self.ply() - self.start_ply();
+            }
             true
         } else {
             false
         }
     }
 
-    /// Who's turn is it to move?
+    /// Make a chess move on the board, and report which draws can now be claimed.
+    ///
+    /// Returns `None` if the move was not made, because the game is over or the move is illegal.
     ///
     /// ```
-    /// use chess::{Game, Color};
+    /// use chess::{Game, Square, ChessMove};
     ///
-    /// let game = Game::new();
-    /// assert_eq!(game.side_to_move(), Color::White);
+    /// let b1c3 = ChessMove::new(Square::B1, Square::C3, None);
+    /// let c3b1 = ChessMove::new(Square::C3, Square::B1, None);
+    ///
+    /// let mut game = Game::new();
+    ///
+    /// let claims = game.make_move_checked(b1c3).expect("Legal move");
+    /// assert_eq!(claims.any(), false);
+    ///
+    /// // The knight can't move back before black has moved.
+    /// assert_eq!(game.make_move_checked(c3b1), None);
     /// ```
-    pub fn side_to_move(&self) -> Color {
-        let move_count = self
-            .moves
-            .iter()
-            .filter(|m| match *m {
-                Action::MakeMove(_) => true,
-                _ => false,
-            })
-            .count()
+    pub fn make_move_checked(&mut self, chess_move: ChessMove) -> Option<DrawClaims> {
+        if self.make_move(chess_move) {
+            Some(self.draw_claims())
+        } else {
+            None
+        }
+    }
+
+    /// How many half-moves have been played since the start of the game?  When the game was set
+    /// up from a FEN, this counts the moves before that position as well, using its fullmove
+    /// number and side to move.
+    ///
+    /// ```
+    /// use chess::{ChessMove, Game, Square};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.ply(), 0);
+    ///
+    /// game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    /// assert_eq!(game.ply(), 1);
+    /// ```
+    pub fn ply(&self) -> usize {
+        self.start_ply()
+            + self
+                .moves
+                .iter()
+                .filter(|m| matches!(*m, Action::MakeMove(_)))
+                .count()
+    }
+
+    /// The ply (as counted by `ply()`) just after the last irreversible move: a pawn move, a
+    /// capture, or a move that gives up castle rights.  No position before it can come up again,
+    /// so repetitions are only looked for from here on.  If there hasn't been an irreversible
+    /// move, this is the ply the game started at.
+    ///
+    /// ```
+    /// use chess::{ChessMove, Game, Square};
+    ///
+    /// let mut game = Game::new();
+    /// game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    /// game.make_move(ChessMove::new(Square::G8, Square::F6, None));
+    /// assert_eq!(game.irreversible_ply(), 1);
+    /// ```
+    pub fn irreversible_ply(&self) -> usize {
+        self.start_ply() + self.window_ply
+    }
+
+    /// The ply the game started at, worked out from the starting position.
+    fn start_ply(&self) -> usize {
+        (self.start_fullmove_number as usize - 1) * 2
             + if self.start_pos.side_to_move() == Color::White {
                 0
             } else {
                 1
-            };
+            }
+    }
 
-        if move_count % 2 == 0 {
-            Color::White
-        } else {
+    /// The number of the current full move, as it would appear in a FEN.  This starts at 1, and
+    /// goes up after each move by black.
+    ///
+    /// ```
+    /// use chess::{ChessMove, Game, Square};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.fullmove_number(), 1);
+    ///
+    /// game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    /// assert_eq!(game.fullmove_number(), 1);
+    ///
+    /// game.make_move(ChessMove::new(Square::E7, Square::E5, None));
+    /// assert_eq!(game.fullmove_number(), 2);
+    /// ```
+    pub fn fullmove_number(&self) -> u32 {
+        (self.ply() / 2) as u32 + 1
+    }
+
+    /// Who's turn is it to move?
+    ///
+    /// ```
+    /// use chess::{Game, Color};
+    ///
+    /// let game = Game::new();
+    /// assert_eq!(game.side_to_move(), Color::White);
+    /// ```
+    pub fn side_to_move(&self) -> Color {
+        if self.ply() % 2 == 1 {
             Color::Black
+        } else {
+            Color::White
         }
     }
 
@@ -360,7 +1034,7 @@ impl Game {
         if self.result().is_some() {
             return false;
         }
-        self.moves.push(Action::OfferDraw(color));
+        self.push_action(Action::OfferDraw(color));
         return true;
     }
 
@@ -387,14 +1061,14 @@ impl Game {
             if self.moves[self.moves.len() - 1] == Action::OfferDraw(Color::White)
                 || self.moves[self.moves.len() - 1] == Action::OfferDraw(Color::Black)
             {
-                self.moves.push(Action::AcceptDraw);
+                self.push_action(Action::AcceptDraw);
                 return true;
             }
         }
 
         if self.moves.len() > 1 {
             if self.moves[self.moves.len() - 2] == Action::OfferDraw(!self.side_to_move()) {
-                self.moves.push(Action::AcceptDraw);
+                self.push_action(Action::AcceptDraw);
                 return true;
             }
         }
@@ -414,19 +1088,126 @@ impl Game {
         if self.result().is_some() {
             return false;
         }
-        self.moves.push(Action::Resign(color));
+        self.push_action(Action::Resign(color));
         return true;
     }
 }
 
+/// A position in the moves of a `Game`, which can step forward and back, such as for a PGN
+/// viewer.  A `Game` holds a single line of moves, so there are no variations to branch into.
+///
+/// The cursor keeps its own copy of the moves, so the `Game` can change afterwards without
+/// affecting it.  Positions are computed one move at a time as the cursor moves forward, and
+/// remembered, so going back (and forward again) is cheap.
+#[derive(Clone, Debug)]
+pub struct GameCursor {
+    moves: Vec<ChessMove>,
+    // The positions reached so far: the starting position, then the position after each move
+    boards: Vec<Board>,
+    ply: usize,
+}
+
+impl GameCursor {
+    fn new(game: &Game) -> GameCursor {
+        let moves = game.chess_moves().collect();
+        GameCursor {
+            moves,
+            boards: vec![game.start_pos],
+            ply: 0,
+        }
+    }
+
+    /// Play the next move.  Returns `false`, without doing anything, at the end of the game.
+    pub fn forward(&mut self) -> bool {
+        if self.ply >= self.moves.len() {
+            return false;
+        }
+        if self.ply + 1 == self.boards.len() {
+            let next = self.boards[self.ply].make_move_new(self.moves[self.ply]);
+            self.boards.push(next);
+        }
+        self.ply += 1;
+        true
+    }
+
+    /// Take back the last move.  Returns `false`, without doing anything, at the start of the
+    /// game.
+    pub fn back(&mut self) -> bool {
+        if self.ply == 0 {
+            return false;
+        }
+        self.ply -= 1;
+        true
+    }
+
+    /// Go back to the starting position.
+    pub fn goto_start(&mut self) {
+        self.ply = 0;
+    }
+
+    /// Play every remaining move.
+    pub fn goto_end(&mut self) {
+        while self.forward() {}
+    }
+
+    /// The position the cursor is at.
+    pub fn current_board(&self) -> Board {
+        self.boards[self.ply]
+    }
+
+    /// How many moves have been played from the starting position to get here.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// The move that would be played by `forward`, if there is one.
+    pub fn next_move(&self) -> Option<ChessMove> {
+        self.moves.get(self.ply).copied()
+    }
+}
+
+/// Two games are equal when they start from the same position and make the same moves.  The
+/// headers are ignored, as are clock times, draw offers and resignations, so the same game
+/// imported from two databases compares equal.  See also `Game::move_hash`.
+///
+/// ```
+/// use chess::Game;
+///
+/// let a = Game::from_pgn("[Event \"A\"]\n\n1. e4 e5 *").expect("Valid PGN");
+/// let b = Game::from_pgn("[Event \"B\"]\n\n1. e4 e5 *").expect("Valid PGN");
+/// assert!(a == b);
+/// ```
+impl PartialEq for Game {
+    fn eq(&self, other: &Game) -> bool {
+        self.start_pos == other.start_pos && self.chess_moves().eq(other.chess_moves())
+    }
+}
+
+impl Eq for Game {}
+
 impl FromStr for Game {
     type Err = Error;
 
     fn from_str(fen: &str) -> Result<Self, Self::Err> {
-        Ok(Game::new_with_board(Board::from_str(fen)?))
+        let builder = BoardBuilder::from_str(fen)?;
+        let mut game = Game::new_with_board(Board::try_from(&builder)?);
+        game.start_fullmove_number = builder.get_fullmove_number();
+        Ok(game)
     }
 }
 
+/// Can no position from before this move ever come up again?
+fn is_irreversible(before: &Board, m: ChessMove, after: &Board) -> bool {
+    !before.is_reversible(m)
+        || before.castle_rights(Color::White) != after.castle_rights(Color::White)
+        || before.castle_rights(Color::Black) != after.castle_rights(Color::Black)
+}
+
+#[cfg(test)]
+use crate::castle_rights::CastleRights;
+#[cfg(test)]
+use crate::square::Square;
+
 #[cfg(test)]
 pub fn fake_pgn_parser(moves: &str) -> Game {
     moves
@@ -475,3 +1256,488 @@ pub fn test_can_declare_draw() {
     let game = fake_pgn_parser("1. d4 Nf6 2. c4 g6 3. Nc3 Bg7 4. e4 d6 5. Nf3 O-O 6. Be2 e5 7. O-O Nc6 8. d5 Ne7 9. Nd2 a5 10. Rb1 Nd7 11. a3 f5 12. b4 Kh8 13. f3 Ng8 14. Qc2 Ngf6 15. Nb5 axb4 16. axb4 Nh5 17. g3 Ndf6 18. c5 Bd7 19. Rb3 Nxg3 20. hxg3 Nh5 21. f4 exf4 22. c6 bxc6 23. dxc6 Nxg3 24. Rxg3 fxg3 25. cxd7 g2 26. Rf3 Qxd7 27. Bb2 fxe4 28. Rxf8+ Rxf8 29. Bxg7+ Qxg7 30. Qxe4 Qf6 31. Nf3 Qf4 32. Qe7 Rf7 33. Qe6 Rf6 34. Qe8+ Rf8 35. Qe7 Rf7 36. Qe6 Rf6 37. Qb3 g5 38. Nxc7 g4 39. Nd5 Qc1+ 40. Qd1 Qxd1+ 41. Bxd1 Rf5 42. Ne3 Rf4 43. Ne1 Rxb4 44. Bxg4 h5 45. Bf3 d5 46. N3xg2 h4 47. Nd3 Ra4 48. Ngf4 Kg7 49. Kg2 Kf6 50. Bxd5 Ra5 51. Bc6 Ra6 52. Bb7 Ra3 53. Be4 Ra4 54. Bd5 Ra5 55. Bc6 Ra6 56. Bf3 Kg5 57. Bb7 Ra1 58. Bc8 Ra4 59. Kf3 Rc4 60. Bd7 Kf6 61. Kg4 Rd4 62. Bc6 Rd8 63. Kxh4 Rg8 64. Be4 Rg1 65. Nh5+ Ke6 66. Ng3 Kf6 67. Kg4 Ra1 68. Bd5 Ra5 69. Bf3 Ra1 70. Kf4 Ke6 71. Nc5+ Kd6 72. Nge4+ Ke7 73. Ke5 Rf1 74. Bg4 Rg1 75. Be6 Re1 76. Bc8 Rc1 77. Kd4 Rd1+ 78. Nd3 Kf7 79. Ke3 Ra1 80. Kf4 Ke7 81. Nb4 Rc1 82. Nd5+ Kf7 83. Bd7 Rf1+ 84. Ke5 Ra1 85. Ng5+ Kg6 86. Nf3 Kg7 87. Bg4 Kg6 88. Nf4+ Kg7 89. Nd4 Re1+ 90. Kf5 Rc1 91. Be2 Re1 92. Bh5 Ra1 93. Nfe6+ Kh6 94. Be8 Ra8 95. Bc6 Ra1 96. Kf6 Kh7 97. Ng5+ Kh8 98. Nde6 Ra6 99. Be8 Ra8 100. Bh5 Ra1 101. Bg6 Rf1+ 102. Ke7 Ra1 103. Nf7+ Kg8 104. Nh6+ Kh8 105. Nf5 Ra7+ 106. Kf6 Ra1 107. Ne3 Re1 108. Nd5 Rg1 109. Bf5 Rf1 110. Ndf4 Ra1 111. Ng6+ Kg8 112. Ne7+ Kh8");
     assert!(!game.can_declare_draw());
 }
+
+#[test]
+pub fn test_make_move_checked_threefold() {
+    let mut game = Game::new();
+    let moves = ["Nc3", "Nf6", "Nb1", "Ng8", "Nc3", "Nf6", "Nb1", "Ng8"];
+
+    for (i, san) in moves.iter().enumerate() {
+        let m = ChessMove::from_san(&game.current_position(), san).expect("Valid SAN Move");
+        let claims = game.make_move_checked(m).expect("Legal move");
+        assert!(!claims.fifty_moves);
+        // The starting position shows up for the third time after the last move.
+        assert_eq!(claims.threefold_repetition, i == moves.len() - 1);
+    }
+}
+
+#[test]
+pub fn test_from_pgn_truncated() {
+    let pgn = "[Event \"Truncated\"]
+[Site \"?\"]
+[Result \"1-0\"]
+
+1. e4 e5 2. Nf3 {The rest of the game is lost} Nc6 (2... d6 3. d4) 3. Bb5 $1 a6";
+
+    let game = Game::from_pgn(pgn).expect("Valid PGN");
+    assert_eq!(game.actions().len(), 6);
+    assert_eq!(game.declared_result(), Some(Outcome::WhiteWins));
+    assert_eq!(game.computed_result(), None);
+    assert_eq!(
+        game.current_position(),
+        fake_pgn_parser("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6").current_position()
+    );
+}
+
+#[test]
+pub fn test_from_pgn_fen_and_en_passant() {
+    let pgn = "[FEN \"4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1\"]
+
+1. e4 dxe3 2. Kd1 e2+ 3. Kd2 e1=Q+ *";
+
+    let game = Game::from_pgn(pgn).expect("Valid PGN");
+    assert_eq!(game.actions().len(), 6);
+    assert_eq!(game.declared_result(), None);
+    assert_eq!(
+        game.current_position(),
+        Board::from_str("4k3/8/8/8/8/8/3K4/4q3 w - - 0 4").unwrap()
+    );
+
+    assert!(Game::from_pgn("1. e4 e5 2. Ke3").is_err());
+    assert!(Game::from_pgn("[Event \"Unterminated").is_err());
+}
+
+#[test]
+pub fn test_ply_from_black_to_move_fen() {
+    let mut game =
+        Game::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    assert_eq!(game.ply(), 1);
+    assert_eq!(game.fullmove_number(), 1);
+
+    game.make_move(ChessMove::new(Square::E7, Square::E5, None));
+    assert_eq!(game.ply(), 2);
+    assert_eq!(game.fullmove_number(), 2);
+
+    let mut game =
+        Game::from_str("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 2 20")
+            .unwrap();
+    assert_eq!(game.ply(), 39);
+    assert_eq!(game.fullmove_number(), 20);
+
+    game.offer_draw(Color::Black);
+    game.make_move(ChessMove::new(Square::G8, Square::F6, None));
+    assert_eq!(game.ply(), 40);
+    assert_eq!(game.fullmove_number(), 21);
+}
+
+#[test]
+pub fn test_to_pgn_tag_order() {
+    let mut headers = Headers::default();
+    headers
+        .extra
+        .insert("Annotator".to_string(), "Nobody".to_string());
+    headers.white = "White".to_string();
+    headers.result = "1-0".to_string();
+    headers.event = "Event".to_string();
+
+    let mut game = Game::from_str("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30").unwrap();
+    game.set_headers(headers.clone());
+    game.make_move(ChessMove::new(Square::E8, Square::D7, None));
+    game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+
+    let pgn = game.to_pgn();
+    let tags: Vec<&str> = pgn
+        .lines()
+        .take_while(|line| line.starts_with('['))
+        .map(|line| line[1..].split(' ').next().unwrap())
+        .collect();
+    assert_eq!(
+        tags,
+        vec![
+            "Event",
+            "Site",
+            "Date",
+            "Round",
+            "White",
+            "Black",
+            "Result",
+            "Annotator",
+            "SetUp",
+            "FEN"
+        ]
+    );
+    assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n"));
+    assert!(pgn.ends_with("\n\n30... Kd7 31. e4 1-0\n"));
+
+    let reloaded = Game::from_pgn(&pgn).expect("Valid PGN");
+    assert_eq!(reloaded.headers(), &headers);
+    assert_eq!(reloaded.current_position(), game.current_position());
+    assert_eq!(reloaded.to_pgn(), pgn);
+}
+
+#[test]
+pub fn test_clock_round_trip() {
+    let pgn = "1. e4 {[%clk 0:03:00]} e5 {A reply [%clk 0:02:58.5]} 2. Nf3 {no clock} Nc6 \
+               {[%clk 0:02:41]} *";
+
+    let game = Game::from_pgn(pgn).expect("Valid PGN");
+    let clocks: Vec<_> = (0..4).map(|i| game.clock(i)).collect();
+    assert_eq!(
+        clocks,
+        vec![
+            Some(Duration::from_secs(180)),
+            Some(Duration::from_millis(178_500)),
+            None,
+            Some(Duration::from_secs(161)),
+        ]
+    );
+
+    let exported = game.to_pgn();
+    assert!(exported
+        .contains("1. e4 {[%clk 0:03:00]} e5 {[%clk 0:02:58.5]} 2. Nf3 Nc6 {[%clk 0:02:41]} *"));
+
+    let reloaded = Game::from_pgn(&exported).expect("Valid PGN");
+    let reloaded_clocks: Vec<_> = (0..4).map(|i| reloaded.clock(i)).collect();
+    assert_eq!(reloaded_clocks, clocks);
+    assert_eq!(Game::new().clock(0), None);
+}
+
+#[test]
+pub fn test_irreversible_ply_after_capture() {
+    let mut game = Game::new();
+    assert_eq!(game.irreversible_ply(), 0);
+
+    let play = |game: &mut Game, moves: &[&str]| {
+        for san in moves {
+            let m = ChessMove::from_san(&game.current_position(), san).expect("Valid SAN Move");
+            assert!(game.make_move(m));
+        }
+    };
+
+    play(&mut game, &["e4", "d5", "Nf3", "Nc6", "Ng1", "Nb8"]);
+    assert_eq!(game.irreversible_ply(), 2);
+
+    // The capture restarts the window, so the earlier repetition doesn't count
+    play(&mut game, &["exd5"]);
+    assert_eq!(game.irreversible_ply(), 7);
+    assert!(!game.can_declare_draw());
+
+    play(&mut game, &["Nf6", "Nc3", "Ng8", "Nb1"]);
+    assert_eq!(game.irreversible_ply(), 7);
+    assert!(!game.can_declare_draw());
+
+    play(&mut game, &["Nf6", "Nc3", "Ng8", "Nb1"]);
+    assert!(game.can_declare_draw());
+
+    // Loading the same game from PGN finds the same window
+    let pgn = Game::from_pgn(&game.to_pgn()).expect("Valid PGN");
+    assert_eq!(pgn.irreversible_ply(), 7);
+    assert!(pgn.can_declare_draw());
+}
+
+#[test]
+pub fn test_position_at_ply() {
+    let mut game = fake_pgn_parser("1. e4 d5 2. exd5 Qxd5 3. Nc3 Qa5 4. d4 Nf6 5. Nf3 Bf5");
+    // Actions that aren't moves don't count as plies
+    game.offer_draw(Color::White);
+
+    let mut board = Board::default();
+    let moves: Vec<ChessMove> = game
+        .actions()
+        .iter()
+        .filter_map(|a| match *a {
+            Action::MakeMove(m) => Some(m),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(moves.len(), 10);
+
+    for (ply, m) in moves.iter().enumerate() {
+        assert_eq!(game.position_at_ply(ply), Some(board));
+        board = board.make_move_new(*m);
+    }
+    assert_eq!(game.position_at_ply(10), Some(game.current_position()));
+    assert_eq!(game.position_at_ply(11), None);
+}
+
+#[test]
+fn test_to_movetext() {
+    let game = Game::from_pgn(
+        "[Event \"Test\"]\n\n1. e4 {[%clk 0:05:00]} e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 \
+         6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7 *",
+    )
+    .unwrap();
+    let pgn = game.to_pgn();
+    let movetext = game.to_movetext();
+
+    assert!(!movetext.contains("Event"));
+    assert!(movetext.starts_with("1. e4 {[%clk 0:05:00]} e5 2. Nf3"));
+    assert!(movetext.ends_with("10. d4 Nbd7 *"));
+    assert_eq!(
+        pgn.split("\n\n").nth(1),
+        Some(format!("{}\n", movetext).as_str())
+    );
+}
+
+#[test]
+pub fn test_game_cursor() {
+    let game = fake_pgn_parser("1. e4 e5 2. Nf3 Nc6 3. Bb5");
+    let mut cursor = game.cursor();
+
+    assert!(!cursor.back());
+    assert!(cursor.forward());
+    assert!(cursor.forward());
+    assert!(cursor.forward());
+    assert_eq!(cursor.ply(), 3);
+    assert_eq!(cursor.current_board(), game.position_at_ply(3).unwrap());
+
+    assert!(cursor.back());
+    assert!(cursor.back());
+    assert_eq!(cursor.ply(), 1);
+    assert_eq!(cursor.current_board(), game.position_at_ply(1).unwrap());
+    assert_eq!(
+        cursor.next_move(),
+        Some(ChessMove::new(Square::E7, Square::E5, None))
+    );
+
+    cursor.goto_end();
+    assert_eq!(cursor.ply(), 5);
+    assert_eq!(cursor.current_board(), game.current_position());
+    assert!(!cursor.forward());
+    assert_eq!(cursor.next_move(), None);
+
+    cursor.goto_start();
+    assert_eq!(cursor.current_board(), Board::default());
+}
+
+#[test]
+pub fn test_game_equality_ignores_headers() {
+    let a = Game::from_pgn(
+        "[Event \"Club Championship\"]\n[White \"A\"]\n\n1. d4 {[%clk 0:10:00]} d5 2. c4 *",
+    )
+    .unwrap();
+    let b = Game::from_pgn("[Event \"Casual\"]\n\n1. d4 d5 2. c4 1/2-1/2").unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a.move_hash(), b.move_hash());
+
+    // A resignation isn't a move
+    let mut c = b.clone();
+    c.resign(Color::Black);
+    assert_eq!(a, c);
+
+    // One move more, or the same moves from another position, is a different game
+    let d = Game::from_pgn("1. d4 d5 2. c4 e6 *").unwrap();
+    assert_ne!(a, d);
+    assert_ne!(a.move_hash(), d.move_hash());
+
+    let mut e = Game::new_with_board(Board::default().null_move().unwrap());
+    e.make_move(ChessMove::new(Square::D7, Square::D5, None));
+    let mut f = Game::new_with_board(Board::default().null_move().unwrap());
+    f.make_move(ChessMove::new(Square::D7, Square::D6, None));
+    assert_ne!(e, f);
+    assert_ne!(a, e);
+}
+
+#[test]
+pub fn test_fork_at_current() {
+    let mut game = fake_pgn_parser("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6");
+    let fork = game.fork_at_current();
+
+    assert!(fork
+        .to_pgn()
+        .contains("[FEN \"r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4\"]"));
+    assert_eq!(fork.fullmove_number(), 4);
+    assert_eq!(fork.irreversible_ply(), fork.ply());
+
+    // The two games go their own ways
+    let mut fork = fork;
+    fork.make_move(ChessMove::new(Square::B5, Square::A4, None));
+    game.make_move(ChessMove::new(Square::B5, Square::C6, None));
+    assert_ne!(fork.current_position(), game.current_position());
+    assert_eq!(fork.actions().len(), 1);
+}
+
+#[test]
+pub fn test_fork_keeps_repetitions() {
+    // The start position comes up twice before the fork, and a third time after it
+    let mut game = fake_pgn_parser("1. Nc3 Nc6 2. Nb1 Nb8 3. Nc3 Nc6");
+    assert!(!game.can_declare_draw());
+    let mut fork = game.fork_at_current();
+    fork.make_move(ChessMove::new(Square::C3, Square::B1, None));
+    fork.make_move(ChessMove::new(Square::C6, Square::B8, None));
+    assert!(fork.can_declare_draw());
+
+    // Forking a fork keeps them too, but an irreversible move in between clears them
+    let fork_of_fork = fork.fork_at_current();
+    assert_eq!(fork_of_fork.repetitions_and_reversible_moves(), (3, 8));
+    assert_eq!(fork_of_fork.halfmove_clock(), 8);
+
+    game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    let fork = game.fork_at_current();
+    assert_eq!(fork.repetitions_and_reversible_moves(), (1, 0));
+    assert_eq!(fork.halfmove_clock(), 0);
+}
+
+#[test]
+pub fn test_is_over_after_checkmate() {
+    let mut game = fake_pgn_parser("1. f3 e5 2. g4");
+    assert!(!game.is_over());
+
+    game.make_move(ChessMove::new(Square::D8, Square::H4, None));
+    assert!(game.is_over());
+    assert_eq!(game.result(), Some(GameResult::BlackCheckmates));
+    assert!(!game.make_move(ChessMove::new(Square::E1, Square::F2, None)));
+}
+
+#[test]
+pub fn test_from_pgn_oversized_clock() {
+    let game = Game::from_pgn("1. e4 {[%clk 99999999999999999:00:00]} *").expect("Valid PGN");
+    assert_eq!(game.ply(), 1);
+    assert_eq!(game.clock(0), None);
+}
+
+#[test]
+pub fn test_from_pgn_castling_with_zeros() {
+    let game = Game::from_pgn(
+        "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 d6 5. d3 Be6 6. Nc3 Qd7 7. a3 0-0-0 *",
+    )
+    .expect("Valid PGN");
+    let letters = Game::from_pgn(
+        "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O d6 5. d3 Be6 6. Nc3 Qd7 7. a3 O-O-O *",
+    )
+    .expect("Valid PGN");
+    assert_eq!(game, letters);
+    assert_eq!(game.ply(), 14);
+
+    let board = game.current_position();
+    assert_eq!(board.king_square(Color::White), Square::G1);
+    assert_eq!(board.king_square(Color::Black), Square::C8);
+}
+
+#[test]
+pub fn test_to_pgn_result_tag_matches_movetext() {
+    let mut game = Game::new();
+    for (from, to) in [
+        (Square::F2, Square::F3),
+        (Square::E7, Square::E5),
+        (Square::G2, Square::G4),
+        (Square::D8, Square::H4),
+    ]
+    .iter()
+    {
+        assert!(game.make_move(ChessMove::new(*from, *to, None)));
+    }
+    assert_eq!(game.headers().result, "*");
+
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[Result \"0-1\"]\n"));
+    assert!(pgn.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+
+    let reloaded = Game::from_pgn(&pgn).expect("Valid PGN");
+    assert_eq!(reloaded.declared_result(), Some(Outcome::BlackWins));
+}
+
+#[test]
+pub fn test_is_over_automatic_draws() {
+    let bare_kings = Game::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").expect("Valid FEN");
+    assert!(bare_kings.result().is_none());
+    assert!(bare_kings.is_over());
+
+    // Shuffle the knights back and forth: the start position comes up a fifth time after
+    // sixteen moves
+    let b1c3 = ChessMove::new(Square::B1, Square::C3, None);
+    let b8c6 = ChessMove::new(Square::B8, Square::C6, None);
+    let c3b1 = ChessMove::new(Square::C3, Square::B1, None);
+    let c6b8 = ChessMove::new(Square::C6, Square::B8, None);
+    let mut game = Game::new();
+    for round in 0..4 {
+        for m in [b1c3, b8c6, c3b1, c6b8].iter() {
+            assert!(!game.is_over(), "round {}", round);
+            assert!(game.make_move(*m));
+        }
+    }
+    assert!(game.can_declare_draw());
+    assert!(game.is_over());
+
+    // 75 moves each without a pawn move or capture, always going to the position seen least
+    // often so far, so that nothing comes up five times
+    let mut game = Game::from_str("r6k/8/8/8/8/8/8/R6K w - - 0 1").expect("Valid FEN");
+    let mut seen = std::collections::HashMap::new();
+    for ply in 0..150 {
+        assert!(!game.is_over(), "ply {}", ply);
+        let board = game.current_position();
+        let m = MoveGen::new_legal(&board)
+            .filter(|m| board.captured_piece(*m).is_none())
+            .filter(|m| board.make_move_new(*m).status() == BoardStatus::Ongoing)
+            .min_by_key(|m| {
+                seen.get(&board.make_move_new(*m).get_hash())
+                    .copied()
+                    .unwrap_or(0)
+            })
+            .unwrap();
+        *seen.entry(board.make_move_new(m).get_hash()).or_insert(0) += 1;
+        assert!(game.make_move(m));
+    }
+    assert!(game.result().is_none());
+    assert_eq!(game.repetitions_and_reversible_moves().1, 150);
+    assert!(game.repetitions_and_reversible_moves().0 < 5);
+    assert!(game.is_over());
+}
+
+#[test]
+pub fn test_is_over_seventy_five_moves_after_losing_castle_rights() {
+    // Like the last part of `test_is_over_automatic_draws`, but white starts out with the right
+    // to castle, and only gives it up partway through
+    let mut game = Game::from_str("r6k/8/8/8/8/8/8/R3K1N1 w Q - 0 1").expect("Valid FEN");
+    let mut seen = std::collections::HashMap::new();
+    for ply in 0..150 {
+        assert!(!game.is_over(), "ply {}", ply);
+        let board = game.current_position();
+        let m = MoveGen::new_legal(&board)
+            .filter(|m| board.captured_piece(*m).is_none())
+            .filter(|m| board.make_move_new(*m).status() == BoardStatus::Ongoing)
+            .min_by_key(|m| {
+                let after = board.make_move_new(*m);
+                let gives_up_castling =
+                    after.castle_rights(Color::White) != board.castle_rights(Color::White);
+                (
+                    ply < 10 && gives_up_castling,
+                    seen.get(&after.get_hash()).copied().unwrap_or(0),
+                )
+            })
+            .unwrap();
+        *seen.entry(board.make_move_new(m).get_hash()).or_insert(0) += 1;
+        assert!(game.make_move(m));
+    }
+    assert_eq!(
+        game.current_position().castle_rights(Color::White),
+        CastleRights::NoRights
+    );
+    // Repetitions are only looked for since castling was given up, but the 75-move rule counts
+    // every move
+    assert!(game.repetitions_and_reversible_moves().1 < 150);
+    assert_eq!(game.halfmove_clock(), 150);
+    assert!(game.result().is_none());
+    assert!(game.is_over());
+}
+
+#[test]
+pub fn test_clocks_follow_actions() {
+    let mut game = Game::new();
+    game.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    game.offer_draw(Color::Black);
+    game.set_clock(1, Some(Duration::from_secs(30)));
+    assert_eq!(game.clock(0), None);
+    assert_eq!(game.clock(1), Some(Duration::from_secs(30)));
+    assert_eq!(game.clocks.len(), game.moves.len());
+
+    game.accept_draw();
+    assert_eq!(game.clocks.len(), game.moves.len());
+    assert_eq!(game.clock(2), None);
+}
+
+#[test]
+#[should_panic]
+pub fn test_set_clock_without_action() {
+    Game::new().set_clock(0, Some(Duration::from_secs(30)));
+}
@@ -18,6 +18,8 @@
 //! ```
 //!
 
+mod macros;
+
 mod board;
 pub use crate::board::*;
 
@@ -42,6 +44,9 @@ pub use crate::construct::*;
 mod file;
 pub use crate::file::*;
 
+mod direction;
+pub use crate::direction::*;
+
 mod magic;
 pub use crate::magic::{
     between, get_adjacent_files, get_bishop_moves, get_bishop_rays, get_file, get_king_moves,
@@ -62,15 +67,20 @@ mod square;
 pub use crate::square::*;
 
 mod movegen;
-pub use crate::movegen::MoveGen;
+pub use crate::movegen::{MoveGen, PerftStats};
 
 mod zobrist;
 
 mod game;
-pub use crate::game::{Action, Game, GameResult};
+pub use crate::game::{Action, DrawClaims, Game, GameCursor, GameResult, Outcome};
+
+mod pgn;
+pub use crate::pgn::Headers;
 
 mod board_builder;
 pub use crate::board_builder::BoardBuilder;
 
 mod error;
 pub use crate::error::Error;
+
+pub mod prelude;
@@ -0,0 +1,44 @@
+/// Write a `Square` by name.  The name is checked at compile time, so a typo is a compile error
+/// rather than a panic.
+///
+/// ```
+/// use chess::{sq, Square};
+///
+/// assert_eq!(sq!(e4), Square::E4);
+/// assert_eq!(sq!(a1), Square::A1);
+/// assert_eq!(sq!(H8), Square::H8);
+/// ```
+///
+/// ```compile_fail
+/// use chess::sq;
+///
+/// let square = sq!(i9);
+/// ```
+#[macro_export]
+macro_rules! sq {
+    ($square:ident) => {{
+        const SQUARE: $crate::Square = $crate::Square::from_literal(stringify!($square));
+        SQUARE
+    }};
+}
+
+/// Write a `BitBoard` as a list of the squares that are set.  Like `sq!`, the names are checked
+/// at compile time.
+///
+/// ```
+/// use chess::{bb, BitBoard, Square, EMPTY};
+///
+/// assert_eq!(
+///     bb!(e4, d5),
+///     BitBoard::from_square(Square::E4) | BitBoard::from_square(Square::D5)
+/// );
+/// assert_eq!(bb!(a1), BitBoard::from_square(Square::A1));
+/// assert_eq!(bb!(), EMPTY);
+/// ```
+#[macro_export]
+macro_rules! bb {
+    ($($square:ident),* $(,)?) => {{
+        const BITS: u64 = 0 $(| 1u64 << $crate::sq!($square).to_int())*;
+        $crate::BitBoard(BITS)
+    }};
+}
@@ -7,8 +7,10 @@ use crate::piece::{Piece, NUM_PROMOTION_PIECES, PROMOTION_PIECES};
 use crate::square::Square;
 use arrayvec::ArrayVec;
 use nodrop::NoDrop;
+use std::collections::HashMap;
 use std::iter::ExactSizeIterator;
 use std::mem;
+use std::vec;
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
 pub struct SquareAndBitBoard {
@@ -44,6 +46,27 @@ pub type MoveList = NoDrop<ArrayVec<[SquareAndBitBoard; 18]>>;
 /// * Being able to iterate pseudo legal moves, while keeping the (nearly) free legality checks in
 ///   place
 ///
+/// # Move Order
+///
+/// Moves from `MoveGen::new_legal` come out in a fixed order, so the same position always
+/// produces the same sequence of moves:
+///
+/// * Moves are grouped by the moving piece, in the order pawns, knights, bishops, rooks, queens,
+///   then the king.
+/// * Within a piece type, unpinned pieces come before pinned ones, and each of those groups goes
+///   from the lowest source square to the highest (a1, b1, ..., h8).  Pawn en-passant captures
+///   come after every other pawn move.
+/// * For each source square, destinations go from the lowest square to the highest.
+/// * Promotions for a destination are in `PROMOTION_PIECES` order.
+///
+/// Calling `set_iterator_mask`, `set_source_mask` or `remove_move` may reorder the remaining
+/// moves.
+///
+/// # Peeking
+///
+/// `MoveGen::peek` looks at the next move without advancing.  `MoveGen` also works with
+/// `Iterator::peekable`, but the `Peekable` wrapper hides methods like `set_iterator_mask`.
+///
 /// # Examples
 ///
 /// ```
@@ -87,9 +110,23 @@ pub struct MoveGen {
     moves: MoveList,
     promotion_index: usize,
     iterator_mask: BitBoard,
+    source_mask: BitBoard,
     index: usize,
 }
 
+/// The result of `MoveGen::perft_detailed`: how many leaf nodes there are, and how many of them
+/// were reached by each kind of move.  En passant captures are counted in `captures` as well as
+/// in `en_passants`, the same as the published perft tables.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+}
+
 impl MoveGen {
     #[inline(always)]
     fn enumerate_moves(board: &Board) -> MoveList {
@@ -118,6 +155,73 @@ impl MoveGen {
         movelist
     }
 
+    /// Does `board` have at least one legal move?  Unlike `new_legal`, this stops as soon as
+    /// one piece type turns up a move.
+    pub(crate) fn has_legals(board: &Board) -> bool {
+        MoveGen::first_legals(board).is_some()
+    }
+
+    /// Some legal move on `board`, if there is one, found the same way as `has_legals`.  A
+    /// promotion is always to a queen.
+    pub(crate) fn first_legal(board: &Board) -> Option<ChessMove> {
+        let first = MoveGen::first_legals(board)?;
+        let promotion = if first.promotion {
+            Some(PROMOTION_PIECES[0])
+        } else {
+            None
+        };
+        Some(ChessMove::new(
+            first.square,
+            first.bitboard.to_square(),
+            promotion,
+        ))
+    }
+
+    /// The moves of the first piece found to have any, trying the most likely pieces first.
+    fn first_legals(board: &Board) -> Option<SquareAndBitBoard> {
+        let checkers = *board.checkers();
+        let mask = !board.color_combined(board.side_to_move());
+
+        if checkers == EMPTY {
+            MoveGen::first_legals_of::<NotInCheckType>(board, mask)
+        } else if checkers.popcnt() == 1 {
+            MoveGen::first_legals_of::<InCheckType>(board, mask)
+        } else {
+            let mut movelist = NoDrop::new(ArrayVec::<[SquareAndBitBoard; 18]>::new());
+            KingType::legals::<InCheckType>(&mut movelist, board, mask);
+            movelist.first().copied()
+        }
+    }
+
+    #[inline(always)]
+    fn first_legals_of<T: CheckType>(board: &Board, mask: BitBoard) -> Option<SquareAndBitBoard> {
+        let mut movelist = NoDrop::new(ArrayVec::<[SquareAndBitBoard; 18]>::new());
+
+        // The king and pawns are the most likely to have a move, so try them first
+        KingType::legals::<T>(&mut movelist, board, mask);
+        if !movelist.is_empty() {
+            return movelist.first().copied();
+        }
+        PawnType::legals::<T>(&mut movelist, board, mask);
+        if !movelist.is_empty() {
+            return movelist.first().copied();
+        }
+        KnightType::legals::<T>(&mut movelist, board, mask);
+        if !movelist.is_empty() {
+            return movelist.first().copied();
+        }
+        BishopType::legals::<T>(&mut movelist, board, mask);
+        if !movelist.is_empty() {
+            return movelist.first().copied();
+        }
+        RookType::legals::<T>(&mut movelist, board, mask);
+        if !movelist.is_empty() {
+            return movelist.first().copied();
+        }
+        QueenType::legals::<T>(&mut movelist, board, mask);
+        movelist.first().copied()
+    }
+
     /// Create a new `MoveGen` structure, only generating legal moves
     #[inline(always)]
     pub fn new_legal(board: &Board) -> MoveGen {
@@ -125,10 +229,143 @@ impl MoveGen {
             moves: MoveGen::enumerate_moves(board),
             promotion_index: 0,
             iterator_mask: !EMPTY,
+            source_mask: !EMPTY,
             index: 0,
         }
     }
 
+    /// Generate every legal move, with the moves that give check (directly or by discovery)
+    /// first.
+    ///
+    /// Only the split into two groups is guaranteed: checks, then everything else.  Within each
+    /// group, moves stay in their usual generation order.
+    ///
+    /// ```
+    /// use chess::{Board, MoveGen};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let moves: Vec<_> = MoveGen::new_legal_checks_first(&board).collect();
+    ///
+    /// // Ra8+ and Re1+ come first
+    /// assert!(board.gives_check(moves[0]));
+    /// assert!(board.gives_check(moves[1]));
+    /// assert!(!board.gives_check(moves[2]));
+    /// ```
+    pub fn new_legal_checks_first(board: &Board) -> vec::IntoIter<ChessMove> {
+        let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+        MoveGen::order_moves(&mut moves, |m| if board.gives_check(m) { 1 } else { 0 });
+        moves.into_iter()
+    }
+
+    /// Generate every legal move, with captures first, ordered by most valuable victim, then
+    /// least valuable attacker (MVV-LVA).  The quiet moves follow in their usual generation order.
+    ///
+    /// This is only a heuristic: it doesn't look at whether the captured piece is defended.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, MoveGen, Square};
+    /// use std::str::FromStr;
+    ///
+    /// let board = Board::from_str("4k3/8/8/3q4/4P3/8/8/3RK3 w - - 0 1").unwrap();
+    /// let moves: Vec<_> = MoveGen::new_legal_mvv_lva(&board).collect();
+    ///
+    /// // The pawn takes the queen before the rook does
+    /// assert_eq!(moves[0], ChessMove::new(Square::E4, Square::D5, None));
+    /// assert_eq!(moves[1], ChessMove::new(Square::D1, Square::D5, None));
+    /// ```
+    pub fn new_legal_mvv_lva(board: &Board) -> vec::IntoIter<ChessMove> {
+        let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+        MoveGen::order_moves(&mut moves, |m| match board.captured_piece(m) {
+            // Attackers are ranked by index rather than value, so the king (worth 0) comes last.
+            // The score is always positive, and victims worth more always come first.
+            Some(victim) => {
+                let attacker = board.piece_on(m.get_source()).unwrap();
+                victim.value() * 10 - attacker.to_index() as i32
+            }
+            None => 0,
+        });
+        moves.into_iter()
+    }
+
+    /// Generate the legal captures that don't lose material according to `Board::see_ge`, in
+    /// their usual generation order.
+    ///
+    /// This is a heuristic filter for quiescence search: static exchange evaluation ignores pins
+    /// and checks, so a capture it throws away may still turn out to be good.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, MoveGen, Square};
+    /// use std::str::FromStr;
+    ///
+    /// // Rxe5 loses the rook to dxe5, but Nxb5 wins a pawn
+    /// let board = Board::from_str("4k3/8/3p4/1p2p3/8/2N5/8/4RK2 w - - 0 1").unwrap();
+    /// let captures: Vec<_> = MoveGen::new_legal_good_captures(&board).collect();
+    ///
+    /// assert_eq!(captures, vec![ChessMove::new(Square::C3, Square::B5, None)]);
+    /// ```
+    pub fn new_legal_good_captures(board: &Board) -> vec::IntoIter<ChessMove> {
+        let captures: Vec<ChessMove> = MoveGen::new_legal(board)
+            .filter(|m| board.captured_piece(*m).is_some() && board.see_ge(*m, 0))
+            .collect();
+        captures.into_iter()
+    }
+
+    /// Sort `moves` from the highest `score` to the lowest.  The sort is stable, so moves with
+    /// the same score stay in their original (generation) order.
+    ///
+    /// ```
+    /// use chess::{Board, ChessMove, MoveGen, Piece};
+    ///
+    /// let board = Board::default();
+    /// let mut moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    ///
+    /// // Put the knight moves first
+    /// MoveGen::order_moves(&mut moves, |m| {
+    ///     if board.piece_on(m.get_source()) == Some(Piece::Knight) { 1 } else { 0 }
+    /// });
+    ///
+    /// assert!(moves[..4].iter().all(|m| board.piece_on(m.get_source()) == Some(Piece::Knight)));
+    /// ```
+    pub fn order_moves(moves: &mut [ChessMove], score: impl Fn(ChessMove) -> i32) {
+        moves.sort_by_key(|m| -(score(*m) as i64));
+    }
+
+    /// Does the entry at `i` have any moves left to iterate, given both the iterator mask and the
+    /// source mask?
+    #[inline(always)]
+    fn is_active(&self, i: usize) -> bool {
+        self.moves[i].bitboard & self.iterator_mask != EMPTY
+            && BitBoard::from_square(self.moves[i].square) & self.source_mask != EMPTY
+    }
+
+    /// Give me the move that `next` would return, without advancing the iterator.
+    ///
+    /// ```
+    /// use chess::{Board, MoveGen};
+    ///
+    /// let mut movegen = MoveGen::new_legal(&Board::default());
+    /// let first = movegen.peek();
+    ///
+    /// assert!(first.is_some());
+    /// assert_eq!(movegen.len(), 20);
+    /// assert_eq!(movegen.next(), first);
+    /// ```
+    pub fn peek(&self) -> Option<ChessMove> {
+        if self.index >= self.moves.len() || !self.is_active(self.index) {
+            return None;
+        }
+
+        let moves = &self.moves[self.index];
+        let dest = (moves.bitboard & self.iterator_mask).to_square();
+        let promotion = if moves.promotion {
+            Some(PROMOTION_PIECES[self.promotion_index])
+        } else {
+            None
+        };
+        Some(ChessMove::new(moves.square, dest, promotion))
+    }
+
     /// Never, ever, iterate any moves that land on the following squares
     pub fn remove_mask(&mut self, mask: BitBoard) {
         for x in 0..self.moves.len() {
@@ -152,6 +389,37 @@ impl MoveGen {
     ///       to get the remaining moves, or another mask
     pub fn set_iterator_mask(&mut self, mask: BitBoard) {
         self.iterator_mask = mask;
+        self.partition();
+    }
+
+    /// For now, only iterate moves of pieces that start on the following squares.  This works
+    /// alongside `set_iterator_mask`: a move is only iterated if its source is in this mask, and
+    /// its destination is in the iterator mask.
+    ///
+    /// Like `set_iterator_mask`, you can pass in a different mask (such as ! `EMPTY`) later to
+    /// get the remaining moves.
+    ///
+    /// ```
+    /// use chess::{BitBoard, Board, ChessMove, MoveGen, Square};
+    ///
+    /// let mut movegen = MoveGen::new_legal(&Board::default());
+    /// movegen.set_source_mask(BitBoard::from_square(Square::G1));
+    ///
+    /// assert_eq!(
+    ///     movegen.collect::<Vec<_>>(),
+    ///     vec![
+    ///         ChessMove::new(Square::G1, Square::F3, None),
+    ///         ChessMove::new(Square::G1, Square::H3, None),
+    ///     ]
+    /// );
+    /// ```
+    pub fn set_source_mask(&mut self, mask: BitBoard) {
+        self.source_mask = mask;
+        self.partition();
+    }
+
+    /// Restart iteration after one of the masks changed.
+    fn partition(&mut self) {
         self.index = 0;
 
         // the iterator portion of this struct relies on the invariant that
@@ -161,14 +429,14 @@ impl MoveGen {
 
         // first, find the first non-used moves index, and store that in i
         let mut i = 0;
-        while i < self.moves.len() && self.moves[i].bitboard & self.iterator_mask != EMPTY {
+        while i < self.moves.len() && self.is_active(i) {
             i += 1;
         }
 
         // next, find each element past i where the moves are used, and store
         // that in i.  Then, increment i to point to a new unused slot.
         for j in (i + 1)..self.moves.len() {
-            if self.moves[j].bitboard & self.iterator_mask != EMPTY {
+            if self.is_active(j) {
                 let backup = self.moves[i];
                 self.moves[i] = self.moves[j];
                 self.moves[j] = backup;
@@ -236,6 +504,97 @@ impl MoveGen {
         }
     }
 
+    /// A perft test that remembers the node count of every `(hash, depth)` it has already
+    /// searched, in `tt`.
+    ///
+    /// This is only for counting nodes.  Transpositions are looked up instead of searched again,
+    /// which makes it faster, but the count is identical to `movegen_perft_test`.  `tt` can be
+    /// reused between calls, as long as the boards are searched with the same hash keys.
+    ///
+    /// ```
+    /// use chess::{Board, MoveGen};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut tt = HashMap::new();
+    /// assert_eq!(MoveGen::perft_hashed(&Board::default(), 4, &mut tt), 197_281);
+    /// ```
+    pub fn perft_hashed(board: &Board, depth: u32, tt: &mut HashMap<(u64, u32), u64>) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        if let Some(count) = tt.get(&(board.get_hash(), depth)) {
+            return *count;
+        }
+
+        let iterable = MoveGen::new_legal(board);
+        let result = if depth == 1 {
+            iterable.len() as u64
+        } else {
+            iterable
+                .map(|m| MoveGen::perft_hashed(&board.make_move_new(m), depth - 1, tt))
+                .sum()
+        };
+
+        tt.insert((board.get_hash(), depth), result);
+        result
+    }
+
+    /// A perft test that also sorts the leaf nodes by the move that reached them, for tracking
+    /// down move generation bugs.  With `depth` 0, the board itself is the only leaf.
+    ///
+    /// ```
+    /// use chess::{Board, MoveGen};
+    ///
+    /// let stats = MoveGen::perft_detailed(&Board::default(), 3);
+    /// assert_eq!(stats.nodes, 8902);
+    /// assert_eq!(stats.captures, 34);
+    /// assert_eq!(stats.checks, 12);
+    /// ```
+    pub fn perft_detailed(board: &Board, depth: u32) -> PerftStats {
+        let mut stats = PerftStats::default();
+        if depth == 0 {
+            stats.nodes = 1;
+            return stats;
+        }
+
+        for m in MoveGen::new_legal(board) {
+            let next = board.make_move_new(m);
+            if depth > 1 {
+                let child = MoveGen::perft_detailed(&next, depth - 1);
+                stats.nodes += child.nodes;
+                stats.captures += child.captures;
+                stats.en_passants += child.en_passants;
+                stats.castles += child.castles;
+                stats.promotions += child.promotions;
+                stats.checks += child.checks;
+                continue;
+            }
+
+            let source = m.get_source();
+            let dest = m.get_dest();
+            stats.nodes += 1;
+            if board.captured_piece(m).is_some() {
+                stats.captures += 1;
+                if board.piece_on(dest).is_none() {
+                    stats.en_passants += 1;
+                }
+            }
+            if board.piece_on(source) == Some(Piece::King)
+                && source.get_file().distance(dest.get_file()) == 2
+            {
+                stats.castles += 1;
+            }
+            if m.get_promotion().is_some() {
+                stats.promotions += 1;
+            }
+            if *next.checkers() != EMPTY {
+                stats.checks += 1;
+            }
+        }
+
+        stats
+    }
+
     #[cfg(test)]
     /// Do a perft test after splitting the moves up into two groups
     pub fn movegen_perft_test_piecewise(board: &Board, depth: usize) -> usize {
@@ -277,7 +636,7 @@ impl ExactSizeIterator for MoveGen {
     fn len(&self) -> usize {
         let mut result = 0;
         for i in 0..self.moves.len() {
-            if self.moves[i].bitboard & self.iterator_mask == EMPTY {
+            if !self.is_active(i) {
                 break;
             }
             if self.moves[i].promotion {
@@ -302,9 +661,7 @@ impl Iterator for MoveGen {
 
     /// Find the next chess move.
     fn next(&mut self) -> Option<ChessMove> {
-        if self.index >= self.moves.len()
-            || self.moves[self.index].bitboard & self.iterator_mask == EMPTY
-        {
+        if self.index >= self.moves.len() || !self.is_active(self.index) {
             // are we done?
             None
         } else if self.moves[self.index].promotion {
@@ -570,3 +927,265 @@ fn test_masked_move_gen() {
         expected.into_iter().collect()
     );
 }
+
+#[test]
+fn test_move_order_is_deterministic() {
+    let board =
+        Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+    let first: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    for _ in 0..10 {
+        assert_eq!(MoveGen::new_legal(&board).collect::<Vec<_>>(), first);
+    }
+
+    let start: Vec<ChessMove> = MoveGen::new_legal(&Board::default()).collect();
+    assert_eq!(
+        start[..4],
+        [
+            move_of("a2a3"),
+            move_of("a2a4"),
+            move_of("b2b3"),
+            move_of("b2b4")
+        ]
+    );
+    assert_eq!(
+        start[16..],
+        [
+            move_of("b1a3"),
+            move_of("b1c3"),
+            move_of("g1f3"),
+            move_of("g1h3")
+        ]
+    );
+}
+
+#[test]
+fn test_order_moves_is_stable() {
+    let board = Board::default();
+    let mut moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    let original = moves.clone();
+
+    MoveGen::order_moves(&mut moves, |m| m.get_dest().get_rank().to_index() as i32);
+
+    // Double pushes first, then single pushes and knight moves, each in generation order
+    let expected: Vec<ChessMove> = original
+        .iter()
+        .filter(|m| m.get_dest().get_rank().to_index() == 3)
+        .chain(
+            original
+                .iter()
+                .filter(|m| m.get_dest().get_rank().to_index() == 2),
+        )
+        .cloned()
+        .collect();
+    assert_eq!(moves, expected);
+}
+
+#[test]
+fn test_promotion_order() {
+    let board = Board::from_str("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    let promotions: Vec<Option<Piece>> = MoveGen::new_legal(&board)
+        .filter(|m| m.get_source() == Square::A7)
+        .map(|m| m.get_promotion())
+        .collect();
+
+    let expected: Vec<Option<Piece>> = PROMOTION_PIECES.iter().map(|p| Some(*p)).collect();
+    assert_eq!(promotions, expected);
+    assert_eq!(promotions[0], Some(Piece::Queen));
+}
+
+#[test]
+fn test_checks_first() {
+    // Plenty of checks: the queen, the knight, a discovered check from the rook, and a
+    // promotion
+    let board = Board::from_str("3k4/5P2/8/1N6/8/8/3B4/3RQ1K1 w - - 0 1").unwrap();
+
+    let moves: Vec<ChessMove> = MoveGen::new_legal_checks_first(&board).collect();
+    assert_eq!(moves.len(), MoveGen::new_legal(&board).len());
+
+    let checks = moves.iter().filter(|m| board.gives_check(**m)).count();
+    assert!(checks > 0);
+    assert!(moves[..checks].iter().all(|m| board.gives_check(*m)));
+    assert!(moves[checks..].iter().all(|m| !board.gives_check(*m)));
+
+    // The discovered check (moving the d2 bishop) and f8=Q+ are among them
+    assert!(moves[..checks].contains(&ChessMove::new(Square::D2, Square::G5, None)));
+    assert!(moves[..checks].contains(&ChessMove::new(Square::F7, Square::F8, Some(Piece::Queen))));
+}
+
+#[test]
+fn test_peek_matches_next() {
+    // Promotions, en passant, and a mask, to cover each way `next` can advance
+    let board = Board::from_str("4k3/1P6/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let mut movegen = MoveGen::new_legal(&board);
+    movegen.set_iterator_mask(!EMPTY);
+
+    let mut count = 0;
+    while let Some(peeked) = movegen.peek() {
+        assert_eq!(movegen.next(), Some(peeked));
+        count += 1;
+    }
+    assert_eq!(movegen.next(), None);
+    assert_eq!(count, MoveGen::new_legal(&board).len());
+}
+
+#[test]
+fn test_perft_hashed() {
+    let mut tt = HashMap::new();
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ]
+    .iter()
+    {
+        let board = Board::from_str(fen).unwrap();
+        assert_eq!(
+            MoveGen::perft_hashed(&board, 5, &mut tt),
+            MoveGen::movegen_perft_test(&board, 5) as u64
+        );
+    }
+}
+
+#[test]
+fn test_source_mask() {
+    let board = Board::default();
+    let mut movegen = MoveGen::new_legal(&board);
+
+    // Only the e2 pawn, and only its double push
+    movegen.set_source_mask(BitBoard::from_square(Square::E2));
+    assert_eq!(movegen.len(), 2);
+    movegen.set_iterator_mask(BitBoard::from_square(Square::E4));
+    assert_eq!(
+        movegen.next(),
+        Some(ChessMove::new(Square::E2, Square::E4, None))
+    );
+    assert_eq!(movegen.next(), None);
+
+    // Everything that is left
+    movegen.set_source_mask(!EMPTY);
+    movegen.set_iterator_mask(!EMPTY);
+    assert_eq!(movegen.len(), 19);
+    assert!(movegen.all(|m| m != ChessMove::new(Square::E2, Square::E4, None)));
+}
+
+#[test]
+fn test_mvv_lva() {
+    // The d3 pawn can take the queen or the e4 pawn, and the queen can take the e4 pawn
+    let board = Board::from_str("4k3/8/8/8/2q1p3/3P1Q2/8/4K3 w - - 0 1").unwrap();
+    let moves: Vec<ChessMove> = MoveGen::new_legal_mvv_lva(&board).collect();
+
+    let pxq = ChessMove::new(Square::D3, Square::C4, None);
+    let qxp = ChessMove::new(Square::F3, Square::E4, None);
+    let pxp = ChessMove::new(Square::D3, Square::E4, None);
+    let position = |m| moves.iter().position(|x| *x == m).unwrap();
+
+    assert_eq!(position(pxq), 0);
+    assert!(position(pxq) < position(pxp));
+    assert!(position(pxp) < position(qxp));
+
+    // Every capture comes before every quiet move
+    let captures = moves
+        .iter()
+        .take_while(|m| board.captured_piece(**m).is_some())
+        .count();
+    assert_eq!(captures, 3);
+    assert_eq!(moves.len(), MoveGen::new_legal(&board).len());
+}
+
+#[test]
+fn test_mvv_lva_king_attacks_last() {
+    // Both the king and the c2 pawn can take the d3 pawn
+    let board = Board::from_str("4k3/8/8/8/8/3p4/2P1K3/8 w - - 0 1").unwrap();
+    let moves: Vec<ChessMove> = MoveGen::new_legal_mvv_lva(&board).collect();
+
+    assert_eq!(moves[0], ChessMove::new(Square::C2, Square::D3, None));
+    assert_eq!(moves[1], ChessMove::new(Square::E2, Square::D3, None));
+}
+
+#[test]
+fn test_good_captures() {
+    // Rxe5 loses the rook to dxe5, but Nxb5 wins a pawn
+    let board = Board::from_str("4k3/8/3p4/1p2p3/8/2N5/8/4RK2 w - - 0 1").unwrap();
+    let captures: Vec<ChessMove> = MoveGen::new_legal_good_captures(&board).collect();
+    assert_eq!(captures, vec![ChessMove::new(Square::C3, Square::B5, None)]);
+
+    // With a second rook behind the first, Rxe5 dxe5 Rxe5 trades R for 2P: still losing
+    let board = Board::from_str("4k3/8/3p4/4p3/8/8/4R3/4RK2 w - - 0 1").unwrap();
+    assert!(!board.see_ge(ChessMove::new(Square::E2, Square::E5, None), 0));
+
+    // Equal trades are fine
+    let board = Board::from_str("4k3/8/3p4/4n3/8/3N4/8/4K3 w - - 0 1").unwrap();
+    let nxe5 = ChessMove::new(Square::D3, Square::E5, None);
+    assert!(board.see_ge(nxe5, 0));
+    assert!(!board.see_ge(nxe5, 1));
+    assert_eq!(
+        MoveGen::new_legal_good_captures(&board).collect::<Vec<_>>(),
+        vec![nxe5]
+    );
+
+    // A king can only take a piece nobody defends
+    let board = Board::from_str("4k3/8/8/8/8/5p2/3pp3/4K3 w - - 0 1").unwrap();
+    assert!(!board.see_ge(ChessMove::new(Square::E1, Square::E2, None), 0));
+    assert!(board.see_ge(ChessMove::new(Square::E1, Square::D2, None), 100));
+}
+
+#[test]
+fn test_perft_detailed() {
+    let board =
+        Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+    assert_eq!(
+        MoveGen::perft_detailed(&board, 1),
+        PerftStats {
+            nodes: 48,
+            captures: 8,
+            en_passants: 0,
+            castles: 2,
+            promotions: 0,
+            checks: 0,
+        }
+    );
+    assert_eq!(
+        MoveGen::perft_detailed(&board, 2),
+        PerftStats {
+            nodes: 2039,
+            captures: 351,
+            en_passants: 1,
+            castles: 91,
+            promotions: 0,
+            checks: 3,
+        }
+    );
+    assert_eq!(MoveGen::perft_detailed(&board, 0).nodes, 1);
+}
+
+#[test]
+fn no_castling_out_of_check() {
+    // The e8 rook gives check, but none of the squares the king would cross are attacked, so
+    // only the check itself rules out O-O and O-O-O
+    let board = Board::from_str("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    let oo = ChessMove::new(Square::E1, Square::G1, None);
+    let ooo = ChessMove::new(Square::E1, Square::C1, None);
+
+    let moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    assert!(!moves.contains(&oo));
+    assert!(!moves.contains(&ooo));
+    assert!(!board.legal(oo));
+    assert!(!board.legal(ooo));
+
+    // The same for black
+    let board = Board::from_str("r3k2r/8/8/8/8/8/8/4R1K1 b kq - 0 1").unwrap();
+    let moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+    assert!(!moves.contains(&ChessMove::new(Square::E8, Square::G8, None)));
+    assert!(!moves.contains(&ChessMove::new(Square::E8, Square::C8, None)));
+
+    // Without the check, both are fine
+    let board = Board::from_str("5rk1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(board.legal(ooo));
+    assert!(!board.legal(oo));
+    let board = Board::from_str("6k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(board.legal(oo));
+    assert!(board.legal(ooo));
+}
@@ -0,0 +1,295 @@
+use crate::error::Error;
+
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::Duration;
+
+/// The tag pairs of a PGN game.  The seven tags every PGN game must have (the "seven tag
+/// roster") get their own fields; any other tags go in `extra`.
+///
+/// ```
+/// use chess::Headers;
+///
+/// let mut headers = Headers::default();
+/// assert_eq!(headers.event, "?");
+/// assert_eq!(headers.result, "*");
+///
+/// headers.white = "Morphy, Paul".to_string();
+/// headers.extra.insert("Opening".to_string(), "Philidor Defense".to_string());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Headers {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    /// "1-0", "0-1", "1/2-1/2", or "*" for a game that is unfinished or whose result is unknown.
+    pub result: String,
+    /// Every other tag, by name.
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Default for Headers {
+    /// The values the PGN standard uses when a tag is unknown.
+    fn default() -> Headers {
+        Headers {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+            result: "*".to_string(),
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl Headers {
+    /// Store a tag, putting it in the right field.
+    pub(crate) fn set(&mut self, name: String, value: String) {
+        match name.as_str() {
+            "Event" => self.event = value,
+            "Site" => self.site = value,
+            "Date" => self.date = value,
+            "Round" => self.round = value,
+            "White" => self.white = value,
+            "Black" => self.black = value,
+            "Result" => self.result = value,
+            _ => {
+                self.extra.insert(name, value);
+            }
+        }
+    }
+
+    /// The seven tag roster, in the order the PGN standard requires.
+    pub(crate) fn roster(&self) -> [(&'static str, &str); 7] {
+        [
+            ("Event", &self.event),
+            ("Site", &self.site),
+            ("Date", &self.date),
+            ("Round", &self.round),
+            ("White", &self.white),
+            ("Black", &self.black),
+            ("Result", &self.result),
+        ]
+    }
+}
+
+/// Write a tag pair, escaping the value.
+pub(crate) fn write_tag(pgn: &mut String, name: &str, value: &str) {
+    pgn.push('[');
+    pgn.push_str(name);
+    pgn.push_str(" \"");
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            pgn.push('\\');
+        }
+        pgn.push(c);
+    }
+    pgn.push_str("\"]\n");
+}
+
+/// Find a `[%clk H:MM:SS]` clock annotation in the text of a comment.  The seconds may have a
+/// fractional part.  A clock too large for a `Duration` is ignored.
+pub(crate) fn parse_clock(comment: &str) -> Option<Duration> {
+    let start = comment.find("[%clk")? + "[%clk".len();
+    let end = start + comment[start..].find(']')?;
+    let mut fields = comment[start..end].trim().split(':');
+
+    let hours: u64 = fields.next()?.parse().ok()?;
+    let minutes: u64 = fields.next()?.parse().ok()?;
+    let seconds: f64 = fields.next()?.parse().ok()?;
+    if fields.next().is_some() || minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+
+    let whole = hours.checked_mul(3600)?.checked_add(minutes * 60)?;
+    Duration::from_secs(whole).checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+}
+
+/// Write a clock time as a `{[%clk H:MM:SS]}` comment.  Fractions of a second are only written
+/// when there are any.
+pub(crate) fn format_clock(clock: Duration) -> String {
+    let secs = clock.as_secs();
+    let mut result = format!(
+        "{{[%clk {}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    );
+    if clock.subsec_millis() != 0 {
+        let fraction = format!("{:03}", clock.subsec_millis());
+        result.push('.');
+        result.push_str(fraction.trim_end_matches('0'));
+    }
+    result.push_str("]}");
+    result
+}
+
+/// One lexical element of a PGN file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PgnToken {
+    /// A `[Name "Value"]` tag pair.
+    Tag(String, String),
+    /// The text of a `{...}` or `; ...` comment.
+    Comment(String),
+    /// A numeric annotation glyph, such as `$1`.
+    Nag(u32),
+    /// The `(` that starts a variation.
+    VariationStart,
+    /// The `)` that ends a variation.
+    VariationEnd,
+    /// Anything else: move numbers, SAN moves and game termination markers.
+    Symbol(String),
+}
+
+/// Split a PGN string into tokens.
+pub fn tokenize(pgn: &str) -> Result<Vec<PgnToken>, Error> {
+    let mut tokens = vec![];
+    let mut chars = pgn.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => tokens.push(read_tag(&mut chars)?),
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(x) => comment.push(x),
+                        None => return Err(Error::InvalidPgn),
+                    }
+                }
+                tokens.push(PgnToken::Comment(comment.trim().to_string()));
+            }
+            ';' => {
+                let mut comment = String::new();
+                for x in chars.by_ref() {
+                    if x == '\n' {
+                        break;
+                    }
+                    comment.push(x);
+                }
+                tokens.push(PgnToken::Comment(comment.trim().to_string()));
+            }
+            '(' => tokens.push(PgnToken::VariationStart),
+            ')' => tokens.push(PgnToken::VariationEnd),
+            '$' => {
+                let mut nag = String::new();
+                while let Some(x) = chars.peek() {
+                    if !x.is_ascii_digit() {
+                        break;
+                    }
+                    nag.push(*x);
+                    chars.next();
+                }
+                tokens.push(PgnToken::Nag(nag.parse().map_err(|_| Error::InvalidPgn)?));
+            }
+            x if x.is_whitespace() => {}
+            x => {
+                let mut symbol = x.to_string();
+                while let Some(x) = chars.peek() {
+                    if x.is_whitespace() || "[]{}();$".contains(*x) {
+                        break;
+                    }
+                    symbol.push(*x);
+                    chars.next();
+                }
+                tokens.push(PgnToken::Symbol(symbol));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read the rest of a tag pair, after the opening `[`.
+fn read_tag(chars: &mut Peekable<Chars>) -> Result<PgnToken, Error> {
+    let mut name = String::new();
+    while let Some(x) = chars.peek() {
+        if x.is_whitespace() || *x == '"' || *x == ']' {
+            break;
+        }
+        name.push(*x);
+        chars.next();
+    }
+
+    while chars.peek().is_some_and(|x| x.is_whitespace()) {
+        chars.next();
+    }
+    if chars.next() != Some('"') {
+        return Err(Error::InvalidPgn);
+    }
+
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('\\') => value.push(chars.next().ok_or(Error::InvalidPgn)?),
+            Some('"') => break,
+            Some(x) => value.push(x),
+            None => return Err(Error::InvalidPgn),
+        }
+    }
+
+    while chars.peek().is_some_and(|x| x.is_whitespace()) {
+        chars.next();
+    }
+    if chars.next() != Some(']') || name.is_empty() {
+        return Err(Error::InvalidPgn);
+    }
+
+    Ok(PgnToken::Tag(name, value))
+}
+
+#[test]
+fn test_tokenize() {
+    let tokens = tokenize(
+        "[Event \"Casual \\\"Game\\\"\"]\n\n1. e4 {best by test} e5 $1 (1... c5) 2.Nf3 ; done\n1-0",
+    )
+    .unwrap();
+
+    assert_eq!(
+        tokens,
+        vec![
+            PgnToken::Tag("Event".to_string(), "Casual \"Game\"".to_string()),
+            PgnToken::Symbol("1.".to_string()),
+            PgnToken::Symbol("e4".to_string()),
+            PgnToken::Comment("best by test".to_string()),
+            PgnToken::Symbol("e5".to_string()),
+            PgnToken::Nag(1),
+            PgnToken::VariationStart,
+            PgnToken::Symbol("1...".to_string()),
+            PgnToken::Symbol("c5".to_string()),
+            PgnToken::VariationEnd,
+            PgnToken::Symbol("2.Nf3".to_string()),
+            PgnToken::Comment("done".to_string()),
+            PgnToken::Symbol("1-0".to_string()),
+        ]
+    );
+
+    assert!(tokenize("[Event \"Unterminated").is_err());
+    assert!(tokenize("1. e4 {Unterminated").is_err());
+}
+
+#[test]
+fn test_clock_annotations() {
+    assert_eq!(parse_clock("[%clk 0:01:30]"), Some(Duration::from_secs(90)));
+    assert_eq!(
+        parse_clock("Good move [%clk 1:00:05.5] [%emt 0:00:02]"),
+        Some(Duration::from_millis(3_605_500))
+    );
+    assert_eq!(parse_clock("no clock here"), None);
+    assert_eq!(parse_clock("[%clk 0:75:00]"), None);
+    assert_eq!(parse_clock("[%clk 99999999999999999:00:00]"), None);
+    assert_eq!(parse_clock("[%clk 0:00:1e300]"), None);
+
+    assert_eq!(format_clock(Duration::from_secs(90)), "{[%clk 0:01:30]}");
+    assert_eq!(
+        format_clock(Duration::from_millis(3_605_500)),
+        "{[%clk 1:00:05.5]}"
+    );
+}
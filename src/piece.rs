@@ -28,8 +28,19 @@ pub const ALL_PIECES: [Piece; NUM_PIECES] = [
 /// How many ways can I promote?
 pub const NUM_PROMOTION_PIECES: usize = 4;
 
-/// What pieces can I promote to?
-pub const PROMOTION_PIECES: [Piece; 4] = [Piece::Queen, Piece::Knight, Piece::Rook, Piece::Bishop];
+/// What pieces can I promote to?  These are ordered from most to least valuable, so the queen
+/// comes first.  `MoveGen` generates promotions in this order, which puts the promotion that is
+/// almost always best at the front for move ordering.
+///
+/// ```
+/// use chess::{Piece, PROMOTION_PIECES};
+///
+/// assert_eq!(
+///     PROMOTION_PIECES,
+///     [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight]
+/// );
+/// ```
+pub const PROMOTION_PIECES: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
 
 impl Piece {
     /// Convert the `Piece` to a `usize` for table lookups.
@@ -38,6 +49,28 @@ impl Piece {
         *self as usize
     }
 
+    /// The conventional material value of this piece, in centipawns.  The king has no material
+    /// value, as it can never be traded off.
+    ///
+    /// ```
+    /// use chess::Piece;
+    ///
+    /// assert_eq!(Piece::Pawn.value(), 100);
+    /// assert_eq!(Piece::Queen.value(), 900);
+    /// assert_eq!(Piece::King.value(), 0);
+    /// ```
+    #[inline]
+    pub fn value(&self) -> i32 {
+        match *self {
+            Piece::Pawn => 100,
+            Piece::Knight => 300,
+            Piece::Bishop => 300,
+            Piece::Rook => 500,
+            Piece::Queen => 900,
+            Piece::King => 0,
+        }
+    }
+
     /// Convert a piece with a color to a string.  White pieces are uppercase, black pieces are
     /// lowercase.
     ///
@@ -74,3 +107,37 @@ impl fmt::Display for Piece {
         )
     }
 }
+
+/// A value for each type of piece, such as one of the two sets of piece values a tapered
+/// evaluation blends between.  Index it with `Piece::to_index()`.
+///
+/// ```
+/// use chess::{Piece, PieceValues};
+///
+/// let values = PieceValues::default();
+/// assert_eq!(values.0[Piece::Rook.to_index()], Piece::Rook.value());
+///
+/// let endgame = PieceValues([120, 280, 300, 520, 950, 0]);
+/// assert_eq!(endgame.get(Piece::Pawn), 120);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PieceValues(pub [i32; NUM_PIECES]);
+
+impl PieceValues {
+    /// The value of `piece`.
+    #[inline]
+    pub fn get(&self, piece: Piece) -> i32 {
+        self.0[piece.to_index()]
+    }
+}
+
+impl Default for PieceValues {
+    /// The conventional values from `Piece::value`.
+    fn default() -> PieceValues {
+        let mut values = [0; NUM_PIECES];
+        for piece in ALL_PIECES.iter() {
+            values[piece.to_index()] = piece.value();
+        }
+        PieceValues(values)
+    }
+}
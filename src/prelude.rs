@@ -0,0 +1,37 @@
+//! The types and functions most programs need, in one place.
+//!
+//! ```
+//! use chess::prelude::*;
+//! use std::str::FromStr;
+//!
+//! let board = Board::default();
+//! assert_eq!(MoveGen::new_legal(&board).len(), 20);
+//!
+//! let m = ChessMove::new(Square::G1, Square::F3, None);
+//! assert_eq!(board.piece_on(m.get_source()), Some(Piece::Knight));
+//! assert_eq!(board.color_on(m.get_source()), Some(Color::White));
+//!
+//! let attacks = get_knight_moves(Square::make_square(Rank::Third, File::F));
+//! assert_ne!(attacks & board.color_combined(Color::White), EMPTY);
+//!
+//! let board = Board::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+//! assert_eq!(board.status(), BoardStatus::Ongoing);
+//! ```
+
+pub use crate::bitboard::{BitBoard, EMPTY};
+pub use crate::board::{Board, BoardStatus};
+pub use crate::board_builder::BoardBuilder;
+pub use crate::castle_rights::CastleRights;
+pub use crate::chess_move::ChessMove;
+pub use crate::color::{Color, ALL_COLORS};
+pub use crate::error::Error;
+pub use crate::file::{File, ALL_FILES};
+pub use crate::game::{Game, GameResult};
+pub use crate::magic::{
+    between, get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves,
+    line,
+};
+pub use crate::movegen::MoveGen;
+pub use crate::piece::{Piece, ALL_PIECES};
+pub use crate::rank::{Rank, ALL_RANKS};
+pub use crate::square::{Square, ALL_SQUARES};
@@ -1,8 +1,12 @@
+use crate::bitboard::BitBoard;
 use crate::error::Error;
 use std::mem::transmute;
 use std::str::FromStr;
 
 /// Describe a rank (row) on a chess board
+///
+/// The numeric values are stable, and safe to rely on across FFI: `First` is 0, up to `Eighth`,
+/// which is 7.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Debug, Hash)]
 pub enum Rank {
     First,
@@ -31,6 +35,21 @@ pub const ALL_RANKS: [Rank; NUM_RANKS] = [
 ];
 
 impl Rank {
+    /// Iterate over every rank, from `First` to `Eighth`.  Use `.rev()` to go from `Eighth` to
+    /// `First`, which is handy for drawing a board from white's point of view.
+    ///
+    /// ```
+    /// use chess::Rank;
+    ///
+    /// assert_eq!(Rank::iter().next(), Some(Rank::First));
+    /// assert_eq!(Rank::iter().rev().next(), Some(Rank::Eighth));
+    /// assert_eq!(Rank::iter().count(), 8);
+    /// ```
+    #[inline]
+    pub fn iter() -> impl DoubleEndedIterator<Item = Rank> + ExactSizeIterator {
+        ALL_RANKS.iter().copied()
+    }
+
     /// Convert a `usize` into a `Rank` (the inverse of to_index).  If the number is > 7, wrap
     /// around.
     #[inline]
@@ -38,6 +57,26 @@ impl Rank {
         unsafe { transmute((i as u8) & 7) }
     }
 
+    /// Convert a `usize` into a `Rank`, returning an error instead of wrapping if the number
+    /// is > 7.
+    ///
+    /// ```
+    /// use chess::Rank;
+    ///
+    /// assert_eq!(Rank::checked_from_index(0).unwrap(), Rank::First);
+    /// assert_eq!(Rank::checked_from_index(7).unwrap(), Rank::Eighth);
+    /// assert!(Rank::checked_from_index(8).is_err());
+    /// assert!(Rank::checked_from_index(usize::max_value()).is_err());
+    /// ```
+    #[inline]
+    pub fn checked_from_index(i: usize) -> Result<Rank, Error> {
+        if i < NUM_RANKS {
+            Ok(Rank::from_index(i))
+        } else {
+            Err(Error::InvalidRank)
+        }
+    }
+
     /// Go one rank down.  If impossible, wrap around.
     #[inline]
     pub fn down(&self) -> Rank {
@@ -55,6 +94,47 @@ impl Rank {
     pub fn to_index(&self) -> usize {
         *self as usize
     }
+
+    /// Convert this `Rank` into a `u8` from 0 (`First`) to 7 (`Eighth`) inclusive.
+    ///
+    /// ```
+    /// use chess::Rank;
+    ///
+    /// assert_eq!(Rank::Third.to_u8(), 2);
+    /// ```
+    #[inline]
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Every square on this `Rank`, the same as `get_rank(self)`.
+    ///
+    /// ```
+    /// use chess::{get_rank, Rank};
+    ///
+    /// for rank in Rank::iter() {
+    ///     assert_eq!(rank.bitboard().popcnt(), 8);
+    ///     assert_eq!(rank.bitboard(), get_rank(rank));
+    /// }
+    /// ```
+    #[inline]
+    pub fn bitboard(self) -> BitBoard {
+        BitBoard::from_rank(self)
+    }
+
+    /// How many ranks apart are these two ranks?
+    ///
+    /// ```
+    /// use chess::Rank;
+    ///
+    /// assert_eq!(Rank::First.distance(Rank::Eighth), 7);
+    /// assert_eq!(Rank::Fifth.distance(Rank::Third), 2);
+    /// assert_eq!(Rank::Fourth.distance(Rank::Fourth), 0);
+    /// ```
+    #[inline]
+    pub fn distance(self, other: Rank) -> u8 {
+        (self.to_u8() as i8 - other.to_u8() as i8).unsigned_abs()
+    }
 }
 
 impl FromStr for Rank {
@@ -77,3 +157,15 @@ impl FromStr for Rank {
         }
     }
 }
+
+#[test]
+fn rank_layout_is_stable() {
+    assert_eq!(Rank::First.to_u8(), 0);
+    assert_eq!(Rank::Second.to_u8(), 1);
+    assert_eq!(Rank::Third.to_u8(), 2);
+    assert_eq!(Rank::Fourth.to_u8(), 3);
+    assert_eq!(Rank::Fifth.to_u8(), 4);
+    assert_eq!(Rank::Sixth.to_u8(), 5);
+    assert_eq!(Rank::Seventh.to_u8(), 6);
+    assert_eq!(Rank::Eighth.to_u8(), 7);
+}
@@ -1,4 +1,5 @@
 use crate::color::Color;
+use crate::direction::Direction;
 use crate::error::Error;
 use crate::file::File;
 use crate::rank::Rank;
@@ -12,6 +13,14 @@ pub struct Square(u8);
 /// How many squares are there?
 pub const NUM_SQUARES: usize = 64;
 
+/// The name of every square, indexed by `Square::to_index()`.
+const SQUARE_NAMES: [&str; NUM_SQUARES] = [
+    "a1", "b1", "c1", "d1", "e1", "f1", "g1", "h1", "a2", "b2", "c2", "d2", "e2", "f2", "g2", "h2",
+    "a3", "b3", "c3", "d3", "e3", "f3", "g3", "h3", "a4", "b4", "c4", "d4", "e4", "f4", "g4", "h4",
+    "a5", "b5", "c5", "d5", "e5", "f5", "g5", "h5", "a6", "b6", "c6", "d6", "e6", "f6", "g6", "h6",
+    "a7", "b7", "c7", "d7", "e7", "f7", "g7", "h7", "a8", "b8", "c8", "d8", "e8", "f8", "g8", "h8",
+];
+
 impl Default for Square {
     /// Create a square on A1.
     ///
@@ -50,6 +59,25 @@ impl Square {
         Square(sq)
     }
 
+    /// Create a new square, given an index, returning an error if the index is >= 64.
+    ///
+    /// ```
+    /// use chess::Square;
+    ///
+    /// assert_eq!(Square::checked_new(0).unwrap(), Square::A1);
+    /// assert_eq!(Square::checked_new(63).unwrap(), Square::H8);
+    /// assert!(Square::checked_new(64).is_err());
+    /// assert!(Square::checked_new(255).is_err());
+    /// ```
+    #[inline]
+    pub fn checked_new(sq: u8) -> Result<Square, Error> {
+        if (sq as usize) < NUM_SQUARES {
+            Ok(Square(sq))
+        } else {
+            Err(Error::InvalidSquare)
+        }
+    }
+
     /// Make a square given a rank and a file
     ///
     /// ```
@@ -100,6 +128,63 @@ impl Square {
         File::from_index((self.0 & 7) as usize)
     }
 
+    /// The color of this square on the board: `Color::White` for a light square, and
+    /// `Color::Black` for a dark one.  a1 is dark.
+    ///
+    /// ```
+    /// use chess::{Color, Square};
+    ///
+    /// assert_eq!(Square::A1.color(), Color::Black);
+    /// assert_eq!(Square::H1.color(), Color::White);
+    /// assert_eq!(Square::D1.color(), Color::White);
+    /// assert_eq!(Square::D8.color(), Color::Black);
+    /// ```
+    #[inline]
+    pub fn color(&self) -> Color {
+        if ((self.0 >> 3) + self.0) & 1 == 0 {
+            Color::Black
+        } else {
+            Color::White
+        }
+    }
+
+    /// The letter of this square's file, from 'a' to 'h'.
+    ///
+    /// ```
+    /// use chess::Square;
+    ///
+    /// assert_eq!(Square::E4.file_char(), 'e');
+    /// ```
+    #[inline]
+    pub fn file_char(&self) -> char {
+        (b'a' + (self.0 & 7)) as char
+    }
+
+    /// The digit of this square's rank, from '1' to '8'.
+    ///
+    /// ```
+    /// use chess::Square;
+    ///
+    /// assert_eq!(Square::E4.rank_char(), '4');
+    /// ```
+    #[inline]
+    pub fn rank_char(&self) -> char {
+        (b'1' + (self.0 >> 3)) as char
+    }
+
+    /// The name of this square in algebraic notation, without allocating.
+    ///
+    /// ```
+    /// use chess::Square;
+    ///
+    /// assert_eq!(Square::default().as_str(), "a1");
+    /// assert_eq!(Square::E4.as_str(), "e4");
+    /// ```
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        SQUARE_NAMES[self.to_index()]
+    }
+
     /// If there is a square above me, return that.  Otherwise, None.
     ///
     /// ```
@@ -183,6 +268,43 @@ impl Square {
         }
     }
 
+    /// Move `file_delta` files to the right and `rank_delta` ranks up.  If that goes off the
+    /// board, return None.
+    ///
+    /// ```
+    /// use chess::Square;
+    ///
+    /// assert_eq!(Square::B1.offset(1, 2), Some(Square::C3));
+    /// assert_eq!(Square::B1.offset(-2, 1), None);
+    /// ```
+    #[inline]
+    pub fn offset(&self, file_delta: i8, rank_delta: i8) -> Option<Square> {
+        let file = self.get_file().to_index() as i8 + file_delta;
+        let rank = self.get_rank().to_index() as i8 + rank_delta;
+        if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+            None
+        } else {
+            Some(Square::make_square(
+                Rank::from_index(rank as usize),
+                File::from_index(file as usize),
+            ))
+        }
+    }
+
+    /// Take one step in `direction`.  If that goes off the board, return None.
+    ///
+    /// ```
+    /// use chess::{Direction, Square};
+    ///
+    /// assert_eq!(Square::E4.step(Direction::NorthWest), Some(Square::D5));
+    /// assert_eq!(Square::H4.step(Direction::East), None);
+    /// ```
+    #[inline]
+    pub fn step(&self, direction: Direction) -> Option<Square> {
+        let (file_delta, rank_delta) = direction.offset();
+        self.offset(file_delta, rank_delta)
+    }
+
     /// If there is a square "forward", given my `Color`, go in that direction.  Otherwise, None.
     ///
     /// ```
@@ -342,6 +464,24 @@ impl Square {
         }
     }
 
+    /// Reflect this square across the a1-h8 diagonal, by swapping its file and rank.  Squares on
+    /// that diagonal stay where they are.
+    ///
+    /// ```
+    /// use chess::Square;
+    ///
+    /// assert_eq!(Square::B1.flip_anti_diagonal(), Square::A2);
+    /// assert_eq!(Square::H4.flip_anti_diagonal(), Square::D8);
+    /// assert_eq!(Square::C3.flip_anti_diagonal(), Square::C3);
+    /// ```
+    #[inline]
+    pub fn flip_anti_diagonal(self) -> Square {
+        Square::make_square(
+            Rank::from_index(self.get_file().to_index()),
+            File::from_index(self.get_rank().to_index()),
+        )
+    }
+
     /// Convert this square to an integer.
     ///
     /// ```
@@ -353,10 +493,30 @@ impl Square {
     /// assert_eq!(Square::make_square(Rank::Eighth, File::H).to_int(), 63);
     /// ```
     #[inline]
-    pub fn to_int(&self) -> u8 {
+    pub const fn to_int(&self) -> u8 {
         self.0
     }
 
+    /// Parse a square name such as "e4" at compile time, for the `sq!` and `bb!` macros.  An
+    /// invalid name fails to compile (or panics, outside of a const context).
+    #[doc(hidden)]
+    pub const fn from_literal(name: &str) -> Square {
+        let bytes = name.as_bytes();
+        if bytes.len() != 2 {
+            panic!("a square is a file (a-h) followed by a rank (1-8)");
+        }
+        let file = match bytes[0] {
+            b'a'..=b'h' => bytes[0] - b'a',
+            b'A'..=b'H' => bytes[0] - b'A',
+            _ => panic!("a square's file must be a-h"),
+        };
+        let rank = match bytes[1] {
+            b'1'..=b'8' => bytes[1] - b'1',
+            _ => panic!("a square's rank must be 1-8"),
+        };
+        Square(rank << 3 ^ file)
+    }
+
     /// Convert this `Square` to a `usize` for table lookup purposes
     ///
     /// ```
@@ -968,12 +1128,7 @@ impl Square {
 
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}{}",
-            (('a' as u8) + ((self.0 & 7) as u8)) as char,
-            (('1' as u8) + ((self.0 >> 3) as u8)) as char
-        )
+        write!(f, "{}{}", self.file_char(), self.rank_char())
     }
 }
 
@@ -981,10 +1136,10 @@ impl FromStr for Square {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 2 {
+        let ch: Vec<char> = s.chars().collect();
+        if ch.len() < 2 {
             return Err(Error::InvalidSquare);
         }
-        let ch: Vec<char> = s.chars().collect();
         match ch[0] {
             'a' | 'b' | 'c' | 'd' | 'e' | 'f' | 'g' | 'h' => {}
             _ => {
@@ -1085,3 +1240,39 @@ pub const ALL_SQUARES: [Square; 64] = [
     Square(62),
     Square(63),
 ];
+
+#[test]
+fn as_str_matches_display() {
+    for sq in ALL_SQUARES.iter() {
+        assert_eq!(sq.as_str(), sq.to_string());
+    }
+}
+
+#[test]
+fn flip_anti_diagonal() {
+    assert_eq!(Square::B1.flip_anti_diagonal(), Square::A2);
+    assert_eq!(Square::A2.flip_anti_diagonal(), Square::B1);
+    assert_eq!(Square::C3.flip_anti_diagonal(), Square::C3);
+
+    for sq in ALL_SQUARES.iter() {
+        assert_eq!(sq.flip_anti_diagonal().flip_anti_diagonal(), *sq);
+    }
+}
+
+#[test]
+fn square_color() {
+    let light = ALL_SQUARES
+        .iter()
+        .filter(|sq| sq.color() == Color::White)
+        .count();
+    assert_eq!(light, 32);
+
+    for sq in ALL_SQUARES.iter() {
+        if let Some(right) = sq.right() {
+            assert_ne!(sq.color(), right.color());
+        }
+        if let Some(up) = sq.up() {
+            assert_ne!(sq.color(), up.color());
+        }
+    }
+}